@@ -1,9 +1,25 @@
+pub mod diff;
 pub mod discover;
+pub mod history;
 pub mod info;
 pub mod listen;
+pub mod monitor;
 pub mod scan;
 
-use clap::{ArgAction, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+use zond_common::history::HistoryStore;
+use zond_common::models::port::{PortSet, Protocol};
+use zond_common::models::probe::DiscoveryProbe;
+use zond_common::models::target;
+use zond_common::utils::timing;
+
+use crate::output::{
+    OutputFormat, Outputs,
+    alerts::{AlertSinks, Webhook},
+};
 
 #[derive(Parser)]
 #[command(name = "zond")]
@@ -20,7 +36,7 @@ pub struct CommandLine {
     #[arg(short = 'n', long = "no-dns", global = true)]
     pub no_dns: bool,
 
-    /// Reduce UI visual density (-q: reduce styling, -qq: raw IPs)
+    /// Reduce UI visual density (-q: reduce styling, -qq: one tab separated line per host)
     #[arg(short = 'q', long = "quiet", action = ArgAction::Count, global = true)]
     pub quiet: u8,
 
@@ -28,9 +44,33 @@ pub struct CommandLine {
     #[arg(long = "redact", global = true)]
     pub redact: bool,
 
+    /// Maximum number of concurrent connection attempts (unprivileged scans)
+    #[arg(long = "max-parallelism", value_name = "N", global = true)]
+    pub max_parallelism: Option<usize>,
+
     /// Increase logging detail (-v: debug logs, -vv: full packets)
     #[arg(short = 'v', long = "verbose", action = ArgAction::Count, global = true)]
     pub verbosity: u8,
+
+    /// Print the results to stdout in a structured format instead of the tree view
+    #[arg(long = "output", value_name = "FORMAT", value_enum, global = true)]
+    pub output: Option<OutputFormat>,
+
+    /// Print one JSON line per scan event to stdout while scanning
+    #[arg(long = "stream", global = true, conflicts_with = "output")]
+    pub stream: bool,
+
+    /// Also write the results as JSON to a file, also -oJ ('-' for stdout)
+    #[arg(long = "output-json", value_name = "FILE", global = true)]
+    pub output_json: Option<PathBuf>,
+
+    /// Also write the results as nmap XML to a file, also -oX ('-' for stdout)
+    #[arg(long = "output-xml", value_name = "FILE", global = true)]
+    pub output_xml: Option<PathBuf>,
+
+    /// Do not record this run in the scan history
+    #[arg(long = "no-history", global = true)]
+    pub no_history: bool,
 }
 
 #[derive(Subcommand)]
@@ -40,25 +80,208 @@ pub enum Commands {
     Info,
     /// Enumerate a network passively
     #[command(alias = "l")]
-    Listen,
-    
+    Listen {
+        /// Interface to listen on (defaults to the primary LAN interface)
+        #[arg(short = 'i', long = "interface", value_name = "IFACE")]
+        interface: Option<String>,
+
+        /// Stop after this long (e.g. 90s, 5m, 1h); runs until 'q' otherwise
+        #[arg(short = 'd', long = "duration", value_name = "DURATION", value_parser = timing::parse_duration)]
+        duration: Option<Duration>,
+    },
+
     /// Discover hosts in a given network
     #[command(alias = "d")]
-    Discover { 
-        #[arg(value_name = "TARGETS", num_args(1..))]
-        targets: Vec<String> 
+    Discover {
+        #[command(flatten)]
+        targets: TargetArgs,
+
+        #[command(flatten)]
+        ports: PortArgs,
+
+        /// Discovery probe, repeatable (-PS/-PA/-PU[ports]: TCP SYN/ACK, UDP | -PE/-PP/-PM: ICMP echo/timestamp/mask | -PR: ARP | -PN: NDP)
+        #[arg(short = 'P', value_name = "PROBE", action = ArgAction::Append)]
+        probes: Vec<DiscoveryProbe>,
     },
-    
+
     /// Scan one or more hosts
     #[command(alias = "s")]
-    Scan { 
-        #[arg(value_name = "TARGETS", num_args(1..))]
-        targets: Vec<String> 
+    Scan {
+        #[command(flatten)]
+        targets: TargetArgs,
+
+        #[command(flatten)]
+        ports: PortArgs,
+
+        /// Scan the most common UDP ports instead of TCP ones (ignored with -p)
+        #[arg(short = 'U', long = "udp")]
+        udp: bool,
+
+        /// Identify services and versions behind open TCP ports
+        #[arg(short = 'S', long = "services")]
+        services: bool,
+
+        /// Extra service signatures, tried before the embedded ones
+        #[arg(long = "signatures", value_name = "FILE", requires = "services")]
+        signatures: Option<PathBuf>,
+    },
+
+    /// Show how a device changed across recorded runs, or list the runs
+    History {
+        /// IP or MAC address of the device
+        #[arg(value_name = "IP|MAC")]
+        host: Option<String>,
+    },
+
+    /// Compare two runs and show new, gone and changed hosts
+    Diff {
+        /// JSON report or @N for run N of the history (defaults to the second latest run)
+        #[arg(value_name = "OLD")]
+        old: Option<String>,
+
+        /// JSON report or @N for run N of the history (defaults to the latest run, @-1)
+        #[arg(value_name = "NEW")]
+        new: Option<String>,
+    },
+
+    /// Re-discover a network on a schedule and alert when hosts join, leave or change
+    #[command(alias = "m")]
+    Monitor {
+        #[command(flatten)]
+        targets: TargetArgs,
+
+        /// Also scan these ports on every host up and alert when new ones open
+        #[command(flatten)]
+        ports: PortArgs,
+
+        /// Discovery probe, repeatable (same as for discover)
+        #[arg(short = 'P', value_name = "PROBE", action = ArgAction::Append)]
+        probes: Vec<DiscoveryProbe>,
+
+        /// Time between rounds (e.g. 90s, 5m, 1h)
+        #[arg(long = "interval", value_name = "DURATION", default_value = "5m", value_parser = timing::parse_duration)]
+        interval: Duration,
+
+        #[command(flatten)]
+        alerts: AlertArgs,
     },
 }
 
+#[derive(Args)]
+pub struct TargetArgs {
+    #[arg(value_name = "TARGETS", required_unless_present = "input_file")]
+    pub targets: Vec<String>,
+
+    /// Read targets from a file, also -iL ('-' for stdin, '#' starts a comment)
+    #[arg(long = "input-file", value_name = "FILE")]
+    pub input_file: Option<PathBuf>,
+
+    /// Skip these targets (same syntax as TARGETS, comma-separated)
+    #[arg(long = "exclude", value_name = "TARGETS", action = ArgAction::Append)]
+    pub exclude: Vec<String>,
+
+    /// Skip the targets listed in a file ('-' for stdin)
+    #[arg(long = "exclude-file", value_name = "FILE")]
+    pub exclude_file: Option<PathBuf>,
+}
+
+impl TargetArgs {
+    /// All target and exclusion specs, with the files read.
+    pub fn into_specs(self) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+        let is_stdin = |path: &Option<PathBuf>| path.as_deref() == Some(Path::new("-"));
+        anyhow::ensure!(
+            !(is_stdin(&self.input_file) && is_stdin(&self.exclude_file)),
+            "--input-file and --exclude-file cannot both read from stdin"
+        );
+
+        let mut targets: Vec<String> = self.targets;
+        if let Some(path) = &self.input_file {
+            targets.extend(target::read_specs(path)?);
+        }
+
+        let mut excludes: Vec<String> = self.exclude;
+        if let Some(path) = &self.exclude_file {
+            excludes.extend(target::read_specs(path)?);
+        }
+
+        Ok((targets, excludes))
+    }
+}
+
+#[derive(Args)]
+pub struct PortArgs {
+    /// Ports to probe (e.g. 22,80,443 | 1-1024 | - | T:80,U:53 | http,ssh)
+    #[arg(
+        short = 'p',
+        long = "ports",
+        value_name = "PORTS",
+        conflicts_with = "top_ports"
+    )]
+    pub ports: Option<PortSet>,
+
+    /// Probe the N most common ports
+    #[arg(long = "top-ports", value_name = "N")]
+    pub top_ports: Option<usize>,
+}
+
+impl PortArgs {
+    /// The ports requested by the user, if any. `--top-ports` picks from `protocol`.
+    pub fn into_port_set(self, protocol: Protocol) -> Option<PortSet> {
+        self.ports
+            .or_else(|| self.top_ports.map(|n| PortSet::top(n, protocol)))
+    }
+}
+
+#[derive(Args)]
+pub struct AlertArgs {
+    /// Append every alert as a JSON line to a file
+    #[arg(long = "alerts-file", value_name = "FILE")]
+    pub alerts_file: Option<PathBuf>,
+
+    /// Run a shell command per alert, with the alert as JSON on stdin and its kind in $ZOND_EVENT
+    #[arg(long = "hook", value_name = "COMMAND")]
+    pub hook: Option<String>,
+
+    /// POST every alert as JSON to a webhook on this machine (e.g. http://127.0.0.1:9000/zond)
+    #[arg(long = "webhook", value_name = "URL", value_parser = Webhook::parse)]
+    pub webhook: Option<Webhook>,
+}
+
+impl AlertArgs {
+    pub fn into_sinks(self) -> AlertSinks {
+        AlertSinks {
+            stdout: false,
+            file: self.alerts_file,
+            hook: self.hook,
+            webhook: self.webhook,
+        }
+    }
+}
+
 impl CommandLine {
     pub fn parse_args() -> Self {
-        Self::parse()
+        // nmap spells these flags `-iL`, `-oJ` and `-oX`, which clap cannot express as short flags
+        Self::parse_from(std::env::args().map(|arg| match arg.as_str() {
+            "-iL" => "--input-file".to_string(),
+            "-oJ" => "--output-json".to_string(),
+            "-oX" => "--output-xml".to_string(),
+            _ => arg,
+        }))
     }
-}
\ No newline at end of file
+
+    /// The reports requested with `--output`, `--stream`, `-oJ` and `-oX`, plus the history.
+    pub fn outputs(&self) -> anyhow::Result<Outputs> {
+        let outputs: Outputs = Outputs {
+            format: self.output,
+            json_file: self.output_json.clone(),
+            xml_file: self.output_xml.clone(),
+            stream: self.stream,
+            history: match self.no_history {
+                true => None,
+                false => HistoryStore::open_default(),
+            },
+        };
+        outputs.ensure_single_stdout()?;
+        Ok(outputs)
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{self, Context, bail};
+use colored::*;
+use zond_common::diff::{DiffStatus, HostDiff, ReportDiff, RunInfo};
+use zond_common::history::HistoryStore;
+use zond_common::models::report::Report;
+use zond_common::{config::Config, success, warn};
+
+use crate::{
+    mprint,
+    output::{OutputFormat, Outputs, json},
+    terminal::{
+        colors, format,
+        host_fmt::Detail,
+        print::{self, GLOBAL_KEY_WIDTH},
+    },
+};
+
+/// Compares two runs, each a JSON report file or `@N` for run N of the history
+/// (`@-1` is the latest). Missing runs default to the latest two in the history.
+pub fn diff(
+    old: Option<&str>,
+    new: Option<&str>,
+    cfg: &Config,
+    outputs: &Outputs,
+) -> anyhow::Result<()> {
+    let mut history: Option<Vec<Report>> = None;
+    let new_report: Report = load_run(new.unwrap_or("@-1"), &mut history)?;
+    let old_report: Report = match old {
+        Some(old) => load_run(old, &mut history)?,
+        None => load_run("@-2", &mut history)?,
+    };
+
+    let diff: ReportDiff = ReportDiff::new(&old_report, &new_report);
+
+    if outputs.xml_file.is_some()
+        || outputs
+            .format
+            .is_some_and(|format| format != OutputFormat::Json)
+    {
+        warn!("Skipping the requested report, diff only writes JSON");
+    }
+    if outputs.format == Some(OutputFormat::Json) {
+        return json::write_stdout(&diff);
+    }
+
+    print_diff(&diff, cfg);
+    if let Some(path) = &outputs.json_file {
+        json::write_file(&diff, path)?;
+    }
+    Ok(())
+}
+
+/// Reads a JSON report, or picks a run from the history for `@N`.
+fn load_run(spec: &str, history: &mut Option<Vec<Report>>) -> anyhow::Result<Report> {
+    let Some(index) = spec.strip_prefix('@') else {
+        let path: &Path = Path::new(spec);
+        let content: String = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read '{}'", path.display()))?;
+        return serde_json::from_str(&content)
+            .with_context(|| format!("'{}' is not a zond JSON report", path.display()));
+    };
+
+    let index: isize = index
+        .parse()
+        .with_context(|| format!("'{spec}' is not a run number (e.g. @3 or @-1)"))?;
+    if history.is_none() {
+        let store: HistoryStore = HistoryStore::open_default()
+            .context("Cannot locate the history, neither $XDG_DATA_HOME nor $HOME is set")?;
+        *history = Some(store.load()?);
+    }
+    let runs: &[Report] = history.as_deref().unwrap_or_default();
+
+    let position: Option<usize> = match index {
+        0.. => Some(index as usize),
+        _ => runs.len().checked_sub(index.unsigned_abs()),
+    };
+    match position.and_then(|position| runs.get(position)) {
+        Some(report) => Ok(report.clone()),
+        None => bail!(
+            "Run {spec} is not in the history ({} runs recorded, see 'zond history')",
+            runs.len()
+        ),
+    }
+}
+
+fn print_diff(diff: &ReportDiff, cfg: &Config) {
+    print::header("Scan Diff", cfg.quiet);
+    GLOBAL_KEY_WIDTH.set(3);
+    print::aligned_line("Old", run_line(&diff.old));
+    print::aligned_line("New", run_line(&diff.new));
+
+    if diff.is_empty() {
+        mprint!();
+        success!("No hosts appeared, disappeared or changed");
+        return;
+    }
+
+    for (idx, host) in diff.hosts.iter().enumerate() {
+        mprint!();
+        print::tree_head(idx, &host.ip.to_string());
+        print::as_tree(host_details(host, cfg));
+    }
+    print_summary(diff, cfg);
+}
+
+fn run_line(run: &RunInfo) -> String {
+    format!(
+        "{} ({}, {} hosts up)",
+        format::local_time(run.started_at),
+        run.command,
+        run.hosts_up
+    )
+}
+
+fn host_details(host: &HostDiff, cfg: &Config) -> Vec<Detail> {
+    let status: ColoredString = match host.status {
+        DiffStatus::New => "new".green().bold(),
+        DiffStatus::Gone => "gone".red().bold(),
+        DiffStatus::Changed => "changed".yellow().bold(),
+    };
+    let mut details: Vec<Detail> = vec![("Status".to_string(), status)];
+
+    if let Some(previous_ip) = host.previous_ip {
+        details.push((
+            "Moved".to_string(),
+            format!("from {previous_ip}").color(colors::IPV4_ADDR),
+        ));
+    }
+    if let Some(mac) = &host.mac {
+        let mac: String = format::mac_str(mac, cfg);
+        let value: ColoredString = match &host.previous_mac {
+            // The same IP answering with another MAC is what ARP spoofing looks like
+            Some(previous) => format!("{mac} (was {})", format::mac_str(previous, cfg))
+                .red()
+                .bold(),
+            None => mac.color(colors::MAC_ADDR),
+        };
+        details.push(("MAC".to_string(), value));
+    }
+    if let Some(vendor) = &host.vendor {
+        details.push(("Vendor".to_string(), vendor.color(colors::MAC_ADDR)));
+    }
+    for claim in &host.claims {
+        let others: Vec<String> = claim
+            .other_macs
+            .iter()
+            .map(|mac| format::mac_str(mac, cfg))
+            .collect();
+        details.push((
+            "Claims".to_string(),
+            format!("{} (held by {})", claim.ip, others.join(", "))
+                .red()
+                .bold(),
+        ));
+    }
+    if let Some(hostname) = &host.hostname {
+        let hostname: String = format::hostname_str(hostname, cfg);
+        let value: String = match &host.previous_hostname {
+            Some(previous) => format!("{hostname} (was {})", format::hostname_str(previous, cfg)),
+            None => hostname,
+        };
+        details.push(("Hostname".to_string(), value.color(colors::HOSTNAME)));
+    }
+    if !host.opened.is_empty() {
+        details.push(("Opened".to_string(), host.opened.join(", ").green()));
+    }
+    if !host.closed.is_empty() {
+        details.push(("Closed".to_string(), host.closed.join(", ").red()));
+    }
+    details
+}
+
+fn print_summary(diff: &ReportDiff, cfg: &Config) {
+    let new: ColoredString = format!("{} new", diff.count(DiffStatus::New))
+        .bold()
+        .green();
+    let gone: ColoredString = format!("{} gone", diff.count(DiffStatus::Gone))
+        .bold()
+        .red();
+    let changed: ColoredString = format!("{} changed", diff.count(DiffStatus::Changed))
+        .bold()
+        .yellow();
+    let output: &ColoredString =
+        &format!("Diff Complete: {new}, {gone}, {changed}").color(colors::TEXT_DEFAULT);
+
+    match cfg.quiet {
+        0 => {
+            print::fat_separator();
+            print::centerln(output);
+        }
+        _ => {
+            mprint!();
+            success!("{output}")
+        }
+    }
+}
@@ -1,50 +1,67 @@
-use std::time::{Duration, Instant};
-use std::{
-    net::IpAddr,
-    sync::{
-        Arc,
-        atomic::{AtomicBool, Ordering},
-    },
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
 };
+use std::time::{Duration, Instant, SystemTime};
 
-use anyhow::{self, bail};
+use anyhow;
 use colored::*;
 use tracing::info_span;
-use unicode_width::UnicodeWidthStr;
 use zond_common::error;
 
 use crate::{
     mprint,
-    terminal::{
-        colors, format,
-        print::{self, TOTAL_WIDTH},
-        spinner,
-    },
+    output::{Outputs, grepable, ndjson::EventPrinter},
+    terminal::{colors, host_fmt, print, spinner},
 };
-use zond_common::models::range::IpCollection;
+use zond_common::models::{range::IpCollection, report::Report};
 use zond_common::{config::Config, models::host::Host, success};
-use zond_core::scanner;
+use zond_core::scanner::{self, ScanState};
 
-type Detail = (String, ColoredString);
-
-pub async fn discover(ips: IpCollection, cfg: &Config) -> anyhow::Result<()> {
+pub async fn discover(ips: IpCollection, cfg: &Config, outputs: &Outputs) -> anyhow::Result<()> {
     let span = info_span!("discovery", indicatif.pb_show = true);
     let guard = span.enter();
 
+    let state: Arc<ScanState> = ScanState::new();
     let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
-    let spinner_handle = spinner::start_discovery_spinner(span.clone(), running.clone());
+    let spinner_handle =
+        spinner::start_discovery_spinner(span.clone(), running.clone(), state.clone());
 
+    let started_at: SystemTime = SystemTime::now();
     let start_time: Instant = Instant::now();
-    let mut hosts: Vec<Host> = scanner::perform_discovery(ips, cfg).await?;
+    let printer: Option<EventPrinter> = outputs.event_printer();
+    let events = printer.as_ref().map(EventPrinter::sender);
+    let target_count: usize = ips.len();
+    let result: anyhow::Result<Vec<Host>> =
+        scanner::perform_discovery(ips, cfg, state, events).await;
 
     running.store(false, Ordering::Relaxed);
     let _ = spinner_handle.join();
 
     drop(guard);
 
+    if let Some(printer) = printer {
+        printer.finish().await?;
+    }
+    let mut hosts: Vec<Host> = result?;
+
     let total_time: Duration = start_time.elapsed();
+    hosts.sort_by_key(|host| *host.ips.iter().next().unwrap_or(&host.primary_ip));
+
+    let report: Report = Report::new(
+        env!("CARGO_PKG_VERSION"),
+        "discover",
+        started_at,
+        total_time,
+    )
+    .with_targets(target_count)
+    .with_hosts(&hosts);
+    if outputs.on_stdout() {
+        return outputs.write(&report, cfg);
+    }
+
     discovery_ends(&mut hosts, total_time, cfg)?;
-    Ok(())
+    outputs.write(&report, cfg)
 }
 
 fn discovery_ends(hosts: &mut [Host], total_time: Duration, cfg: &Config) -> anyhow::Result<()> {
@@ -53,13 +70,16 @@ fn discovery_ends(hosts: &mut [Host], total_time: Duration, cfg: &Config) -> any
         return Ok(());
     }
 
+    if cfg.quiet > 1 {
+        return grepable::write_hosts(hosts, cfg);
+    }
+
     if cfg.quiet > 0 {
         mprint!();
     }
 
     print::header("Network Discovery", cfg.quiet);
-    hosts.sort_by_key(|host| *host.ips.iter().next().unwrap_or(&host.primary_ip));
-    print_hosts(hosts, cfg)?;
+    print_hosts(hosts, cfg);
     print_summary(hosts.len(), total_time, cfg);
 
     Ok(())
@@ -74,17 +94,13 @@ fn no_hosts_found(cfg: &Config) {
     error!("Scan completed: 0 devices responded.");
 }
 
-fn print_hosts(hosts: &mut [Host], cfg: &Config) -> anyhow::Result<()> {
+fn print_hosts(hosts: &[Host], cfg: &Config) {
     for (idx, host) in hosts.iter().enumerate() {
-        match cfg.quiet {
-            2 => bail!("-qq is currently unimplemented"),
-            _ => print_host_tree(host, idx, cfg),
-        }
+        host_fmt::print_host_tree(host, idx, cfg);
         if idx + 1 != hosts.len() {
             mprint!();
         }
     }
-    Ok(())
 }
 
 fn print_summary(hosts_len: usize, total_time: Duration, cfg: &Config) {
@@ -105,87 +121,3 @@ fn print_summary(hosts_len: usize, total_time: Duration, cfg: &Config) {
         }
     }
 }
-
-fn print_host_tree(host: &Host, idx: usize, cfg: &Config) {
-    let primary_ip: IpAddr = host.primary_ip;
-    print_host_head(idx, &primary_ip, host);
-    let mut details: Vec<Detail> = format::ip_to_detail(host, cfg);
-
-    if let Some(mac_detai) = format::mac_to_detail(&host.mac, cfg) {
-        details.push(mac_detai);
-    }
-
-    if let Some(vendor_detail) = format::vendor_to_detail(&host.vendor) {
-        details.push(vendor_detail);
-    }
-
-    if let Some(hostname_detail) = format::hostname_to_detail(&host.hostname, cfg) {
-        details.push(hostname_detail);
-    }
-
-    if !host.network_roles.is_empty() {
-        let joined_roles: String = host
-            .network_roles
-            .iter()
-            .map(|role| format!("{:?}", role))
-            .collect::<Vec<String>>()
-            .join(", ");
-
-        let roles_detail: (String, ColoredString) = ("Roles".to_string(), joined_roles.normal());
-
-        details.push(roles_detail);
-    }
-
-    print::as_tree(details);
-}
-
-fn print_host_head(idx: usize, primary_ip: &IpAddr, host: &Host) {
-    let rtt_string: String = rtt_to_string(host);
-    let rtt_width: usize = rtt_string.width();
-
-    let block_width: usize = 20;
-    let local_pad: usize = block_width.saturating_sub(rtt_width);
-    let right_part: String = format!("{}{}", " ".repeat(local_pad), rtt_string);
-
-    let left_part: String = format!("[{}] {}", idx, primary_ip);
-
-    let used_width: usize = left_part.width() + block_width;
-
-    let padding_len: usize = TOTAL_WIDTH.saturating_sub(used_width + 1);
-    let padding: String = " ".repeat(padding_len);
-
-    let output: String = format!(
-        "{} {}{}{}",
-        format!("[{}]", idx.to_string().color(colors::ACCENT)).color(colors::SEPARATOR),
-        primary_ip.to_string().color(colors::PRIMARY),
-        padding,
-        right_part.color(colors::SECONDARY)
-    );
-
-    mprint!(&output);
-}
-
-fn rtt_to_string(host: &Host) -> String {
-    let min_rtt: Option<Duration> = host.min_rtt();
-
-    if min_rtt.is_none() {
-        return String::new();
-    }
-
-    let min_rtt: Duration = host.min_rtt().unwrap();
-    let max_rtt: Duration = host.max_rtt().unwrap();
-    let avg_rtt: Duration = host.average_rtt().unwrap();
-
-    if min_rtt == max_rtt {
-        return format!("⌛ {}ms", min_rtt.as_millis());
-    }
-
-    let spread: Duration = max_rtt.saturating_sub(min_rtt);
-    let tolerance: Duration = min_rtt.mul_f64(0.05).max(Duration::from_millis(2));
-
-    if tolerance > spread {
-        return format!("⌛ ~{}ms", avg_rtt.as_millis());
-    }
-
-    format!("⌛ {}ms - {}ms", min_rtt.as_millis(), max_rtt.as_millis())
-}
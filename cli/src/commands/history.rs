@@ -0,0 +1,165 @@
+use anyhow::{self, Context};
+use colored::*;
+use zond_common::history::{HistoryStore, HostHistory, HostKey, Observation};
+use zond_common::models::report::Report;
+use zond_common::{config::Config, error, warn};
+
+use crate::{
+    mprint,
+    terminal::{colors, format, host_fmt::Detail, print},
+};
+
+pub fn history(host: Option<&str>, cfg: &Config) -> anyhow::Result<()> {
+    let store: HistoryStore = HistoryStore::open_default()
+        .context("Cannot locate the history, neither $XDG_DATA_HOME nor $HOME is set")?;
+    let reports: Vec<Report> = store.load()?;
+
+    if reports.is_empty() {
+        warn!("No runs recorded yet in {}", store.path().display());
+        return Ok(());
+    }
+
+    let Some(host) = host else {
+        print_runs(&reports, cfg);
+        return Ok(());
+    };
+
+    let query: HostKey = HostKey::parse(host)
+        .with_context(|| format!("'{host}' is neither an IP nor a MAC address"))?;
+    match HostHistory::find(&reports, &query) {
+        Some(history) => print_history(&history, reports.len(), cfg),
+        None => error!(
+            "None of the {} recorded runs has seen {host}",
+            reports.len()
+        ),
+    }
+    Ok(())
+}
+
+/// One line per recorded run, newest last, numbered for `zond diff`.
+fn print_runs(reports: &[Report], cfg: &Config) {
+    print::header("Recorded Runs", cfg.quiet);
+    for (idx, report) in reports.iter().enumerate() {
+        let hosts_up: usize = report.hosts.as_ref().map_or(0, Vec::len);
+        let targets: String = report
+            .targets
+            .map(|targets| format!(" of {targets}"))
+            .unwrap_or_default();
+        print::tree_head(idx, &format::local_time(report.started_at));
+        print::as_tree(vec![
+            ("Command".to_string(), report.command.normal()),
+            (
+                "Hosts".to_string(),
+                format!("{hosts_up}{targets} up").color(colors::TEXT_DEFAULT),
+            ),
+        ]);
+    }
+}
+
+fn print_history(history: &HostHistory, runs: usize, cfg: &Config) {
+    print::header("Host History", cfg.quiet);
+
+    let key: String = match &history.key {
+        HostKey::Mac(mac) => format::mac_str(mac, cfg),
+        HostKey::Ip(ip) => ip.to_string(),
+    };
+    print::tree_head(0, &key);
+
+    let mut details: Vec<Detail> = Vec::new();
+    if let Some(vendor) = &history.vendor {
+        details.push(("Vendor".to_string(), vendor.color(colors::MAC_ADDR)));
+    }
+    details.push((
+        "First".to_string(),
+        format::local_time(history.first_seen).normal(),
+    ));
+    details.push((
+        "Last".to_string(),
+        format::local_time(history.last_seen).normal(),
+    ));
+    details.push((
+        "Seen".to_string(),
+        format!("in {} of {runs} runs", history.observations.len()).normal(),
+    ));
+    print::as_tree(details);
+
+    let mut previous: Option<&Observation> = None;
+    let mut last_scan: Option<&Observation> = None;
+    let mut idx: usize = 1;
+    for observation in &history.observations {
+        let changes: Vec<Detail> = changes(previous, last_scan, observation, cfg);
+        previous = Some(observation);
+        if observation.open_ports.is_some() {
+            last_scan = Some(observation);
+        }
+        if changes.is_empty() {
+            continue;
+        }
+
+        mprint!();
+        let head: String = format!(
+            "{} ({})",
+            format::local_time(observation.seen_at),
+            observation.command
+        );
+        print::tree_head(idx, &head);
+        print::as_tree(changes);
+        idx += 1;
+    }
+}
+
+/// What differs from the previous observation. The first one shows everything known.
+///
+/// Ports are compared with the last `scan`, since `discover` runs do not look at them,
+/// and only those both scans probed. Without that, the open ports are listed instead.
+fn changes(
+    previous: Option<&Observation>,
+    last_scan: Option<&Observation>,
+    current: &Observation,
+    cfg: &Config,
+) -> Vec<Detail> {
+    let mut details: Vec<Detail> = Vec::new();
+
+    if previous.is_none_or(|previous| previous.ips != current.ips) {
+        let ips: Vec<String> = current.ips.iter().map(|ip| ip.to_string()).collect();
+        details.push(("IP".to_string(), ips.join(", ").color(colors::IPV4_ADDR)));
+    }
+    let hostname_changed: bool = match previous {
+        Some(previous) => previous.hostname != current.hostname,
+        None => current.hostname.is_some(),
+    };
+    if hostname_changed {
+        let hostname: String = match &current.hostname {
+            Some(hostname) => format::hostname_str(hostname, cfg),
+            None => "none".to_string(),
+        };
+        details.push(("Hostname".to_string(), hostname.color(colors::HOSTNAME)));
+    }
+
+    let Some(ports) = &current.open_ports else {
+        return details;
+    };
+    match last_scan.and_then(|last_scan| current.port_changes(last_scan)) {
+        None => details.push((
+            "Ports".to_string(),
+            port_list(ports.iter()).color(colors::PORT),
+        )),
+        Some((opened, closed)) => {
+            if !opened.is_empty() {
+                details.push(("Opened".to_string(), port_list(opened).green()));
+            }
+            if !closed.is_empty() {
+                details.push(("Closed".to_string(), port_list(closed).red()));
+            }
+        }
+    }
+    details
+}
+
+fn port_list<T: ToString>(ports: impl IntoIterator<Item = T>) -> String {
+    let ports: Vec<String> = ports.into_iter().map(|port| port.to_string()).collect();
+    match ports.is_empty() {
+        true => "none open".to_string(),
+        false => ports.join(", "),
+    }
+}
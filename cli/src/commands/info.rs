@@ -3,10 +3,12 @@ use colored::*;
 use is_root::is_root;
 use pnet::datalink::NetworkInterface;
 use std::env;
+use std::time::{Instant, SystemTime};
 use sys_info;
 
 use crate::{
     mprint,
+    output::Outputs,
     terminal::{
         colors,
         print::{self, GLOBAL_KEY_WIDTH},
@@ -15,11 +17,33 @@ use crate::{
 use zond_common::{
     config::Config,
     models::localhost::{FirewallStatus, IpServiceGroup, Service},
+    models::report::{InterfaceRecord, Report, ServiceRecord, SystemRecord},
 };
-use zond_core::info::InfoService;
+use zond_core::info::{InfoService, SystemInfo};
 use zond_core::system::SystemRepo;
 
-pub fn info(cfg: &Config) -> anyhow::Result<()> {
+pub fn info(cfg: &Config, outputs: &Outputs) -> anyhow::Result<()> {
+    let started_at: SystemTime = SystemTime::now();
+    let start_time: Instant = Instant::now();
+
+    let system_repo = Box::new(SystemRepo);
+    let service = InfoService::new(system_repo);
+
+    let system_info = service.get_system_info()?;
+
+    if !outputs.is_empty() {
+        let report: Report = Report::new(
+            env!("CARGO_PKG_VERSION"),
+            "info",
+            started_at,
+            start_time.elapsed(),
+        );
+        outputs.write(&report.with_system(system_record(&system_info)), cfg)?;
+        if outputs.on_stdout() {
+            return Ok(());
+        }
+    }
+
     mprint!(
         format!(
             "{}",
@@ -30,11 +54,6 @@ pub fn info(cfg: &Config) -> anyhow::Result<()> {
     mprint!();
     GLOBAL_KEY_WIDTH.set(10);
 
-    let system_repo = Box::new(SystemRepo);
-    let service = InfoService::new(system_repo);
-
-    let system_info = service.get_system_info()?;
-
     if !is_root() {
         print_about_the_tool();
         print_local_system(cfg)?;
@@ -72,6 +91,33 @@ pub fn info(cfg: &Config) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The structured form of what this command prints. Firewall and services need root.
+fn system_record(system_info: &SystemInfo) -> SystemRecord {
+    let root: bool = is_root();
+    let os: Option<String> = sys_info::os_type()
+        .ok()
+        .map(|os_name| match sys_info::os_release() {
+            Ok(release) => format!("{os_name} {release}"),
+            Err(_) => os_name,
+        });
+
+    SystemRecord {
+        hostname: sys_info::hostname().ok(),
+        os,
+        user: env::var("USER").or_else(|_| env::var("USERNAME")).ok(),
+        firewall: root.then(|| system_info.firewall.clone()),
+        interfaces: system_info
+            .interfaces
+            .iter()
+            .map(InterfaceRecord::from)
+            .collect(),
+        services: match root {
+            true => ServiceRecord::from_groups(&system_info.services),
+            false => Vec::new(),
+        },
+    }
+}
+
 fn print_about_the_tool() {
     print::aligned_line("Version", env!("CARGO_PKG_VERSION"));
     print::aligned_line("Author", "hollowpointer");
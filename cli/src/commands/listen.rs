@@ -1,3 +1,165 @@
-pub fn listen() -> anyhow::Result<()> {
-    anyhow::bail!("'listen' subcommand not implemented yet");
+use std::collections::HashSet;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, Local};
+use colored::*;
+use pnet::{datalink::NetworkInterface, util::MacAddr};
+use tokio::sync::mpsc;
+use tracing::info_span;
+use zond_common::{config::Config, error, models::host::Host, success, utils::redact};
+use zond_core::listener::{self, Sighting};
+use zond_core::scanner::ScanState;
+
+use crate::{
+    mprint,
+    terminal::{
+        colors,
+        host_fmt::{self, Detail},
+        print, spinner,
+    },
+};
+
+pub async fn listen(
+    interface: Option<&str>,
+    duration: Option<Duration>,
+    cfg: &Config,
+) -> anyhow::Result<()> {
+    let intf: NetworkInterface = listener::select_interface(interface)?;
+
+    let span = info_span!("listen", indicatif.pb_show = true);
+    let guard = span.enter();
+
+    let state: Arc<ScanState> = ScanState::new();
+    let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+    let spinner_handle =
+        spinner::start_discovery_spinner(span.clone(), running.clone(), state.clone());
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<Sighting>();
+    let live_cfg: Config = cfg.clone();
+    let printer = tokio::spawn(async move {
+        let mut known: HashSet<Option<MacAddr>> = HashSet::new();
+        while let Some(sighting) = rx.recv().await {
+            let is_new: bool = known.insert(sighting.host.mac);
+            print_live_row(&sighting, is_new, &live_cfg);
+        }
+    });
+
+    let start_time: Instant = Instant::now();
+    let result: anyhow::Result<Vec<Sighting>> =
+        listener::perform_listen(&intf, duration, cfg, state, Some(tx)).await;
+
+    let _ = printer.await;
+    running.store(false, Ordering::Relaxed);
+    let _ = spinner_handle.join();
+
+    drop(guard);
+
+    let mut sightings: Vec<Sighting> = result?;
+    listen_ends(&mut sightings, start_time.elapsed(), cfg);
+    Ok(())
+}
+
+/// One line per new host (`+`) or newly learned detail (`~`), printed as it is heard.
+fn print_live_row(sighting: &Sighting, is_new: bool, cfg: &Config) {
+    if cfg.quiet > 1 {
+        return;
+    }
+
+    let host: &Host = &sighting.host;
+    let marker: ColoredString = match is_new {
+        true => "+".green().bold(),
+        false => "~".color(colors::ACCENT),
+    };
+
+    let mut row: String = format!(
+        "{} {:<16}",
+        marker,
+        host.primary_ip.to_string().color(colors::PRIMARY)
+    );
+    if let Some(mac) = &host.mac {
+        let mac_str: String = match cfg.redact {
+            true => redact::mac_addr(mac),
+            false => mac.to_string(),
+        };
+        row.push_str(&format!(" {}", mac_str.color(colors::MAC_ADDR)));
+    }
+    if let Some(vendor) = &host.vendor {
+        row.push_str(&format!(" {}", vendor.color(colors::SECONDARY)));
+    }
+    if let Some(hostname) = &host.hostname {
+        let hostname_str: String = match cfg.redact {
+            true => redact::hostname(hostname),
+            false => hostname.to_string(),
+        };
+        row.push_str(&format!(" {}", hostname_str.color(colors::HOSTNAME)));
+    }
+
+    mprint!(&row);
+}
+
+fn listen_ends(sightings: &mut [Sighting], total_time: Duration, cfg: &Config) {
+    if sightings.is_empty() {
+        error!("Listening completed: no devices were heard.");
+        return;
+    }
+
+    if cfg.quiet > 0 {
+        mprint!();
+    }
+
+    print::header("Passive Inventory", cfg.quiet);
+    sightings.sort_by_key(|sighting| sighting.host.primary_ip);
+    for (idx, sighting) in sightings.iter().enumerate() {
+        host_fmt::print_host_tree_with(&sighting.host, idx, sighting_to_details(sighting), cfg);
+        if idx + 1 != sightings.len() {
+            mprint!();
+        }
+    }
+    print_summary(sightings.len(), total_time, cfg);
+}
+
+fn sighting_to_details(sighting: &Sighting) -> Vec<Detail> {
+    let seen: String = format!(
+        "{} - {}",
+        local_time(sighting.first_seen),
+        local_time(sighting.last_seen)
+    );
+    let sources: String = sighting
+        .sources
+        .iter()
+        .map(|source| source.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    vec![
+        ("Seen".to_string(), seen.color(colors::TEXT_DEFAULT)),
+        ("Via".to_string(), sources.color(colors::SECONDARY)),
+    ]
+}
+
+fn local_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).format("%H:%M:%S").to_string()
+}
+
+fn print_summary(hosts_len: usize, total_time: Duration, cfg: &Config) {
+    let heard_hosts: ColoredString = format!("{hosts_len} hosts").bold().green();
+    let total_time: ColoredString = format!("{:.2}s", total_time.as_secs_f64()).bold().yellow();
+    let output: &ColoredString =
+        &format!("Listening Complete: {heard_hosts} heard in {total_time}")
+            .color(colors::TEXT_DEFAULT);
+
+    match cfg.quiet {
+        0 => {
+            print::fat_separator();
+            print::centerln(output);
+        }
+        _ => {
+            mprint!();
+            success!("{output}")
+        }
+    }
 }
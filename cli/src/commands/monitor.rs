@@ -0,0 +1,293 @@
+use std::io::{self, IsTerminal};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow;
+use chrono::{DateTime, Local};
+use colored::*;
+use tracing::info_span;
+use zond_common::diff::ReportDiff;
+use zond_common::models::alert::{Alert, AlertRecord};
+use zond_common::models::{host::Host, range::IpCollection, report::Report};
+use zond_common::{config::Config, info, success, warn};
+use zond_core::scanner::{self, ScanState};
+
+use crate::{
+    mprint,
+    output::{OutputFormat, Outputs, alerts::AlertSinks},
+    terminal::{colors, format, print, spinner},
+};
+
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Re-discovers `ips` every `interval` and alerts on what changed since the previous round.
+///
+/// The first round is the baseline. With ports configured, every host up is also scanned
+/// on them, so newly opened ports are reported too. A round that fails is reported and
+/// retried at the next interval. Stops on 'q' or Ctrl-C.
+pub async fn monitor(
+    ips: IpCollection,
+    interval: Duration,
+    cfg: &Config,
+    outputs: &Outputs,
+    mut sinks: AlertSinks,
+) -> anyhow::Result<()> {
+    if outputs.xml_file.is_some()
+        || outputs.json_file.is_some()
+        || outputs
+            .format
+            .is_some_and(|format| format != OutputFormat::Json)
+    {
+        warn!("Skipping the requested report, monitor writes alerts (see --alerts-file)");
+    }
+    sinks.stdout = outputs.format == Some(OutputFormat::Json);
+
+    // One session outlives the rounds, each round gets a fresh state to stop and count.
+    // Monitors often run as services, where only Ctrl-C (SIGINT) can stop them
+    let session: Arc<ScanState> = ScanState::new();
+    if !cfg.disable_input && io::stdin().is_terminal() {
+        scanner::spawn_user_input_listener(session.clone());
+    }
+    let signal_session: Arc<ScanState> = session.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            signal_session.stop();
+        }
+    });
+
+    let round_cfg: Config = Config {
+        disable_input: true,
+        ..cfg.clone()
+    };
+    let mut previous: Option<Report> = None;
+    let mut rounds: usize = 0;
+    let mut alert_count: usize = 0;
+
+    loop {
+        let state: Arc<ScanState> = ScanState::new();
+        let round = run_round(&ips, &round_cfg, state.clone());
+        tokio::pin!(round);
+
+        let result: anyhow::Result<Report> = tokio::select! {
+            result = &mut round => result,
+            _ = stopped(&session) => {
+                // A cut short round would report every host it missed as gone
+                state.stop();
+                let _ = round.await;
+                break;
+            }
+        };
+        rounds += 1;
+
+        // A failed round (e.g. the interface went down) is compared with nothing,
+        // the next one is compared with the last round that succeeded
+        match result {
+            Err(e) => warn!("Round {rounds} failed, retrying at the next interval: {e:#}"),
+            Ok(report) => {
+                let hosts_up: usize = report.hosts.as_ref().map_or(0, Vec::len);
+                match &previous {
+                    None => {
+                        success!("Baseline recorded: {hosts_up} hosts up, watching for changes")
+                    }
+                    Some(previous) => {
+                        let alerts: Vec<Alert> =
+                            Alert::from_diff(&ReportDiff::new(previous, &report));
+                        alert_count += alerts.len();
+                        for alert in alerts {
+                            print_alert(&alert, cfg);
+                            sinks.send(&AlertRecord::now(alert)).await;
+                        }
+                    }
+                }
+                previous = Some(report);
+            }
+        }
+
+        let next: DateTime<Local> = Local::now() + interval;
+        info!(
+            verbosity = 1,
+            "Round {rounds} done, next one at {}",
+            next.format("%H:%M:%S")
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = stopped(&session) => break,
+        }
+    }
+
+    print_summary(rounds, alert_count, cfg);
+    Ok(())
+}
+
+/// One discovery, plus a port scan of the hosts up if ports were given.
+async fn run_round(
+    ips: &IpCollection,
+    cfg: &Config,
+    state: Arc<ScanState>,
+) -> anyhow::Result<Report> {
+    let span = info_span!("monitor", indicatif.pb_show = true);
+    let guard = span.enter();
+
+    let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+    let spinner_handle =
+        spinner::start_discovery_spinner(span.clone(), running.clone(), state.clone());
+
+    let started_at: SystemTime = SystemTime::now();
+    let start_time: Instant = Instant::now();
+    let result: anyhow::Result<(Vec<Host>, &str)> = async {
+        let mut hosts: Vec<Host> =
+            scanner::perform_discovery(ips.clone(), cfg, state.clone(), None).await?;
+        if cfg.ports.is_empty() {
+            return Ok((hosts, "discover"));
+        }
+
+        let mut live: IpCollection = IpCollection::new();
+        for host in &hosts {
+            live.add_single(host.primary_ip);
+        }
+        if !hosts.is_empty() && !state.is_stopped() {
+            // Names were resolved by the discovery already
+            let scan_cfg: Config = Config {
+                no_dns: true,
+                ..cfg.clone()
+            };
+            let scanned: Vec<Host> =
+                scanner::perform_port_scan(live, &scan_cfg, state, None).await?;
+            for host in scanned {
+                if let Some(known) = hosts
+                    .iter_mut()
+                    .find(|known| known.primary_ip == host.primary_ip)
+                {
+                    known.merge(host);
+                }
+            }
+        }
+        Ok((hosts, "scan"))
+    }
+    .await;
+
+    running.store(false, Ordering::Relaxed);
+    let _ = spinner_handle.join();
+    drop(guard);
+
+    let (hosts, command) = result?;
+    let mut report: Report = Report::new(
+        env!("CARGO_PKG_VERSION"),
+        command,
+        started_at,
+        start_time.elapsed(),
+    )
+    .with_targets(ips.len());
+    if command == "scan" {
+        report = report.with_ports(&cfg.ports);
+    }
+    Ok(report.with_hosts(&hosts))
+}
+
+async fn stopped(session: &ScanState) {
+    while !session.is_stopped() {
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+}
+
+/// One line per alert: `+` joined, `-` left, `!` new MAC on a known IP, `~` other changes.
+fn print_alert(alert: &Alert, cfg: &Config) {
+    if cfg.quiet > 1 {
+        return;
+    }
+
+    let (marker, detail): (ColoredString, String) = match alert {
+        Alert::HostJoined {
+            mac,
+            vendor,
+            hostname,
+            ..
+        } => ("+".green().bold(), host_detail(mac, vendor, hostname, cfg)),
+        Alert::HostLeft {
+            mac,
+            vendor,
+            hostname,
+            ..
+        } => ("-".red().bold(), host_detail(mac, vendor, hostname, cfg)),
+        Alert::MacChanged {
+            mac, previous_mac, ..
+        } => (
+            "!".red().bold(),
+            format!(
+                "MAC {} (was {})",
+                format::mac_str(mac, cfg),
+                format::mac_str(previous_mac, cfg)
+            )
+            .red()
+            .bold()
+            .to_string(),
+        ),
+        Alert::IpChanged { previous_ip, .. } => (
+            "~".color(colors::ACCENT),
+            format!("moved from {previous_ip}")
+                .color(colors::IPV4_ADDR)
+                .to_string(),
+        ),
+        Alert::PortsOpened { ports, .. } => (
+            "~".color(colors::ACCENT),
+            format!("opened {}", ports.join(", ")).green().to_string(),
+        ),
+    };
+
+    let time: String = Local::now().format("%H:%M:%S").to_string();
+    mprint!(&format!(
+        "{} {} {:<16} {}",
+        marker,
+        time.color(colors::TEXT_DEFAULT),
+        alert.ip().to_string().color(colors::PRIMARY),
+        detail
+    ));
+}
+
+fn host_detail(
+    mac: &Option<String>,
+    vendor: &Option<String>,
+    hostname: &Option<String>,
+    cfg: &Config,
+) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(mac) = mac {
+        parts.push(
+            format::mac_str(mac, cfg)
+                .color(colors::MAC_ADDR)
+                .to_string(),
+        );
+    }
+    if let Some(vendor) = vendor {
+        parts.push(vendor.color(colors::SECONDARY).to_string());
+    }
+    if let Some(hostname) = hostname {
+        parts.push(
+            format::hostname_str(hostname, cfg)
+                .color(colors::HOSTNAME)
+                .to_string(),
+        );
+    }
+    parts.join(" ")
+}
+
+fn print_summary(rounds: usize, alert_count: usize, cfg: &Config) {
+    let rounds: ColoredString = format!("{rounds} rounds").bold().green();
+    let alerts: ColoredString = format!("{alert_count} alerts").bold().yellow();
+    let output: &ColoredString =
+        &format!("Monitoring Stopped: {alerts} in {rounds}").color(colors::TEXT_DEFAULT);
+
+    match cfg.quiet {
+        0 => {
+            print::fat_separator();
+            print::centerln(output);
+        }
+        _ => {
+            mprint!();
+            success!("{output}")
+        }
+    }
+}
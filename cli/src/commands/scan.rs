@@ -1,5 +1,107 @@
-use zond_common::{config::Config, models::range::IpCollection};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::{Duration, Instant, SystemTime};
 
-pub fn scan(_ips: IpCollection, _cfg: &Config) -> anyhow::Result<()> {
-    anyhow::bail!("'scan' subcommand not implemented yet");
+use colored::*;
+use tracing::info_span;
+use zond_common::models::{range::IpCollection, report::Report};
+use zond_common::{config::Config, error, models::host::Host, success};
+use zond_core::scanner::{self, ScanState};
+
+use crate::{
+    mprint,
+    output::{Outputs, grepable, ndjson::EventPrinter},
+    terminal::{colors, host_fmt, print, spinner},
+};
+
+pub async fn scan(ips: IpCollection, cfg: &Config, outputs: &Outputs) -> anyhow::Result<()> {
+    let span = info_span!("scan", indicatif.pb_show = true);
+    let guard = span.enter();
+
+    let state: Arc<ScanState> = ScanState::new();
+    let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+    let spinner_handle =
+        spinner::start_discovery_spinner(span.clone(), running.clone(), state.clone());
+
+    let started_at: SystemTime = SystemTime::now();
+    let start_time: Instant = Instant::now();
+    let printer: Option<EventPrinter> = outputs.event_printer();
+    let events = printer.as_ref().map(EventPrinter::sender);
+    let target_count: usize = ips.len();
+    let result: anyhow::Result<Vec<Host>> =
+        scanner::perform_port_scan(ips, cfg, state, events).await;
+
+    running.store(false, Ordering::Relaxed);
+    let _ = spinner_handle.join();
+
+    drop(guard);
+
+    if let Some(printer) = printer {
+        printer.finish().await?;
+    }
+
+    let mut hosts: Vec<Host> = result?;
+    let total_time: Duration = start_time.elapsed();
+    hosts.sort_by_key(|host| host.primary_ip);
+
+    let report: Report = Report::new(env!("CARGO_PKG_VERSION"), "scan", started_at, total_time)
+        .with_targets(target_count)
+        .with_ports(&cfg.ports)
+        .with_hosts(&hosts);
+    if outputs.on_stdout() {
+        return outputs.write(&report, cfg);
+    }
+
+    scan_ends(&hosts, total_time, cfg)?;
+    outputs.write(&report, cfg)
+}
+
+fn scan_ends(hosts: &[Host], total_time: Duration, cfg: &Config) -> anyhow::Result<()> {
+    if hosts.is_empty() {
+        error!("Scan completed: 0 hosts responded.");
+        return Ok(());
+    }
+
+    if cfg.quiet > 1 {
+        return grepable::write_hosts(hosts, cfg);
+    }
+
+    if cfg.quiet > 0 {
+        mprint!();
+    }
+
+    print::header("Port Scan", cfg.quiet);
+
+    for (idx, host) in hosts.iter().enumerate() {
+        host_fmt::print_host_tree(host, idx, cfg);
+        if idx + 1 != hosts.len() {
+            mprint!();
+        }
+    }
+
+    let open_ports: usize = hosts.iter().map(|host| host.open_ports().count()).sum();
+    print_summary(hosts.len(), open_ports, total_time, cfg);
+    Ok(())
+}
+
+fn print_summary(hosts_len: usize, open_ports: usize, total_time: Duration, cfg: &Config) {
+    let active_hosts: ColoredString = format!("{hosts_len} hosts up").bold().green();
+    let open_ports: ColoredString = format!("{open_ports} open ports").bold().green();
+    let total_time: ColoredString = format!("{:.2}s", total_time.as_secs_f64()).bold().yellow();
+    let output: &ColoredString =
+        &format!("Scan Complete: {active_hosts}, {open_ports} in {total_time}")
+            .color(colors::TEXT_DEFAULT);
+
+    match cfg.quiet {
+        0 => {
+            print::fat_separator();
+            print::centerln(output);
+        }
+        _ => {
+            mprint!();
+            success!("{output}")
+        }
+    }
 }
@@ -1,12 +1,25 @@
 mod commands;
+mod output;
 mod terminal;
 
-use commands::{CommandLine, Commands, discover::discover, info::info, listen::listen, scan::scan};
+use commands::{
+    CommandLine, Commands, diff::diff, discover::discover, history::history, info::info,
+    listen::listen, monitor::monitor, scan::scan,
+};
 
-use zond_common::{config::Config, error, models::target};
+use zond_common::{
+    config::Config,
+    error,
+    models::port::{PortSet, Protocol},
+};
+use zond_core::network::lookup;
 
+use crate::output::Outputs;
 use crate::terminal::{print, spinner};
 
+/// Amount of most common ports probed by `scan` when neither `-p` nor `--top-ports` is given.
+const DEFAULT_TOP_PORTS: usize = 20;
+
 #[tokio::main]
 async fn main() {
     let commands = CommandLine::parse_args();
@@ -27,32 +40,83 @@ async fn main() {
 }
 
 async fn run(commands: CommandLine) -> anyhow::Result<()> {
-    let cfg = Config {
+    let mut cfg = Config {
         no_banner: commands.no_banner,
         no_dns: commands.no_dns,
         redact: commands.redact,
         quiet: commands.quiet,
         disable_input: false,
+        ports: PortSet::new(),
+        probes: Vec::new(),
+        max_parallelism: commands.max_parallelism,
+        service_detection: false,
+        signatures: None,
     };
+    let outputs: Outputs = commands.outputs()?;
 
     match commands.command {
         Commands::Info => {
             print::header("about the tool", cfg.quiet);
-            info(&cfg)
+            info(&cfg, &outputs)
         }
-        Commands::Listen => {
+        Commands::Listen {
+            interface,
+            duration,
+        } => {
             print::header("starting listener", cfg.quiet);
-            listen()
+            listen(interface.as_deref(), duration, &cfg).await
         }
-        Commands::Discover { targets } => {
+        Commands::Discover {
+            targets,
+            ports,
+            probes,
+        } => {
             print::header("performing host discovery", cfg.quiet);
-            let ips = target::to_collection(&targets)?;
-            discover(ips, &cfg).await
+            cfg.ports = ports.into_port_set(Protocol::Tcp).unwrap_or_default();
+            cfg.probes = probes;
+            let (targets, excludes) = targets.into_specs()?;
+            let ips = lookup::to_collection(&targets, &excludes).await?;
+            discover(ips, &cfg, &outputs).await
         }
-        Commands::Scan { targets } => {
+        Commands::Scan {
+            targets,
+            ports,
+            udp,
+            services,
+            signatures,
+        } => {
             print::header("starting scanner", cfg.quiet);
-            let ips = target::to_collection(&targets)?;
-            scan(ips, &cfg)
+            let protocol: Protocol = if udp { Protocol::Udp } else { Protocol::Tcp };
+            cfg.ports = ports
+                .into_port_set(protocol)
+                .unwrap_or_else(|| PortSet::top(DEFAULT_TOP_PORTS, protocol));
+            cfg.service_detection = services;
+            cfg.signatures = signatures;
+            let (targets, excludes) = targets.into_specs()?;
+            let ips = lookup::to_collection(&targets, &excludes).await?;
+            scan(ips, &cfg, &outputs).await
+        }
+        Commands::History { host } => {
+            print::header("scan history", cfg.quiet);
+            history(host.as_deref(), &cfg)
+        }
+        Commands::Diff { old, new } => {
+            print::header("comparing runs", cfg.quiet);
+            diff(old.as_deref(), new.as_deref(), &cfg, &outputs)
+        }
+        Commands::Monitor {
+            targets,
+            ports,
+            probes,
+            interval,
+            alerts,
+        } => {
+            print::header("starting monitor", cfg.quiet);
+            cfg.ports = ports.into_port_set(Protocol::Tcp).unwrap_or_default();
+            cfg.probes = probes;
+            let (targets, excludes) = targets.into_specs()?;
+            let ips = lookup::to_collection(&targets, &excludes).await?;
+            monitor(ips, interval, &cfg, &outputs, alerts.into_sinks()).await
         }
     }
 }
@@ -0,0 +1,190 @@
+//! **Structured Output** of a run, for other programs to consume.
+//!
+//! The terminal view is written to stderr, so a report on stdout never mixes with it.
+//! When a report or event stream goes to stdout, the host trees and summaries are skipped.
+
+pub mod alerts;
+pub mod csv;
+pub mod grepable;
+pub mod html;
+pub mod inventory;
+pub mod json;
+pub mod markdown;
+pub mod ndjson;
+pub mod xml;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::ensure;
+use clap::ValueEnum;
+use zond_common::{config::Config, history::HistoryStore, models::report::Report, warn};
+
+use ndjson::EventPrinter;
+
+/// Formats `--output` can write to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    /// nmap's XML format
+    Xml,
+    /// One row per host, for spreadsheets
+    Csv,
+    /// A host table and port sections, for wikis and tickets
+    Markdown,
+    /// A self-contained page with a sortable host table
+    Html,
+}
+
+impl OutputFormat {
+    /// Whether the format only describes hosts, so `info` has nothing to put in it.
+    fn hosts_only(self) -> bool {
+        self != OutputFormat::Json
+    }
+}
+
+/// The reports requested for a run.
+#[derive(Debug, Clone, Default)]
+pub struct Outputs {
+    /// Report written to stdout in place of the terminal view
+    pub format: Option<OutputFormat>,
+
+    /// File receiving a JSON report ('-' for stdout)
+    pub json_file: Option<PathBuf>,
+
+    /// File receiving an nmap XML report ('-' for stdout)
+    pub xml_file: Option<PathBuf>,
+
+    /// Print scan events to stdout as JSON lines while scanning
+    pub stream: bool,
+
+    /// Store recording every discover and scan, unless `--no-history` was given
+    pub history: Option<HistoryStore>,
+}
+
+impl Outputs {
+    /// Whether a report was requested. The history is not a request, it records quietly.
+    pub fn is_empty(&self) -> bool {
+        self.format.is_none() && self.json_file.is_none() && self.xml_file.is_none() && !self.stream
+    }
+
+    /// Whether a report or the event stream goes to stdout, which replaces the terminal view.
+    pub fn on_stdout(&self) -> bool {
+        let file_on_stdout = |file: &Option<PathBuf>| file.as_deref().is_some_and(is_stdout);
+        self.stream
+            || self.format.is_some()
+            || file_on_stdout(&self.json_file)
+            || file_on_stdout(&self.xml_file)
+    }
+
+    /// Fails if more than one report or the event stream would go to stdout, where they
+    /// would run together.
+    pub fn ensure_single_stdout(&self) -> anyhow::Result<()> {
+        let file_on_stdout = |file: &Option<PathBuf>| file.as_deref().is_some_and(is_stdout);
+        let writers: Vec<&str> = [
+            (self.stream, "--stream"),
+            (self.format.is_some(), "--output"),
+            (file_on_stdout(&self.json_file), "-oJ -"),
+            (file_on_stdout(&self.xml_file), "-oX -"),
+        ]
+        .into_iter()
+        .filter_map(|(on_stdout, flag)| on_stdout.then_some(flag))
+        .collect();
+
+        ensure!(
+            writers.len() <= 1,
+            "Only one of {} can write to stdout",
+            writers.join(", ")
+        );
+        Ok(())
+    }
+
+    /// Starts printing scan events if `--stream` was given.
+    pub fn event_printer(&self) -> Option<EventPrinter> {
+        self.stream.then(EventPrinter::spawn)
+    }
+
+    /// Writes the report in every requested format.
+    pub fn write(&self, report: &Report, cfg: &Config) -> anyhow::Result<()> {
+        let has_hosts: bool = report.hosts.is_some();
+        let wants_hosts: bool =
+            self.format.is_some_and(OutputFormat::hosts_only) || self.xml_file.is_some();
+        if wants_hosts && !has_hosts {
+            warn!("Skipping host report, it only covers discover and scan results");
+        }
+
+        match self.format {
+            Some(OutputFormat::Json) => json::write_stdout(report)?,
+            Some(_) if !has_hosts => {}
+            Some(OutputFormat::Xml) => xml::write_stdout(report, cfg)?,
+            Some(OutputFormat::Csv) => csv::write_stdout(report, cfg)?,
+            Some(OutputFormat::Markdown) => markdown::write_stdout(report, cfg)?,
+            Some(OutputFormat::Html) => html::write_stdout(report, cfg)?,
+            None => {}
+        }
+        if let Some(path) = &self.json_file {
+            json::write_file(report, path)?;
+        }
+        if let Some(path) = self.xml_file.as_deref().filter(|_| has_hosts) {
+            xml::write_file(report, cfg, path)?;
+        }
+        if let Some(store) = self.history.as_ref().filter(|_| has_hosts) {
+            // A broken history should not cost the results of the run
+            if let Err(e) = store.append(report) {
+                warn!("Failed to record the run in the history: {e:#}");
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_stdout(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_a_single_report_on_stdout() {
+        let outputs = |format: Option<OutputFormat>, json: &str, xml: &str| {
+            Outputs {
+                format,
+                json_file: (!json.is_empty()).then(|| PathBuf::from(json)),
+                xml_file: (!xml.is_empty()).then(|| PathBuf::from(xml)),
+                ..Outputs::default()
+            }
+            .ensure_single_stdout()
+        };
+        let streaming = |json: &str, xml: &str| {
+            Outputs {
+                json_file: (!json.is_empty()).then(|| PathBuf::from(json)),
+                xml_file: (!xml.is_empty()).then(|| PathBuf::from(xml)),
+                stream: true,
+                ..Outputs::default()
+            }
+            .ensure_single_stdout()
+        };
+
+        assert!(outputs(Some(OutputFormat::Json), "scan.json", "-").is_err());
+        assert!(outputs(Some(OutputFormat::Json), "-", "").is_err());
+        assert!(outputs(Some(OutputFormat::Xml), "", "-").is_err());
+        assert!(outputs(None, "-", "-").is_err());
+
+        assert!(outputs(Some(OutputFormat::Json), "scan.json", "scan.xml").is_ok());
+        assert!(outputs(None, "-", "scan.xml").is_ok());
+
+        assert!(streaming("-", "").is_err());
+        assert!(streaming("", "-").is_err());
+        assert!(streaming("scan.json", "scan.xml").is_ok());
+    }
+}
@@ -0,0 +1,219 @@
+//! **Alert Delivery** for `zond monitor`.
+//!
+//! Every alert is one JSON line ([`AlertRecord`]), printed to stdout, appended to a file,
+//! piped into a command or posted to a webhook on this machine. A sink that fails is
+//! reported and skipped, so a broken hook does not stop the monitor.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, bail, ensure};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::time::timeout;
+use zond_common::models::alert::AlertRecord;
+use zond_common::warn;
+
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the alerts of a monitor go.
+#[derive(Debug, Clone, Default)]
+pub struct AlertSinks {
+    /// Print every alert to stdout as a JSON line
+    pub stdout: bool,
+
+    /// File the alerts are appended to as JSON lines
+    pub file: Option<PathBuf>,
+
+    /// Shell command run per alert, with the JSON line on its stdin
+    pub hook: Option<String>,
+
+    /// Local HTTP endpoint every alert is posted to
+    pub webhook: Option<Webhook>,
+}
+
+impl AlertSinks {
+    /// Delivers `record` to every sink, warning about the ones that fail.
+    pub async fn send(&self, record: &AlertRecord) {
+        let line: String = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize the alert: {e}");
+                return;
+            }
+        };
+
+        if self.stdout {
+            let mut stdout = io::stdout().lock();
+            // A closed pipe (e.g. `| head`) loses the alerts but not the monitor
+            let _ = writeln!(stdout, "{line}").and_then(|_| stdout.flush());
+        }
+        if let Some(path) = &self.file {
+            let appended: io::Result<()> = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"));
+            if let Err(e) = appended {
+                warn!("Failed to write the alert to '{}': {e}", path.display());
+            }
+        }
+        if let Some(command) = &self.hook
+            && let Err(e) = run_hook(command, record.alert.kind(), &line).await
+        {
+            warn!("Alert hook failed: {e:#}");
+        }
+        if let Some(webhook) = &self.webhook
+            && let Err(e) = webhook.post(&line).await
+        {
+            warn!("Alert webhook failed: {e:#}");
+        }
+    }
+}
+
+/// Runs `command` with `sh -c`, the alert on stdin and its kind in `$ZOND_EVENT`.
+///
+/// The output of the command goes to stderr, so it never mixes with alerts on stdout.
+async fn run_hook(command: &str, kind: &str, line: &str) -> anyhow::Result<()> {
+    let mut child: Child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("ZOND_EVENT", kind)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(io::stderr()))
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to run '{command}'"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input closes the pipe early, which is fine
+        let _ = stdin.write_all(format!("{line}\n").as_bytes()).await;
+    }
+
+    let status = timeout(HOOK_TIMEOUT, child.wait())
+        .await
+        .with_context(|| format!("'{command}' did not finish within {HOOK_TIMEOUT:?}"))??;
+    ensure!(status.success(), "'{command}' exited with {status}");
+    Ok(())
+}
+
+/// An `http://` endpoint on a loopback address, e.g. `http://127.0.0.1:9000/zond`.
+///
+/// Only local endpoints are accepted: alerts name devices and addresses of the network,
+/// so forwarding them elsewhere is left to a relay the user controls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    /// Host and port (e.g. `localhost:9000`)
+    authority: String,
+    path: String,
+}
+
+impl Webhook {
+    pub fn parse(url: &str) -> anyhow::Result<Self> {
+        let Some(rest) = url.strip_prefix("http://") else {
+            bail!("Only http:// webhooks are supported");
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/"),
+        };
+
+        // `[::1]` has colons but no port, `[::1]:9000` and `localhost:9000` have one
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.ends_with(']') => (host, Some(port)),
+            _ => (authority, None),
+        };
+        if let Some(port) = port {
+            port.parse::<u16>()
+                .with_context(|| format!("Invalid webhook port '{port}'"))?;
+        }
+
+        let host: &str = host.trim_start_matches('[').trim_end_matches(']');
+        let is_local: bool =
+            host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+        ensure!(
+            is_local,
+            "Webhooks must point to this machine (localhost, 127.0.0.1 or ::1), not '{host}'"
+        );
+
+        let authority: String = match port {
+            Some(_) => authority.to_string(),
+            None => format!("{authority}:80"),
+        };
+        Ok(Self {
+            authority,
+            path: path.to_string(),
+        })
+    }
+
+    /// POSTs `body` as JSON and expects a 2xx answer.
+    async fn post(&self, body: &str) -> anyhow::Result<()> {
+        let request: String = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.path,
+            self.authority,
+            body.len()
+        );
+
+        let exchange = async {
+            let mut stream: TcpStream = TcpStream::connect(&self.authority).await?;
+            stream.write_all(request.as_bytes()).await?;
+            let mut response: [u8; 512] = [0; 512];
+            let read: usize = stream.read(&mut response).await?;
+            Ok::<String, io::Error>(String::from_utf8_lossy(&response[..read]).into_owned())
+        };
+        let response: String = timeout(WEBHOOK_TIMEOUT, exchange)
+            .await
+            .with_context(|| {
+                format!(
+                    "{} did not answer within {WEBHOOK_TIMEOUT:?}",
+                    self.authority
+                )
+            })?
+            .with_context(|| format!("Failed to reach {}", self.authority))?;
+
+        let status_line: &str = response.lines().next().unwrap_or_default();
+        let success: bool = status_line
+            .split_whitespace()
+            .nth(1)
+            .is_some_and(|status| status.starts_with('2'));
+        ensure!(success, "{} answered '{status_line}'", self.authority);
+        Ok(())
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_local_webhooks() {
+        let webhook: Webhook = Webhook::parse("http://127.0.0.1:9000/zond").unwrap();
+        assert_eq!(webhook.authority, "127.0.0.1:9000");
+        assert_eq!(webhook.path, "/zond");
+
+        let webhook: Webhook = Webhook::parse("http://[::1]").unwrap();
+        assert_eq!(webhook.authority, "[::1]:80");
+        assert_eq!(webhook.path, "/");
+        assert!(Webhook::parse("http://localhost:8080").is_ok());
+
+        assert!(Webhook::parse("http://192.168.1.10/hook").is_err());
+        assert!(Webhook::parse("https://localhost/hook").is_err());
+        assert!(Webhook::parse("http://localhost:http/hook").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! **CSV** inventory, one row per host, for spreadsheets.
+//!
+//! Fields follow RFC 4180. Values that a spreadsheet would evaluate as a formula (a hostname
+//! or vendor starting with `=`, `+`, `-` or `@`) are prefixed with `'`, since they come from
+//! the network and cannot be trusted.
+
+use std::io::{self, Write};
+
+use zond_common::{config::Config, models::report::Report};
+
+use super::inventory::{self, HostRow};
+
+const HEADER: [&str; 9] = [
+    "ip",
+    "other_ips",
+    "mac",
+    "vendor",
+    "hostname",
+    "roles",
+    "rtt_ms",
+    "open_ports",
+    "other_ports",
+];
+
+pub fn write_stdout(report: &Report, cfg: &Config) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(to_csv(report, cfg).as_bytes())?;
+    Ok(())
+}
+
+pub fn to_csv(report: &Report, cfg: &Config) -> String {
+    let mut csv: String = HEADER.join(",");
+    csv.push_str("\r\n");
+
+    for row in inventory::rows(report, cfg) {
+        csv.push_str(&record(&row).join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+fn record(row: &HostRow) -> Vec<String> {
+    let optional = |value: &Option<String>| value.as_deref().map(field).unwrap_or_default();
+    vec![
+        row.ip.to_string(),
+        field(&row.other_ips.join(" ")),
+        optional(&row.mac),
+        optional(&row.vendor),
+        optional(&row.hostname),
+        field(&row.roles.join(" ")),
+        row.rtt_ms
+            .map(|rtt| format!("{rtt:.2}"))
+            .unwrap_or_default(),
+        field(&row.ports_summary()),
+        row.other_ports.to_string(),
+    ]
+}
+
+fn field(value: &str) -> String {
+    let value: String = match value.starts_with(['=', '+', '-', '@']) {
+        true => format!("'{value}"),
+        false => value.to_string(),
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_and_defuses_fields() {
+        assert_eq!(field("Raspberry Pi"), "Raspberry Pi");
+        assert_eq!(
+            field("22/tcp ssh, 80/tcp http"),
+            "\"22/tcp ssh, 80/tcp http\""
+        );
+        assert_eq!(field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(field("=HYPERLINK(1)"), "'=HYPERLINK(1)");
+    }
+}
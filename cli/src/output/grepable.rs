@@ -0,0 +1,111 @@
+//! **Grepable** host lines for `-qq`.
+//!
+//! One host per line with tab separated fields, in a fixed order:
+//! `IP  MAC  VENDOR  HOSTNAME  PORTS`. Unknown fields are `-` so every line has the same
+//! number of columns, and open ports are comma separated (`22/tcp,443/tcp`).
+
+use std::io::{self, Write};
+
+use zond_common::{config::Config, models::host::Host, utils::redact};
+
+const EMPTY: &str = "-";
+
+pub fn write_hosts(hosts: &[Host], cfg: &Config) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for host in hosts {
+        match writeln!(stdout, "{}", host_line(host, cfg)) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+pub fn host_line(host: &Host, cfg: &Config) -> String {
+    let mac: String = match &host.mac {
+        Some(mac) if cfg.redact => redact::mac_addr(mac),
+        Some(mac) => mac.to_string(),
+        None => EMPTY.to_string(),
+    };
+    let hostname: Option<String> = match &host.hostname {
+        Some(hostname) if cfg.redact => Some(redact::hostname(hostname)),
+        hostname => hostname.clone(),
+    };
+    let ports: Vec<String> = host
+        .open_ports()
+        .map(|port| format!("{}/{}", port.number, port.protocol))
+        .collect();
+
+    [
+        host.primary_ip.to_string(),
+        mac,
+        field(host.vendor.as_deref()),
+        field(hostname.as_deref()),
+        if ports.is_empty() {
+            EMPTY.to_string()
+        } else {
+            ports.join(",")
+        },
+    ]
+    .join("\t")
+}
+
+/// Tabs and line breaks would shift the columns, so they become spaces.
+fn field(value: Option<&str>) -> String {
+    value
+        .map(|value| value.replace(['\t', '\n', '\r'], " "))
+        .unwrap_or_else(|| EMPTY.to_string())
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use pnet::datalink::MacAddr;
+    use zond_common::models::port::{Port, PortReason, PortState, Protocol};
+
+    use super::*;
+
+    #[test]
+    fn writes_one_tab_separated_line_per_host() {
+        let mut host: Host = Host::new(IpAddr::from([192, 168, 1, 1]))
+            .with_mac(MacAddr::new(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3));
+        host.vendor = Some("Raspberry\tPi".to_string());
+        host.add_port(Port::new(
+            443,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        host.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        host.add_port(Port::new(
+            23,
+            Protocol::Tcp,
+            PortState::Closed,
+            PortReason::Rst,
+        ));
+
+        assert_eq!(
+            host_line(&host, &Config::default()),
+            "192.168.1.1\t2c:cf:67:f2:51:e3\tRaspberry Pi\t-\t22/tcp,443/tcp"
+        );
+        assert_eq!(
+            host_line(&Host::new(IpAddr::from([10, 0, 0, 7])), &Config::default()),
+            "10.0.0.7\t-\t-\t-\t-"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! **HTML** inventory, a single self-contained file for people without a terminal.
+//!
+//! Styles and the sorting script are inlined, so the file can be mailed or opened offline.
+//! The host table sorts by any column when its header is clicked, and every host with open
+//! ports gets its own section below the table.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::net::IpAddr;
+
+use zond_common::{config::Config, models::report::Report};
+
+use super::inventory::{self, HostRow, PortRow};
+
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; background: #f6f8fa; }
+h1 { margin-bottom: 0.2rem; }
+.meta { color: #656d76; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; background: #fff; margin-bottom: 1.5rem; }
+th, td { border: 1px solid #d0d7de; padding: 0.4rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #eaeef2; cursor: pointer; user-select: none; white-space: nowrap; }
+th[aria-sort="ascending"]::after { content: " \25B2"; }
+th[aria-sort="descending"]::after { content: " \25BC"; }
+td.mono, .mono { font-family: ui-monospace, monospace; }
+.muted { color: #656d76; }
+.badge { display: inline-block; padding: 0 0.45rem; border-radius: 1rem; font-size: 0.8rem; margin: 0.1rem 0.2rem 0.1rem 0; }
+.vendor { background: #ddf4ff; color: #0550ae; }
+.role { background: #fff8c5; color: #7d4e00; }
+.role-gateway { background: #ffebe9; color: #a40e26; }
+.port { background: #dafbe1; color: #116329; }
+section { background: #fff; border: 1px solid #d0d7de; padding: 0 1rem 0.5rem; margin-bottom: 1rem; }
+section table { margin-bottom: 0.5rem; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll("table.sortable th").forEach((th, column) => {
+  th.addEventListener("click", () => {
+    const tbody = th.closest("table").tBodies[0];
+    const ascending = th.getAttribute("aria-sort") !== "ascending";
+    th.parentNode.querySelectorAll("th").forEach((other) => other.removeAttribute("aria-sort"));
+    th.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+    const key = (row) => {
+      const cell = row.cells[column];
+      return cell.dataset.sort ?? cell.textContent.trim();
+    };
+    const rows = Array.from(tbody.rows).sort((a, b) => {
+      const [x, y] = [key(a), key(b)];
+      const numeric = x !== "" && y !== "" && !isNaN(x) && !isNaN(y);
+      const order = numeric ? x - y : x.localeCompare(y);
+      return ascending ? order : -order;
+    });
+    rows.forEach((row) => tbody.appendChild(row));
+  });
+});
+"#;
+
+pub fn write_stdout(report: &Report, cfg: &Config) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(to_html(report, cfg).as_bytes())?;
+    Ok(())
+}
+
+pub fn to_html(report: &Report, cfg: &Config) -> String {
+    let rows: Vec<HostRow> = inventory::rows(report, cfg);
+
+    let mut html: String = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    let _ = writeln!(
+        html,
+        "<title>zond {} report</title>",
+        escape(&report.command)
+    );
+    let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+    html.push_str("<h1>zond report</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p class=\"meta\">{}, started {} (zond {})</p>",
+        escape(&inventory::run_summary(report)),
+        escape(&inventory::started_at(report)),
+        escape(&report.zond_version)
+    );
+
+    write_host_table(&mut html, &rows);
+    for (idx, row) in rows.iter().enumerate() {
+        if !row.open_ports.is_empty() {
+            write_port_section(&mut html, idx, row);
+        }
+    }
+
+    let _ = writeln!(html, "<script>{SCRIPT}</script>\n</body>\n</html>");
+    html
+}
+
+fn write_host_table(html: &mut String, rows: &[HostRow]) {
+    html.push_str("<table class=\"sortable\">\n<thead><tr>");
+    for column in [
+        "IP",
+        "MAC",
+        "Vendor",
+        "Hostname",
+        "Roles",
+        "RTT",
+        "Open Ports",
+    ] {
+        let _ = write!(html, "<th>{column}</th>");
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for (idx, row) in rows.iter().enumerate() {
+        let ip: String = match row.open_ports.is_empty() {
+            true => row.ip.to_string(),
+            false => format!("<a href=\"#host-{idx}\">{}</a>", row.ip),
+        };
+        let other_ips: String = row
+            .other_ips
+            .iter()
+            .map(|ip| format!("<br><span class=\"muted\">{}</span>", escape(ip)))
+            .collect();
+        let vendor: String = row
+            .vendor
+            .as_deref()
+            .map(|vendor| badge("vendor", vendor))
+            .unwrap_or_default();
+        let roles: String = row
+            .roles
+            .iter()
+            .map(|role| badge(&format!("role role-{}", role.to_lowercase()), role))
+            .collect();
+        let (rtt, rtt_key): (String, String) = match row.rtt_ms {
+            Some(rtt) => (format!("{rtt:.2} ms"), format!("{rtt:.3}")),
+            None => (String::new(), String::new()),
+        };
+        let ports: String = row
+            .open_ports
+            .iter()
+            .map(|port| badge("port", &port_label(port)))
+            .collect();
+
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"mono\" data-sort=\"{}\">{ip}{other_ips}</td><td class=\"mono\">{}</td><td>{vendor}</td><td>{}</td><td>{roles}</td><td data-sort=\"{rtt_key}\">{rtt}</td><td data-sort=\"{}\">{ports}</td></tr>",
+            ip_sort_key(&row.ip),
+            escape(row.mac.as_deref().unwrap_or_default()),
+            escape(row.hostname.as_deref().unwrap_or_default()),
+            row.open_ports.len(),
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+fn write_port_section(html: &mut String, idx: usize, row: &HostRow) {
+    let _ = write!(
+        html,
+        "<section id=\"host-{idx}\">\n<h2 class=\"mono\">{}",
+        row.ip
+    );
+    if let Some(hostname) = &row.hostname {
+        let _ = write!(html, " <span class=\"muted\">{}</span>", escape(hostname));
+    }
+    html.push_str("</h2>\n");
+
+    html.push_str("<table class=\"sortable\">\n<thead><tr>");
+    for column in ["Port", "Service", "Version", "Reason", "Details"] {
+        let _ = write!(html, "<th>{column}</th>");
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for port in &row.open_ports {
+        let number: &str = port.port.split('/').next().unwrap_or_default();
+        let _ = writeln!(
+            html,
+            "<tr><td class=\"mono\" data-sort=\"{number}\">{}</td><td>{}</td><td>{}</td><td class=\"muted\">{}</td><td>{}</td></tr>",
+            port.port,
+            escape(port.service.as_deref().unwrap_or("unknown")),
+            escape(port.version.as_deref().unwrap_or_default()),
+            escape(&port.reason),
+            escape(port.detail.as_deref().unwrap_or_default()),
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    if row.other_ports > 0 {
+        let unit: &str = if row.other_ports == 1 {
+            "port"
+        } else {
+            "ports"
+        };
+        let _ = writeln!(
+            html,
+            "<p class=\"muted\">{} more {unit} closed or filtered</p>",
+            row.other_ports
+        );
+    }
+    html.push_str("</section>\n");
+}
+
+fn badge(class: &str, text: &str) -> String {
+    format!("<span class=\"badge {class}\">{}</span>", escape(text))
+}
+
+fn port_label(port: &PortRow) -> String {
+    match &port.service {
+        Some(service) => format!("{} {service}", port.port),
+        None => port.port.clone(),
+    }
+}
+
+/// Sorts addresses numerically instead of by text (`10.0.0.9` before `10.0.0.10`).
+fn ip_sort_key(ip: &IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ipv4_addr) => format!("4-{:010}", u32::from(*ipv4_addr)),
+        IpAddr::V6(ipv6_addr) => format!("6-{:032x}", u128::from(*ipv6_addr)),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped: String = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use zond_common::models::host::{Host, NetworkRole};
+    use zond_common::models::port::{Port, PortReason, PortState, Protocol};
+
+    use super::*;
+
+    #[test]
+    fn renders_badges_and_port_sections() {
+        let mut host: Host = Host::new(IpAddr::from([192, 168, 1, 1]));
+        host.vendor = Some("AVM <GmbH>".to_string());
+        host.network_roles.insert(NetworkRole::Gateway);
+        host.add_port(Port::new(
+            80,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        let quiet: Host = Host::new(IpAddr::from([192, 168, 1, 9]));
+        let report: Report = Report::new("0.0.0", "scan", SystemTime::now(), Duration::ZERO)
+            .with_hosts(&[host, quiet]);
+
+        let html: String = to_html(&report, &Config::default());
+        assert!(html.contains("<span class=\"badge vendor\">AVM &lt;GmbH&gt;</span>"));
+        assert!(html.contains("<span class=\"badge role role-gateway\">Gateway</span>"));
+        assert!(html.contains("<a href=\"#host-0\">192.168.1.1</a>"));
+        assert!(html.contains("<section id=\"host-0\">"));
+        assert!(!html.contains("<section id=\"host-1\">"));
+    }
+
+    #[test]
+    fn sorts_addresses_numerically() {
+        let low: String = ip_sort_key(&IpAddr::from([10, 0, 0, 9]));
+        let high: String = ip_sort_key(&IpAddr::from([10, 0, 0, 10]));
+        assert!(low < high);
+    }
+}
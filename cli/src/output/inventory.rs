@@ -0,0 +1,197 @@
+//! The **Inventory** view shared by the CSV, Markdown and HTML exporters.
+//!
+//! Flattens the hosts of a report into display strings once, applying `--redact` the same
+//! way the terminal view does: MAC addresses, hostnames and secondary IPv6 addresses are
+//! masked, the primary address is kept so rows stay distinguishable.
+
+use std::net::IpAddr;
+
+use chrono::{DateTime, Local};
+use pnet::util::MacAddr;
+use zond_common::{
+    config::Config,
+    models::{
+        port::PortState,
+        report::{HostRecord, PortRecord, Report},
+    },
+    utils::redact,
+};
+
+/// A host as the exporters print it.
+pub struct HostRow {
+    pub ip: IpAddr,
+    /// Every address besides the primary one.
+    pub other_ips: Vec<String>,
+    pub mac: Option<String>,
+    pub vendor: Option<String>,
+    pub hostname: Option<String>,
+    pub roles: Vec<String>,
+    pub rtt_ms: Option<f64>,
+    pub open_ports: Vec<PortRow>,
+    /// Closed and filtered ports, which are only counted.
+    pub other_ports: usize,
+}
+
+/// An open port as the exporters print it.
+pub struct PortRow {
+    /// The port and protocol (e.g. `22/tcp`).
+    pub port: String,
+    pub service: Option<String>,
+    pub version: Option<String>,
+    pub reason: String,
+    /// What the TLS and HTTP probes learned, in one line.
+    pub detail: Option<String>,
+}
+
+impl HostRow {
+    pub fn new(host: &HostRecord, cfg: &Config) -> Self {
+        let other_ips: Vec<String> = host
+            .ips
+            .iter()
+            .filter(|&&ip| ip != host.primary_ip)
+            .map(|ip| match ip {
+                IpAddr::V6(ipv6_addr) if cfg.redact => redact::ipv6_addr(ipv6_addr),
+                ip => ip.to_string(),
+            })
+            .collect();
+
+        let mac: Option<String> = host.mac.as_ref().map(|mac| match mac.parse::<MacAddr>() {
+            Ok(mac) if cfg.redact => redact::mac_addr(&mac),
+            _ => mac.clone(),
+        });
+        let hostname: Option<String> = host.hostname.as_ref().map(|hostname| {
+            if cfg.redact {
+                redact::hostname(hostname)
+            } else {
+                hostname.clone()
+            }
+        });
+
+        let (open, other): (Vec<&PortRecord>, Vec<&PortRecord>) = host
+            .ports
+            .iter()
+            .partition(|port| port.state == PortState::Open);
+
+        Self {
+            ip: host.primary_ip,
+            other_ips,
+            mac,
+            vendor: host.vendor.clone(),
+            hostname,
+            roles: host.roles.iter().map(|role| format!("{role:?}")).collect(),
+            rtt_ms: host.rtt.as_ref().map(|rtt| rtt.avg_ms),
+            open_ports: open.into_iter().map(PortRow::new).collect(),
+            other_ports: other.len(),
+        }
+    }
+
+    /// The open ports in one line (`22/tcp ssh, 443/tcp https`).
+    pub fn ports_summary(&self) -> String {
+        self.open_ports
+            .iter()
+            .map(|port| match &port.service {
+                Some(service) => format!("{} {service}", port.port),
+                None => port.port.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+impl PortRow {
+    fn new(port: &PortRecord) -> Self {
+        let mut details: Vec<String> = Vec::new();
+        if let Some(tls) = &port.tls {
+            details.push(tls.version.clone());
+        }
+        if let Some(http) = &port.http {
+            match &http.title {
+                Some(title) => details.push(format!("HTTP {} \"{title}\"", http.status)),
+                None => details.push(format!("HTTP {}", http.status)),
+            }
+            if let Some(server) = &http.server {
+                details.push(server.clone());
+            }
+        }
+
+        Self {
+            port: format!("{}/{}", port.port, port.protocol),
+            service: port.service.clone(),
+            version: port.version.clone(),
+            reason: port.reason.clone(),
+            detail: (!details.is_empty()).then(|| details.join(", ")),
+        }
+    }
+}
+
+/// The hosts of a report as rows, empty for reports without hosts.
+pub fn rows(report: &Report, cfg: &Config) -> Vec<HostRow> {
+    report
+        .hosts
+        .iter()
+        .flatten()
+        .map(|host| HostRow::new(host, cfg))
+        .collect()
+}
+
+/// One line about the run (`scan of 256 addresses, 12 hosts up in 4.20s`).
+pub fn run_summary(report: &Report) -> String {
+    let hosts_up: usize = report.hosts.as_ref().map_or(0, Vec::len);
+    let elapsed: f64 = report.elapsed_ms as f64 / 1000.0;
+    match report.targets {
+        Some(targets) => format!(
+            "{} of {targets} addresses, {hosts_up} hosts up in {elapsed:.2}s",
+            report.command
+        ),
+        None => format!("{}, {hosts_up} hosts up in {elapsed:.2}s", report.command),
+    }
+}
+
+/// The start of the run in local time.
+pub fn started_at(report: &Report) -> String {
+    DateTime::from_timestamp(report.started_at as i64, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use zond_common::models::host::Host;
+
+    use super::*;
+
+    #[test]
+    fn redacts_like_the_terminal_view() {
+        let mut host: Host = Host::new(IpAddr::from([192, 168, 1, 20]))
+            .with_mac(MacAddr::new(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3));
+        host.ips.insert("fe80::ca52:61ff:fec7:594".parse().unwrap());
+        host.hostname = Some("workstation".to_string());
+        let report: Report =
+            Report::new("0.0.0", "discover", SystemTime::now(), Duration::ZERO).with_hosts(&[host]);
+        let cfg: Config = Config {
+            redact: true,
+            ..Config::default()
+        };
+
+        let row: &HostRow = &rows(&report, &cfg)[0];
+        assert_eq!(row.ip.to_string(), "192.168.1.20");
+        assert_eq!(row.other_ips, ["fe80::ca52:61ff:XXXX:XXXX"]);
+        assert_eq!(row.mac.as_deref(), Some("2c:cf:67:XX:XX:XX"));
+        assert_eq!(row.hostname.as_deref(), Some("woXXXXXon"));
+    }
+}
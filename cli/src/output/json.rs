@@ -0,0 +1,28 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Serialize;
+use zond_common::success;
+
+/// Writes any versioned document (a report or a diff) to stdout.
+pub fn write_stdout<T: Serialize>(report: &T) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, report)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+/// Writes the report to `path`, or to stdout when it is `-`.
+pub fn write_file<T: Serialize>(report: &T, path: &Path) -> anyhow::Result<()> {
+    if super::is_stdout(path) {
+        return write_stdout(report);
+    }
+
+    let json: String = serde_json::to_string_pretty(report)?;
+    fs::write(path, json + "\n")
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    success!("JSON report written to {}", path.display());
+    Ok(())
+}
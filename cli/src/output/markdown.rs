@@ -0,0 +1,110 @@
+//! **Markdown** inventory for wikis and tickets.
+//!
+//! A table with one row per host, followed by a section with the open ports of every host
+//! that has any.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use zond_common::{config::Config, models::report::Report};
+
+use super::inventory::{self, HostRow};
+
+pub fn write_stdout(report: &Report, cfg: &Config) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(to_markdown(report, cfg).as_bytes())?;
+    Ok(())
+}
+
+pub fn to_markdown(report: &Report, cfg: &Config) -> String {
+    let rows: Vec<HostRow> = inventory::rows(report, cfg);
+
+    let mut md: String = String::from("# zond report\n\n");
+    let _ = writeln!(
+        md,
+        "{}, started {} (zond {}).\n",
+        capitalize(&inventory::run_summary(report)),
+        inventory::started_at(report),
+        report.zond_version
+    );
+
+    md.push_str("| IP | MAC | Vendor | Hostname | Roles | Open Ports |\n");
+    md.push_str("|---|---|---|---|---|---|\n");
+    for row in &rows {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {} |",
+            row.ip,
+            cell(row.mac.as_deref().unwrap_or_default()),
+            cell(row.vendor.as_deref().unwrap_or_default()),
+            cell(row.hostname.as_deref().unwrap_or_default()),
+            row.roles.join(", "),
+            cell(&row.ports_summary())
+        );
+    }
+
+    for row in rows.iter().filter(|row| !row.open_ports.is_empty()) {
+        let _ = write!(md, "\n## {}", row.ip);
+        if let Some(hostname) = &row.hostname {
+            let _ = write!(md, " ({})", cell(hostname));
+        }
+        md.push_str("\n\n| Port | Service | Version | Reason | Details |\n");
+        md.push_str("|---|---|---|---|---|\n");
+        for port in &row.open_ports {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                port.port,
+                cell(port.service.as_deref().unwrap_or("unknown")),
+                cell(port.version.as_deref().unwrap_or_default()),
+                port.reason,
+                cell(port.detail.as_deref().unwrap_or_default())
+            );
+        }
+    }
+    md
+}
+
+/// Escapes what would end a table cell or start inline markup.
+fn cell(value: &str) -> String {
+    let mut escaped: String = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '|' | '\\' | '`' | '*' | '_' | '<' | '>' | '[' | ']' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '\r' | '\n' => escaped.push(' '),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_table_cells() {
+        assert_eq!(cell("a|b"), "a\\|b");
+        assert_eq!(cell("my_host\nname"), "my\\_host name");
+    }
+}
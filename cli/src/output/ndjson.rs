@@ -0,0 +1,50 @@
+use std::io::{self, Write};
+
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::task::JoinHandle;
+use zond_common::models::event::{EventRecord, ScanEvent};
+use zond_core::scanner::EventTx;
+
+/// Prints every scan event as one JSON line on stdout while the scan runs.
+pub struct EventPrinter {
+    tx: EventTx,
+    task: JoinHandle<()>,
+}
+
+impl EventPrinter {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            task: tokio::spawn(print_events(rx)),
+        }
+    }
+
+    /// A sender to hand to the scanner.
+    pub fn sender(&self) -> EventTx {
+        self.tx.clone()
+    }
+
+    /// Waits until the events of every sender have been printed.
+    pub async fn finish(self) -> anyhow::Result<()> {
+        drop(self.tx);
+        self.task.await?;
+        Ok(())
+    }
+}
+
+async fn print_events(mut rx: UnboundedReceiver<ScanEvent>) {
+    while let Some(event) = rx.recv().await {
+        let Ok(line) = serde_json::to_string(&EventRecord::now(event)) else {
+            continue;
+        };
+        let mut stdout = io::stdout().lock();
+        // A closed pipe (e.g. `| head`) ends the stream but not the scan
+        if writeln!(stdout, "{line}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
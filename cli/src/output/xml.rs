@@ -0,0 +1,331 @@
+//! **Nmap XML** for tools that import nmap results.
+//!
+//! Follows the `nmaprun` layout of nmap 7 (`xmloutputversion` 1.05), so report generators
+//! and vulnerability importers read zond results like an nmap scan. Reasons and RTTs use
+//! nmap's names and units (microseconds for `<times>`).
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, Local};
+use is_root::is_root;
+use zond_common::{
+    config::Config,
+    models::report::{HostRecord, PortRecord, Report},
+    success,
+};
+
+const XML_OUTPUT_VERSION: &str = "1.05";
+/// nmap's lower bound for the probe timeout, in microseconds.
+const MIN_TIMEOUT_US: u64 = 100_000;
+
+pub fn write_stdout(report: &Report, cfg: &Config) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(to_xml(report, cfg).as_bytes())?;
+    Ok(())
+}
+
+/// Writes the report to `path`, or to stdout when it is `-`.
+pub fn write_file(report: &Report, cfg: &Config, path: &Path) -> anyhow::Result<()> {
+    if super::is_stdout(path) {
+        return write_stdout(report, cfg);
+    }
+
+    fs::write(path, to_xml(report, cfg))
+        .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    success!("XML report written to {}", path.display());
+    Ok(())
+}
+
+pub fn to_xml(report: &Report, cfg: &Config) -> String {
+    let args: String = std::env::args().collect::<Vec<String>>().join(" ");
+    let hosts: &[HostRecord] = report.hosts.as_deref().unwrap_or_default();
+
+    let mut xml: String = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<!DOCTYPE nmaprun>\n");
+    let _ = writeln!(xml, "<!-- zond {} -->", escape(&report.zond_version));
+    let _ = writeln!(
+        xml,
+        "<nmaprun scanner=\"nmap\" args=\"{}\" start=\"{}\" startstr=\"{}\" version=\"{}\" xmloutputversion=\"{XML_OUTPUT_VERSION}\">",
+        escape(&args),
+        report.started_at,
+        time_str(report.started_at),
+        escape(&report.zond_version),
+    );
+
+    if report.command == "scan" {
+        write_scan_info(&mut xml, cfg);
+    }
+    let _ = writeln!(xml, "<verbose level=\"0\"/>\n<debugging level=\"0\"/>");
+
+    for host in hosts {
+        write_host(&mut xml, host);
+    }
+
+    write_run_stats(&mut xml, report, hosts.len());
+    xml.push_str("</nmaprun>\n");
+    xml
+}
+
+/// One `<scaninfo>` per protocol, listing the probed ports like nmap does (`1-1024,8080`).
+fn write_scan_info(xml: &mut String, cfg: &Config) {
+    let root: bool = is_root();
+    let tcp_type: &str = if root { "syn" } else { "connect" };
+
+    if !cfg.ports.tcp.is_empty() {
+        let ports: Vec<u16> = cfg.ports.tcp.iter().copied().collect();
+        scan_info_line(xml, tcp_type, "tcp", &ports);
+    }
+    // UDP ports are skipped without root
+    if root && !cfg.ports.udp.is_empty() {
+        let ports: Vec<u16> = cfg.ports.udp.iter().copied().collect();
+        scan_info_line(xml, "udp", "udp", &ports);
+    }
+}
+
+fn scan_info_line(xml: &mut String, scan_type: &str, protocol: &str, ports: &[u16]) {
+    let _ = writeln!(
+        xml,
+        "<scaninfo type=\"{scan_type}\" protocol=\"{protocol}\" numservices=\"{}\" services=\"{}\"/>",
+        ports.len(),
+        compress_ports(ports)
+    );
+}
+
+fn write_host(xml: &mut String, host: &HostRecord) {
+    let (reason, reason_ttl) = host_reason(host);
+    xml.push_str("<host>");
+    let _ = writeln!(
+        xml,
+        "<status state=\"up\" reason=\"{reason}\" reason_ttl=\"{reason_ttl}\"/>"
+    );
+
+    for ip in &host.ips {
+        let addr_type: &str = if ip.is_ipv4() { "ipv4" } else { "ipv6" };
+        let _ = writeln!(xml, "<address addr=\"{ip}\" addrtype=\"{addr_type}\"/>");
+    }
+    if let Some(mac) = &host.mac {
+        let vendor: String = host
+            .vendor
+            .as_deref()
+            .map(|vendor| format!(" vendor=\"{}\"", escape(vendor)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            xml,
+            "<address addr=\"{}\" addrtype=\"mac\"{vendor}/>",
+            mac.to_uppercase()
+        );
+    }
+
+    xml.push_str("<hostnames>\n");
+    if let Some(hostname) = &host.hostname {
+        let _ = writeln!(
+            xml,
+            "<hostname name=\"{}\" type=\"PTR\"/>",
+            escape(hostname)
+        );
+    }
+    xml.push_str("</hostnames>\n");
+
+    if !host.ports.is_empty() {
+        xml.push_str("<ports>");
+        for port in &host.ports {
+            write_port(xml, port);
+        }
+        xml.push_str("</ports>\n");
+    }
+
+    if let Some(rtt) = &host.rtt {
+        let srtt: u64 = (rtt.avg_ms * 1000.0) as u64;
+        let rttvar: u64 = ((rtt.max_ms - rtt.min_ms) * 500.0) as u64;
+        let timeout: u64 = (srtt + 4 * rttvar).max(MIN_TIMEOUT_US);
+        let _ = writeln!(
+            xml,
+            "<times srtt=\"{srtt}\" rttvar=\"{rttvar}\" to=\"{timeout}\"/>"
+        );
+    }
+    xml.push_str("</host>\n");
+}
+
+fn write_port(xml: &mut String, port: &PortRecord) {
+    let _ = write!(
+        xml,
+        "<port protocol=\"{}\" portid=\"{}\"><state state=\"{}\" reason=\"{}\" reason_ttl=\"{}\"/>",
+        port.protocol,
+        port.port,
+        port.state,
+        escape(&port.reason),
+        port.ttl.unwrap_or(0)
+    );
+
+    if let Some(service) = &port.service {
+        // Detected versions were probed, everything else is a guess from the service table
+        let (product, method, conf) = match &port.version {
+            Some(version) => (format!(" product=\"{}\"", escape(version)), "probed", 10),
+            None => (String::new(), "table", 3),
+        };
+        let tunnel: &str = if port.tls.is_some() {
+            " tunnel=\"ssl\""
+        } else {
+            ""
+        };
+        let _ = write!(
+            xml,
+            "<service name=\"{}\"{product}{tunnel} method=\"{method}\" conf=\"{conf}\"/>",
+            escape(service)
+        );
+    }
+    xml.push_str("</port>\n");
+}
+
+fn write_run_stats(xml: &mut String, report: &Report, hosts_up: usize) {
+    let finished: u64 = report.started_at + report.elapsed_ms / 1000;
+    let elapsed: f64 = report.elapsed_ms as f64 / 1000.0;
+    let total: usize = report.targets.unwrap_or(hosts_up).max(hosts_up);
+    let host_word: &str = if hosts_up == 1 { "host" } else { "hosts" };
+    let summary: String = format!(
+        "zond done at {}; {total} IP addresses ({hosts_up} {host_word} up) scanned in {elapsed:.2} seconds",
+        time_str(finished)
+    );
+
+    xml.push_str("<runstats>");
+    let _ = writeln!(
+        xml,
+        "<finished time=\"{finished}\" timestr=\"{}\" summary=\"{summary}\" elapsed=\"{elapsed:.2}\" exit=\"success\"/>",
+        time_str(finished)
+    );
+    let _ = writeln!(
+        xml,
+        "<hosts up=\"{hosts_up}\" down=\"{}\" total=\"{total}\"/>",
+        total - hosts_up
+    );
+    xml.push_str("</runstats>\n");
+}
+
+/// Why the host counts as up, in nmap's words: the link layer answer for on-link hosts,
+/// otherwise the first answered port.
+fn host_reason(host: &HostRecord) -> (&str, u8) {
+    if host.mac.is_some() {
+        return match host.primary_ip {
+            IpAddr::V4(_) => ("arp-response", 0),
+            IpAddr::V6(_) => ("nd-response", 0),
+        };
+    }
+
+    host.ports
+        .iter()
+        .find(|port| {
+            matches!(
+                port.reason.as_str(),
+                "syn-ack" | "reset" | "conn-refused" | "udp-response" | "port-unreach"
+            )
+        })
+        .map(|port| (port.reason.as_str(), port.ttl.unwrap_or(0)))
+        .unwrap_or(("unknown-response", 0))
+}
+
+/// Joins sorted ports into ranges, e.g. `[22, 80, 81, 82]` to `22,80-82`.
+fn compress_ports(ports: &[u16]) -> String {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+    for &port in ports {
+        match ranges.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(port) => *end = port,
+            _ => ranges.push((port, port)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{start}-{end}"),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// nmap's `ctime()` style timestamps, e.g. `Mon Oct  5 14:03:12 2026`.
+fn time_str(unix_secs: u64) -> String {
+    DateTime::from_timestamp(unix_secs as i64, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%a %b %e %H:%M:%S %Y")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped: String = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use pnet::datalink::MacAddr;
+    use zond_common::models::{
+        host::Host,
+        port::{Port, PortReason, PortState, Protocol},
+    };
+
+    use super::*;
+
+    #[test]
+    fn compresses_consecutive_ports() {
+        assert_eq!(compress_ports(&[22, 80, 81, 82, 443]), "22,80-82,443");
+        assert_eq!(compress_ports(&[65534, 65535]), "65534-65535");
+    }
+
+    #[test]
+    fn maps_hosts_onto_nmap_elements() {
+        let mut host: Host = Host::new(IpAddr::from([10, 0, 0, 1]))
+            .with_mac(MacAddr::new(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3))
+            .with_rtt(Duration::from_millis(2));
+        host.hostname = Some("r&d.lan".to_string());
+        let mut ssh: Port =
+            Port::new(22, Protocol::Tcp, PortState::Open, PortReason::SynAck).with_ttl(64);
+        ssh.version = Some("OpenSSH 9.6".to_string());
+        host.add_port(ssh);
+
+        let report: Report = Report::new("0.0.0", "scan", SystemTime::UNIX_EPOCH, Duration::ZERO)
+            .with_targets(4)
+            .with_hosts(&[host]);
+        let xml: String = to_xml(&report, &Config::default());
+
+        assert!(xml.contains("<status state=\"up\" reason=\"arp-response\" reason_ttl=\"0\"/>"));
+        assert!(xml.contains("<address addr=\"2C:CF:67:F2:51:E3\" addrtype=\"mac\""));
+        assert!(xml.contains("<hostname name=\"r&amp;d.lan\" type=\"PTR\"/>"));
+        assert!(xml.contains(
+            "<port protocol=\"tcp\" portid=\"22\"><state state=\"open\" reason=\"syn-ack\" reason_ttl=\"64\"/>\
+             <service name=\"ssh\" product=\"OpenSSH 9.6\" method=\"probed\" conf=\"10\"/></port>"
+        ));
+        assert!(xml.contains("<times srtt=\"2000\" rttvar=\"0\" to=\"100000\"/>"));
+        assert!(xml.contains("<hosts up=\"1\" down=\"3\" total=\"4\"/>"));
+    }
+}
@@ -66,3 +66,9 @@ pub const MAC_ADDR: Color = Color::TrueColor {
     g: 165,
     b: 0,
 }; // Soft Orange
+
+pub const PORT: Color = Color::TrueColor {
+    r: 135,
+    g: 206,
+    b: 135,
+}; // Sage Green
@@ -1,10 +1,19 @@
 use crate::terminal::colors;
+use chrono::{DateTime, Local, Utc};
 use colored::*;
 use pnet::util::MacAddr;
+use std::collections::BTreeMap;
 use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
 use zond_common::config::Config;
 use zond_common::models::host::Host;
-use zond_common::utils::{ip, redact};
+use zond_common::models::http::HttpInfo;
+use zond_common::models::port::{Port, PortState};
+use zond_common::models::tls::TlsInfo;
+use zond_common::utils::redact;
+
+/// Certificates expiring sooner than this are highlighted.
+const CERT_EXPIRY_WARNING: Duration = Duration::from_secs(30 * 86_400);
 
 // Logic moved from network/ip.rs
 pub fn ipv6_to_type_str(ipv6_addr: &Ipv6Addr) -> &'static str {
@@ -30,15 +39,9 @@ pub fn ip_to_detail(host: &Host, cfg: &Config) -> Vec<(String, ColoredString)> {
                 (String::from("IPv4"), value)
             }
             IpAddr::V6(ipv6_addr) => {
-                let ipv6_type: &str = ipv6_to_type_str(&ipv6_addr);
+                let ipv6_type: &str = ipv6_to_type_str(ipv6_addr);
                 let ipv6_addr: ColoredString = if cfg.redact {
-                    let ip_str: String = match ip::get_ipv6_type(ipv6_addr) {
-                        ip::Ipv6AddressType::GlobalUnicast => redact::global_unicast(&ipv6_addr),
-                        ip::Ipv6AddressType::UniqueLocal => redact::unique_local(&ipv6_addr),
-                        ip::Ipv6AddressType::LinkLocal => redact::link_local(&ipv6_addr),
-                        _ => ipv6_addr.to_string(),
-                    };
-                    ip_str.color(colors::IPV6_ADDR)
+                    redact::ipv6_addr(ipv6_addr).color(colors::IPV6_ADDR)
                 } else {
                     ipv6_addr.to_string().color(colors::IPV6_ADDR)
                 };
@@ -94,6 +97,33 @@ pub fn mac_to_detail(mac_opt: &Option<MacAddr>, cfg: &Config) -> Option<(String,
     result
 }
 
+/// A MAC address read back from a report, redacted like [`mac_to_detail`] does.
+pub fn mac_str(mac: &str, cfg: &Config) -> String {
+    match mac.parse::<MacAddr>() {
+        Ok(mac) if cfg.redact => redact::mac_addr(&mac),
+        _ => mac.to_string(),
+    }
+}
+
+pub fn hostname_str(hostname: &str, cfg: &Config) -> String {
+    if cfg.redact {
+        redact::hostname(hostname)
+    } else {
+        hostname.to_string()
+    }
+}
+
+/// A timestamp of a report (seconds since the Unix epoch) in local time.
+pub fn local_time(unix_secs: u64) -> String {
+    DateTime::from_timestamp(unix_secs as i64, 0)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
 pub fn vendor_to_detail(vendor_opt: &Option<String>) -> Option<(String, ColoredString)> {
     vendor_opt.as_ref().map(|vendor| {
         (
@@ -102,3 +132,192 @@ pub fn vendor_to_detail(vendor_opt: &Option<String>) -> Option<(String, ColoredS
         )
     })
 }
+
+/// One detail per open port (plus its TLS and HTTP details), followed by a count for every other state.
+pub fn ports_to_details(ports: &[Port], cfg: &Config) -> Vec<(String, ColoredString)> {
+    let mut details: Vec<(String, ColoredString)> = Vec::new();
+
+    for port in ports.iter().filter(|port| port.is_open()) {
+        let key: String = format!("{}/{}", port.number, port.protocol);
+        let mut value: String = port.service.as_deref().unwrap_or("unknown").to_string();
+
+        if let Some(version) = &port.version {
+            value.push_str(&format!(" {version}"));
+        }
+
+        let reason: String = match port.ttl {
+            Some(ttl) => format!("({}, ttl {ttl})", port.reason),
+            None => format!("({})", port.reason),
+        };
+
+        details.push((
+            key,
+            format!(
+                "{} {}",
+                value.color(colors::PORT),
+                reason.color(colors::SEPARATOR)
+            )
+            .normal(),
+        ));
+
+        if let Some(tls) = &port.tls {
+            details.extend(tls_to_details(tls, cfg));
+        }
+
+        if let Some(http) = &port.http {
+            details.extend(http_to_details(http, cfg));
+        }
+    }
+
+    let mut state_counts: BTreeMap<PortState, usize> = BTreeMap::new();
+    for port in ports.iter().filter(|port| !port.is_open()) {
+        *state_counts.entry(port.state).or_default() += 1;
+    }
+
+    for (state, count) in state_counts {
+        let unit: &str = if count == 1 { "port" } else { "ports" };
+        let key: String = capitalize(&state.to_string());
+        details.push((key, format!("{count} {unit}").color(colors::SEPARATOR)));
+    }
+
+    details
+}
+
+/// Indented details about the TLS session of a port and the certificate it presented.
+fn tls_to_details(tls: &TlsInfo, cfg: &Config) -> Vec<(String, ColoredString)> {
+    let mut details: Vec<(String, ColoredString)> = vec![(
+        "  tls".to_string(),
+        format!("{} {}", tls.version, tls.cipher).color(colors::SEPARATOR),
+    )];
+
+    let Some(cert) = &tls.certificate else {
+        return details;
+    };
+
+    let redact_dn = |dn: &str| -> String {
+        if !cfg.redact {
+            return dn.to_string();
+        }
+        dn.split(", ")
+            .map(|part| match part.strip_prefix("CN=") {
+                Some(name) => format!("CN={}", redact::hostname(name)),
+                None => part.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    details.push(("  subject".to_string(), redact_dn(&cert.subject).normal()));
+
+    let issuer: String = if cert.is_self_signed() {
+        "self-signed".to_string()
+    } else {
+        redact_dn(&cert.issuer)
+    };
+    details.push(("  issuer".to_string(), issuer.normal()));
+
+    if !cert.sans.is_empty() {
+        let sans: Vec<String> = cert
+            .sans
+            .iter()
+            .map(|san| {
+                if cfg.redact {
+                    redact::hostname(san)
+                } else {
+                    san.clone()
+                }
+            })
+            .collect();
+        details.push((
+            "  sans".to_string(),
+            sans.join(", ").color(colors::HOSTNAME),
+        ));
+    }
+
+    let validity: String = format!(
+        "{} - {}",
+        DateTime::<Utc>::from(cert.not_before).format("%Y-%m-%d"),
+        DateTime::<Utc>::from(cert.not_after).format("%Y-%m-%d")
+    );
+    let validity: ColoredString = match cert.remaining() {
+        None => format!("{validity} (expired)").red(),
+        Some(left) if left < CERT_EXPIRY_WARNING => {
+            format!("{validity} (expires in {} days)", left.as_secs() / 86_400).yellow()
+        }
+        Some(_) => validity.normal(),
+    };
+    details.push(("  valid".to_string(), validity));
+
+    let key: String = match cert.key_bits {
+        Some(bits) => format!("{} {bits} bits", cert.key_type),
+        None => cert.key_type.clone(),
+    };
+    details.push(("  key".to_string(), key.normal()));
+
+    details
+}
+
+/// Indented details about the landing page of a web service.
+fn http_to_details(http: &HttpInfo, cfg: &Config) -> Vec<(String, ColoredString)> {
+    let status: ColoredString = match http.status {
+        200..=299 => http.status.to_string().green(),
+        300..=399 => http.status.to_string().yellow(),
+        _ => http.status.to_string().red(),
+    };
+    let page: ColoredString = match &http.title {
+        Some(title) => format!("{status} {}", title.color(colors::TEXT_DEFAULT)).normal(),
+        None => status,
+    };
+    let mut details: Vec<(String, ColoredString)> = vec![("  http".to_string(), page)];
+
+    let server: Option<String> = match (&http.server, &http.powered_by) {
+        (Some(server), Some(powered_by)) => Some(format!("{server} ({powered_by})")),
+        (Some(server), None) => Some(server.clone()),
+        (None, Some(powered_by)) => Some(powered_by.clone()),
+        (None, None) => None,
+    };
+    if let Some(server) = server {
+        details.push(("  server".to_string(), server.normal()));
+    }
+
+    if !http.redirects.is_empty() {
+        let chain: Vec<String> = http
+            .redirects
+            .iter()
+            .map(|location| {
+                if cfg.redact {
+                    redact_url(location)
+                } else {
+                    location.clone()
+                }
+            })
+            .collect();
+        details.push((
+            "  redirect".to_string(),
+            chain.join(" -> ").color(colors::SEPARATOR),
+        ));
+    }
+
+    if let Some(hash) = http.favicon_hash {
+        details.push(("  favicon".to_string(), hash.to_string().normal()));
+    }
+
+    details
+}
+
+/// Redacts the host of an absolute URL, leaving relative locations untouched.
+fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let (authority, path) = rest.split_at(rest.find(['/', '?', '#']).unwrap_or(rest.len()));
+    format!("{scheme}://{}{path}", redact::hostname(authority))
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
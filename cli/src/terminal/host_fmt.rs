@@ -0,0 +1,112 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use colored::*;
+use unicode_width::UnicodeWidthStr;
+use zond_common::{
+    config::Config,
+    models::host::{Host, NetworkRole},
+};
+
+use crate::{
+    mprint,
+    terminal::{
+        colors, format,
+        print::{self, TOTAL_WIDTH},
+    },
+};
+
+pub type Detail = (String, ColoredString);
+
+pub fn print_host_tree(host: &Host, idx: usize, cfg: &Config) {
+    print_host_tree_with(host, idx, Vec::new(), cfg);
+}
+
+/// Prints the host tree with `extra` details appended before the ports.
+pub fn print_host_tree_with(host: &Host, idx: usize, extra: Vec<Detail>, cfg: &Config) {
+    let primary_ip: IpAddr = host.primary_ip;
+    print_host_head(idx, &primary_ip, host);
+    let mut details: Vec<Detail> = format::ip_to_detail(host, cfg);
+
+    if let Some(mac_detai) = format::mac_to_detail(&host.mac, cfg) {
+        details.push(mac_detai);
+    }
+
+    if let Some(vendor_detail) = format::vendor_to_detail(&host.vendor) {
+        details.push(vendor_detail);
+    }
+
+    if let Some(hostname_detail) = format::hostname_to_detail(&host.hostname, cfg) {
+        details.push(hostname_detail);
+    }
+
+    if !host.network_roles.is_empty() {
+        let mut roles: Vec<&NetworkRole> = host.network_roles.iter().collect();
+        roles.sort();
+        let joined_roles: String = roles
+            .iter()
+            .map(|role| format!("{:?}", role))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let roles_detail: (String, ColoredString) = ("Roles".to_string(), joined_roles.normal());
+
+        details.push(roles_detail);
+    }
+
+    details.extend(extra);
+    details.extend(format::ports_to_details(&host.ports, cfg));
+
+    print::as_tree(details);
+}
+
+fn print_host_head(idx: usize, primary_ip: &IpAddr, host: &Host) {
+    let rtt_string: String = rtt_to_string(host);
+    let rtt_width: usize = rtt_string.width();
+
+    let block_width: usize = 20;
+    let local_pad: usize = block_width.saturating_sub(rtt_width);
+    let right_part: String = format!("{}{}", " ".repeat(local_pad), rtt_string);
+
+    let left_part: String = format!("[{}] {}", idx, primary_ip);
+
+    let used_width: usize = left_part.width() + block_width;
+
+    let padding_len: usize = TOTAL_WIDTH.saturating_sub(used_width + 1);
+    let padding: String = " ".repeat(padding_len);
+
+    let output: String = format!(
+        "{} {}{}{}",
+        format!("[{}]", idx.to_string().color(colors::ACCENT)).color(colors::SEPARATOR),
+        primary_ip.to_string().color(colors::PRIMARY),
+        padding,
+        right_part.color(colors::SECONDARY)
+    );
+
+    mprint!(&output);
+}
+
+fn rtt_to_string(host: &Host) -> String {
+    let min_rtt: Option<Duration> = host.min_rtt();
+
+    if min_rtt.is_none() {
+        return String::new();
+    }
+
+    let min_rtt: Duration = host.min_rtt().unwrap();
+    let max_rtt: Duration = host.max_rtt().unwrap();
+    let avg_rtt: Duration = host.average_rtt().unwrap();
+
+    if min_rtt == max_rtt {
+        return format!("⌛ {}ms", min_rtt.as_millis());
+    }
+
+    let spread: Duration = max_rtt.saturating_sub(min_rtt);
+    let tolerance: Duration = min_rtt.mul_f64(0.05).max(Duration::from_millis(2));
+
+    if tolerance > spread {
+        return format!("⌛ ~{}ms", avg_rtt.as_millis());
+    }
+
+    format!("⌛ {}ms - {}ms", min_rtt.as_millis(), max_rtt.as_millis())
+}
@@ -1,6 +1,7 @@
 pub mod banner;
 pub mod colors;
 pub mod format;
+pub mod host_fmt;
 pub mod logging;
 pub mod network_fmt;
 pub mod print;
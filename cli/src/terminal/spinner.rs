@@ -8,6 +8,7 @@ use indicatif::ProgressStyle;
 use tracing::Span;
 use tracing_indicatif::{IndicatifLayer, span_ext::IndicatifSpanExt};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use zond_core::scanner::ScanState;
 
 use crate::terminal::logging;
 
@@ -51,7 +52,11 @@ pub fn init_logging(verbosity: u8) {
         .init();
 }
 
-pub fn start_discovery_spinner(span: Span, running: Arc<AtomicBool>) -> JoinHandle<()> {
+pub fn start_discovery_spinner(
+    span: Span,
+    running: Arc<AtomicBool>,
+    state: Arc<ScanState>,
+) -> JoinHandle<()> {
     thread::spawn(move || {
         let mut tip_index = rand::random_range(0..TIPS.len());
         let mut last_phase = 0;
@@ -76,7 +81,7 @@ pub fn start_discovery_spinner(span: Span, running: Arc<AtomicBool>) -> JoinHand
                     tip_index = new_index;
                 }
             } else {
-                let count: usize = zond_core::scanner::get_host_count();
+                let count: usize = state.host_count();
                 let host_str: &str = match count {
                     1 => "host",
                     _ => "hosts",
@@ -1,3 +1,9 @@
+use std::path::PathBuf;
+
+use crate::models::port::PortSet;
+use crate::models::probe::DiscoveryProbe;
+
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     /// Keep logs and colors but hide the ASCII art
     pub no_banner: bool,
@@ -10,9 +16,24 @@ pub struct Config {
     /// Redact sensitive info (IPv6 suffixes, MAC addresses etc.)
     pub redact: bool,
 
-    /// Reduce UI visual density (1: reduce styling, 2: raw IPs)
+    /// Reduce UI visual density (1: reduce styling, 2: one tab separated line per host)
     pub quiet: u8,
 
     /// Disable user input listening (e.g. for non-interactive tests)
     pub disable_input: bool,
+
+    /// Ports probed by `scan`, and by `discover` when set (defaults to 443 there)
+    pub ports: PortSet,
+
+    /// Discovery probes chosen by the user; empty means the defaults
+    pub probes: Vec<DiscoveryProbe>,
+
+    /// Upper bound of concurrent connect() attempts for unprivileged scans
+    pub max_parallelism: Option<usize>,
+
+    /// Identify services and versions behind open TCP ports after a port scan
+    pub service_detection: bool,
+
+    /// Extra service signatures, tried before the embedded ones
+    pub signatures: Option<PathBuf>,
 }
@@ -0,0 +1,432 @@
+//! # Report Diff
+//!
+//! What changed on the network between two runs.
+//!
+//! ## Key Concepts
+//! * **Identity**: Hosts are matched like in the history, by MAC or by IP without one
+//!   (see [`HostKey`]). A host that shows up under the same IP with a different MAC is
+//!   reported as a MAC change instead of one host leaving and another joining, since that
+//!   is what ARP spoofing looks like.
+//! * **Claimed Addresses**: Every address of every host counts, not only the primary one.
+//!   A MAC answering for an address another MAC held before, or holds alongside it, is
+//!   flagged on that host as an [`IpClaim`] (e.g. a spoofed gateway IP that was merged
+//!   into the attacker's host as a secondary address).
+//! * **Comparable Facts Only**: Ports are compared only where both runs probed them, and
+//!   hostnames when both runs resolved one, so a `discover`, a scan of other ports or a run
+//!   with `-n` does not report everything as changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::HostKey;
+use crate::models::port::{PortSet, PortState};
+use crate::models::report::{HostRecord, Report, SCHEMA_VERSION};
+
+/// The differences between an older and a newer run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub schema_version: u32,
+    pub old: RunInfo,
+    pub new: RunInfo,
+    /// Hosts that appeared, disappeared or changed, ordered by IP.
+    pub hosts: Vec<HostDiff>,
+}
+
+/// Which run one side of the diff is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunInfo {
+    pub command: String,
+    pub started_at: u64,
+    pub hosts_up: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    /// Only in the newer run.
+    New,
+    /// Only in the older run.
+    Gone,
+    /// In both runs, with differences.
+    Changed,
+}
+
+/// One host that differs between the runs. The host fields describe the newer run,
+/// or the older one for hosts that are gone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostDiff {
+    pub status: DiffStatus,
+    pub ip: IpAddr,
+    pub mac: Option<String>,
+    pub vendor: Option<String>,
+    pub hostname: Option<String>,
+    /// Set when the same MAC answered from another IP.
+    pub previous_ip: Option<IpAddr>,
+    /// Set when the same IP answered with another MAC.
+    pub previous_mac: Option<String>,
+    /// Set when the resolved name changed.
+    pub previous_hostname: Option<String>,
+    /// Addresses this host answered for that other MACs held before or hold as well.
+    pub claims: Vec<IpClaim>,
+    pub opened: Vec<String>,
+    pub closed: Vec<String>,
+}
+
+/// An address answered by a MAC it did not belong to before, or by several MACs at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IpClaim {
+    pub ip: IpAddr,
+    /// The other MACs that held the address in the older run or hold it in the newer one.
+    pub other_macs: Vec<String>,
+}
+
+impl HostDiff {
+    fn new(status: DiffStatus, host: &HostRecord) -> Self {
+        Self {
+            status,
+            ip: host.primary_ip,
+            mac: host.mac.clone(),
+            vendor: host.vendor.clone(),
+            hostname: host.hostname.clone(),
+            previous_ip: None,
+            previous_mac: None,
+            previous_hostname: None,
+            claims: Vec::new(),
+            opened: Vec::new(),
+            closed: Vec::new(),
+        }
+    }
+
+    /// Compares two sightings of the same host, `None` if nothing changed.
+    /// Ports are compared among `probed`, the ports both runs probed.
+    fn between(old: &HostRecord, new: &HostRecord, probed: Option<&PortSet>) -> Option<Self> {
+        let mut diff: HostDiff = HostDiff::new(DiffStatus::Changed, new);
+
+        if old.primary_ip != new.primary_ip {
+            diff.previous_ip = Some(old.primary_ip);
+        }
+        if old.mac.is_some() && new.mac.is_some() && old.mac != new.mac {
+            diff.previous_mac = old.mac.clone();
+        }
+        if old.hostname.is_some() && new.hostname.is_some() && old.hostname != new.hostname {
+            diff.previous_hostname = old.hostname.clone();
+        }
+        if let Some(probed) = probed {
+            let before: BTreeSet<String> = open_ports(old, probed);
+            let now: BTreeSet<String> = open_ports(new, probed);
+            diff.opened = now.difference(&before).cloned().collect();
+            diff.closed = before.difference(&now).cloned().collect();
+        }
+
+        let changed: bool = diff.previous_ip.is_some()
+            || diff.previous_mac.is_some()
+            || diff.previous_hostname.is_some()
+            || !diff.opened.is_empty()
+            || !diff.closed.is_empty();
+        changed.then_some(diff)
+    }
+}
+
+impl ReportDiff {
+    pub fn new(old: &Report, new: &Report) -> Self {
+        let probed: Option<PortSet> = old
+            .probed_ports()
+            .zip(new.probed_ports())
+            .map(|(old_ports, new_ports)| old_ports.intersection(&new_ports));
+        let mut old_hosts: BTreeMap<HostKey, &HostRecord> = by_key(old);
+        let mut new_hosts: BTreeMap<HostKey, &HostRecord> = by_key(new);
+        let mut hosts: Vec<HostDiff> = Vec::new();
+
+        // Same identity in both runs
+        let shared: Vec<HostKey> = old_hosts
+            .keys()
+            .filter(|key| new_hosts.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in shared {
+            let (old_host, new_host) = (old_hosts.remove(&key), new_hosts.remove(&key));
+            if let (Some(old_host), Some(new_host)) = (old_host, new_host) {
+                hosts.extend(HostDiff::between(old_host, new_host, probed.as_ref()));
+            }
+        }
+
+        // Same IP under another identity: a new MAC, or a MAC that one run could not see
+        let unmatched: Vec<HostKey> = new_hosts.keys().cloned().collect();
+        for key in unmatched {
+            let new_host: &HostRecord = new_hosts[&key];
+            let Some(old_key) = old_hosts
+                .iter()
+                .find(|(_, old_host)| old_host.primary_ip == new_host.primary_ip)
+                .map(|(old_key, _)| old_key.clone())
+            else {
+                continue;
+            };
+            if let Some(old_host) = old_hosts.remove(&old_key) {
+                new_hosts.remove(&key);
+                hosts.extend(HostDiff::between(old_host, new_host, probed.as_ref()));
+            }
+        }
+
+        hosts.extend(
+            new_hosts
+                .values()
+                .map(|host| HostDiff::new(DiffStatus::New, host)),
+        );
+        hosts.extend(
+            old_hosts
+                .values()
+                .map(|host| HostDiff::new(DiffStatus::Gone, host)),
+        );
+        add_claims(old, new, &mut hosts);
+        hosts.sort_by_key(|diff| diff.ip);
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            old: RunInfo::of(old),
+            new: RunInfo::of(new),
+            hosts,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    pub fn count(&self, status: DiffStatus) -> usize {
+        self.hosts
+            .iter()
+            .filter(|diff| diff.status == status)
+            .count()
+    }
+}
+
+impl RunInfo {
+    fn of(report: &Report) -> Self {
+        Self {
+            command: report.command.clone(),
+            started_at: report.started_at,
+            hosts_up: report.hosts.as_ref().map_or(0, Vec::len),
+        }
+    }
+}
+
+fn by_key(report: &Report) -> BTreeMap<HostKey, &HostRecord> {
+    hosts_of(report)
+        .map(|host| (HostKey::of(host), host))
+        .collect()
+}
+
+/// Flags every address that a MAC took over from another one, or shares with another one,
+/// unless it is already reported as the MAC change of a host with that primary IP.
+fn add_claims(old: &Report, new: &Report, hosts: &mut Vec<HostDiff>) {
+    let (old_macs, new_macs) = (macs_by_ip(old), macs_by_ip(new));
+
+    for (ip, macs) in &new_macs {
+        let held: Option<&BTreeSet<String>> = old_macs.get(ip);
+        for mac in macs {
+            if held.is_some_and(|held| held.contains(mac)) {
+                continue;
+            }
+            let other_macs: Vec<String> = held
+                .into_iter()
+                .flatten()
+                .chain(macs)
+                .filter(|other| *other != mac)
+                .collect::<BTreeSet<&String>>()
+                .into_iter()
+                .cloned()
+                .collect();
+            if other_macs.is_empty() {
+                continue;
+            }
+
+            let owns = |diff: &HostDiff| {
+                diff.status != DiffStatus::Gone
+                    && diff
+                        .mac
+                        .as_deref()
+                        .is_some_and(|own| own.eq_ignore_ascii_case(mac))
+            };
+            let idx: usize = match hosts.iter().position(owns) {
+                Some(idx) if hosts[idx].ip == *ip && hosts[idx].previous_mac.is_some() => continue,
+                Some(idx) => idx,
+                // The host itself did not change, only what it answers for
+                None => {
+                    let Some(host) = hosts_of(new).find(|host| {
+                        host.mac
+                            .as_deref()
+                            .is_some_and(|own| own.eq_ignore_ascii_case(mac))
+                    }) else {
+                        continue;
+                    };
+                    hosts.push(HostDiff::new(DiffStatus::Changed, host));
+                    hosts.len() - 1
+                }
+            };
+            hosts[idx].claims.push(IpClaim {
+                ip: *ip,
+                other_macs,
+            });
+        }
+    }
+}
+
+/// The MACs answering for each address, primary or not. Hosts without a MAC are left out.
+fn macs_by_ip(report: &Report) -> BTreeMap<IpAddr, BTreeSet<String>> {
+    let mut macs: BTreeMap<IpAddr, BTreeSet<String>> = BTreeMap::new();
+    for host in hosts_of(report) {
+        let Some(mac) = &host.mac else {
+            continue;
+        };
+        for ip in host.ips.iter().chain([&host.primary_ip]) {
+            macs.entry(*ip).or_default().insert(mac.to_lowercase());
+        }
+    }
+    macs
+}
+
+fn hosts_of(report: &Report) -> impl Iterator<Item = &HostRecord> {
+    report.hosts.iter().flatten()
+}
+
+fn open_ports(host: &HostRecord, probed: &PortSet) -> BTreeSet<String> {
+    host.ports
+        .iter()
+        .filter(|port| port.state == PortState::Open && probed.contains(port.protocol, port.port))
+        .map(|port| format!("{}/{}", port.port, port.protocol))
+        .collect()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use pnet::datalink::MacAddr;
+
+    use super::*;
+    use crate::models::host::Host;
+    use crate::models::port::{Port, PortReason, Protocol};
+
+    const PHONE: MacAddr = MacAddr(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3);
+    const ROUTER: MacAddr = MacAddr(0x3c, 0xa6, 0x2f, 0x00, 0x00, 0x01);
+    const ROGUE: MacAddr = MacAddr(0x02, 0x00, 0x00, 0x00, 0x00, 0x66);
+
+    fn host(ip: [u8; 4], mac: Option<MacAddr>, ports: &[u16]) -> Host {
+        let mut host: Host = Host::new(IpAddr::from(ip));
+        host.mac = mac;
+        for &port in ports {
+            host.add_port(Port::new(
+                port,
+                Protocol::Tcp,
+                PortState::Open,
+                PortReason::SynAck,
+            ));
+        }
+        host
+    }
+
+    fn scan(hosts: &[Host]) -> Report {
+        scan_of("1-1024", hosts)
+    }
+
+    fn scan_of(ports: &str, hosts: &[Host]) -> Report {
+        Report::new("0.0.0", "scan", SystemTime::now(), Duration::ZERO)
+            .with_ports(&PortSet::parse(ports).unwrap())
+            .with_hosts(hosts)
+    }
+
+    #[test]
+    fn reports_new_gone_and_moved_hosts() {
+        let old: Report = scan(&[
+            host([192, 168, 1, 1], Some(ROUTER), &[53]),
+            host([192, 168, 1, 5], Some(PHONE), &[]),
+            host([192, 168, 1, 8], None, &[]),
+        ]);
+        let new: Report = scan(&[
+            host([192, 168, 1, 1], Some(ROUTER), &[53, 80]),
+            host([192, 168, 1, 9], Some(PHONE), &[]),
+            host([192, 168, 1, 20], None, &[]),
+        ]);
+        let diff: ReportDiff = ReportDiff::new(&old, &new);
+
+        let statuses: Vec<(IpAddr, DiffStatus)> = diff
+            .hosts
+            .iter()
+            .map(|host| (host.ip, host.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (IpAddr::from([192, 168, 1, 1]), DiffStatus::Changed),
+                (IpAddr::from([192, 168, 1, 8]), DiffStatus::Gone),
+                (IpAddr::from([192, 168, 1, 9]), DiffStatus::Changed),
+                (IpAddr::from([192, 168, 1, 20]), DiffStatus::New),
+            ]
+        );
+        assert_eq!(diff.hosts[0].opened, ["80/tcp"]);
+        assert_eq!(
+            diff.hosts[2].previous_ip,
+            Some(IpAddr::from([192, 168, 1, 5]))
+        );
+    }
+
+    #[test]
+    fn compares_only_ports_both_runs_probed() {
+        let old: Report = scan_of("22,80", &[host([192, 168, 1, 1], Some(ROUTER), &[22, 80])]);
+        let new: Report = scan_of("22,443", &[host([192, 168, 1, 1], Some(ROUTER), &[443])]);
+        let diff: ReportDiff = ReportDiff::new(&old, &new);
+
+        assert_eq!(diff.hosts.len(), 1);
+        assert!(diff.hosts[0].opened.is_empty());
+        assert_eq!(diff.hosts[0].closed, ["22/tcp"]);
+    }
+
+    #[test]
+    fn reports_a_new_mac_on_a_known_ip() {
+        let old: Report = scan(&[host([192, 168, 1, 1], Some(ROUTER), &[])]);
+        let new: Report = scan(&[host([192, 168, 1, 1], Some(ROGUE), &[])]);
+        let diff: ReportDiff = ReportDiff::new(&old, &new);
+
+        assert_eq!(diff.hosts.len(), 1);
+        assert_eq!(diff.hosts[0].status, DiffStatus::Changed);
+        assert_eq!(diff.hosts[0].previous_mac, Some(ROUTER.to_string()));
+        assert!(ReportDiff::new(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn flags_a_known_ip_claimed_as_a_secondary_address() {
+        // Replies are merged by MAC, so the spoofed gateway IP ends up on the rogue host
+        let mut rogue: Host = host([192, 168, 1, 66], Some(ROGUE), &[]);
+        rogue.ips.insert(IpAddr::from([192, 168, 1, 1]));
+        let old: Report = scan(&[
+            host([192, 168, 1, 1], Some(ROUTER), &[]),
+            host([192, 168, 1, 66], Some(ROGUE), &[]),
+        ]);
+        let new: Report = scan(&[host([192, 168, 1, 1], Some(ROUTER), &[]), rogue]);
+        let diff: ReportDiff = ReportDiff::new(&old, &new);
+
+        assert_eq!(diff.hosts.len(), 1);
+        assert_eq!(diff.hosts[0].ip, IpAddr::from([192, 168, 1, 66]));
+        assert_eq!(diff.hosts[0].status, DiffStatus::Changed);
+        assert_eq!(
+            diff.hosts[0].claims,
+            [IpClaim {
+                ip: IpAddr::from([192, 168, 1, 1]),
+                other_macs: vec![ROUTER.to_string()],
+            }]
+        );
+        // Already flagged in the older run, so not flagged again
+        assert!(ReportDiff::new(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,473 @@
+//! # Scan History
+//!
+//! A local record of every `discover` and `scan`, so devices can be followed across runs.
+//!
+//! ## Key Concepts
+//! * **Single File**: Runs are appended to `history.jsonl` under `$XDG_DATA_HOME/zond`
+//!   (`~/.local/share/zond` by default), one [`Report`] per line. A damaged line only
+//!   loses that run.
+//! * **One History per User**: Under `sudo`, runs go to the history of the invoking user,
+//!   and the files are handed back to them. A root shell keeps a history of its own.
+//!   Since that directory is in the hands of the user, symlinks and hard links in it are
+//!   refused rather than followed with root's rights.
+//! * **Identity**: A host is the same device across runs when its MAC matches, or its IP
+//!   when no MAC is known (e.g. hosts behind a router), see [`HostKey`].
+//! * **Timeline**: [`HostHistory`] collects every observation of one device, with the IPs,
+//!   hostname and ports it had at the time.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::net::IpAddr;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt, fchown, lchown};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, ensure};
+use is_root::is_root;
+use pnet::datalink::MacAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::models::port::{PortSet, PortState};
+use crate::models::report::{HostRecord, Report};
+use crate::warn;
+
+const FILE_NAME: &str = "history.jsonl";
+
+/// The file holding the recorded runs.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+    /// The user (uid, gid) the files are handed to, set when running under `sudo`.
+    owner: Option<(u32, u32)>,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, owner: None }
+    }
+
+    /// The store under `$XDG_DATA_HOME/zond`, or `~/.local/share/zond` without it.
+    ///
+    /// Under `sudo`, the home is the one of the invoking user rather than root's, so the
+    /// runs end up in the same history as the ones made without `sudo`.
+    pub fn open_default() -> Option<Self> {
+        let sudo_user: Option<(PathBuf, (u32, u32))> = if is_root() { sudo_user() } else { None };
+        let home: Option<PathBuf> = match &sudo_user {
+            Some((home, _)) => Some(home.clone()),
+            None => std::env::var_os("HOME").map(PathBuf::from),
+        };
+        let data_home: PathBuf = match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home?.join(".local/share"),
+        };
+
+        Some(Self {
+            path: data_home.join("zond").join(FILE_NAME),
+            owner: sudo_user.map(|(_, owner)| owner),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends one run, creating the store on first use.
+    pub fn append(&self, report: &Report) -> anyhow::Result<()> {
+        if self.owner.is_none() && is_root() && !self.path.exists() {
+            warn!(
+                "Recording the runs of root in '{}', apart from the history of other users",
+                self.path.display()
+            );
+        }
+
+        // Directories created here, e.g. `~/.local/share` in a fresh home
+        let mut created: Vec<PathBuf> = Vec::new();
+        if let Some(dir) = self.path.parent() {
+            created = dir
+                .ancestors()
+                .take_while(|dir| !dir.exists())
+                .map(Path::to_path_buf)
+                .collect();
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create '{}'", dir.display()))?;
+        }
+
+        if let (Some((uid, gid)), Some(dir)) = (self.owner, self.path.parent()) {
+            for path in &created {
+                lchown(path, Some(uid), Some(gid))
+                    .with_context(|| format!("Failed to hand '{}' to uid {uid}", path.display()))?;
+            }
+            let meta: Metadata = fs::symlink_metadata(dir)
+                .with_context(|| format!("Failed to inspect '{}'", dir.display()))?;
+            ensure!(
+                meta.is_dir() && meta.uid() == uid,
+                "Refusing to write the history to '{}', it is not a directory owned by uid {uid}",
+                dir.display()
+            );
+        }
+
+        // A symlink in place of the file would have root write wherever it points
+        let mut file: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open '{}'", self.path.display()))?;
+        let meta: Metadata = file.metadata()?;
+        ensure!(
+            meta.is_file() && meta.nlink() == 1,
+            "Refusing to write the history to '{}', it is not a plain file",
+            self.path.display()
+        );
+        if let Some((uid, gid)) = self.owner {
+            fchown(&file, Some(uid), Some(gid)).with_context(|| {
+                format!("Failed to hand '{}' to uid {uid}", self.path.display())
+            })?;
+        }
+
+        let mut line: String = serde_json::to_string(report)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write '{}'", self.path.display()))
+    }
+
+    /// Every recorded run, oldest first. A missing store has no runs.
+    pub fn load(&self) -> anyhow::Result<Vec<Report>> {
+        let content: String = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read '{}'", self.path.display()));
+            }
+        };
+
+        let mut reports: Vec<Report> = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Report>(line) {
+                Ok(report) => reports.push(report),
+                Err(e) => warn!(
+                    verbosity = 1,
+                    "Skipping line {} of the history: {e}",
+                    idx + 1
+                ),
+            }
+        }
+        reports.sort_by_key(|report| report.started_at);
+        Ok(reports)
+    }
+}
+
+/// What makes two observations the same device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKey {
+    Mac(String),
+    Ip(IpAddr),
+}
+
+impl HostKey {
+    pub fn of(host: &HostRecord) -> Self {
+        match &host.mac {
+            Some(mac) => Self::Mac(mac.to_lowercase()),
+            None => Self::Ip(host.primary_ip),
+        }
+    }
+
+    /// Reads a MAC (`aa:bb:cc:dd:ee:ff`) or an IP address.
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Ok(mac) = value.parse::<MacAddr>() {
+            return Some(Self::Mac(mac.to_string()));
+        }
+        value.parse::<IpAddr>().ok().map(Self::Ip)
+    }
+}
+
+impl fmt::Display for HostKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostKey::Mac(mac) => write!(f, "{mac}"),
+            HostKey::Ip(ip) => write!(f, "{ip}"),
+        }
+    }
+}
+
+/// One device in one recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Observation {
+    pub seen_at: u64,
+    /// The command of the run (`discover` or `scan`).
+    pub command: String,
+    pub primary_ip: IpAddr,
+    pub ips: Vec<IpAddr>,
+    pub mac: Option<String>,
+    pub hostname: Option<String>,
+    /// Open ports (e.g. `22/tcp`), only known for `scan` runs.
+    pub open_ports: Option<Vec<String>>,
+    /// The ports the run probed, unknown for runs recorded before they were kept.
+    #[serde(skip)]
+    pub probed_ports: Option<PortSet>,
+}
+
+impl Observation {
+    fn new(report: &Report, host: &HostRecord) -> Self {
+        let open_ports: Vec<String> = host
+            .ports
+            .iter()
+            .filter(|port| port.state == PortState::Open)
+            .map(|port| format!("{}/{}", port.port, port.protocol))
+            .collect();
+
+        Self {
+            seen_at: report.started_at,
+            command: report.command.clone(),
+            primary_ip: host.primary_ip,
+            ips: host.ips.clone(),
+            mac: host.mac.clone(),
+            hostname: host.hostname.clone(),
+            open_ports: (report.command == "scan").then_some(open_ports),
+            probed_ports: report.probed_ports(),
+        }
+    }
+
+    /// The ports opened and closed since `earlier`, among the ports both runs probed.
+    /// `None` if either run did not scan ports or did not record which.
+    pub fn port_changes(&self, earlier: &Observation) -> Option<(Vec<String>, Vec<String>)> {
+        let probed: PortSet = self
+            .probed_ports
+            .as_ref()?
+            .intersection(earlier.probed_ports.as_ref()?);
+        let before: BTreeSet<&String> = earlier.open_ports.as_ref()?.iter().collect();
+        let now: BTreeSet<&String> = self.open_ports.as_ref()?.iter().collect();
+
+        let probed_only = |ports: BTreeSet<&&String>| -> Vec<String> {
+            ports
+                .into_iter()
+                .filter(|port| probed.contains_label(port))
+                .map(|port| port.to_string())
+                .collect()
+        };
+        Some((
+            probed_only(now.difference(&before).collect()),
+            probed_only(before.difference(&now).collect()),
+        ))
+    }
+}
+
+/// Every observation of one device, oldest first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostHistory {
+    pub key: HostKey,
+    pub vendor: Option<String>,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub observations: Vec<Observation>,
+}
+
+impl HostHistory {
+    /// Follows the device `query` points at through `reports` (oldest first).
+    ///
+    /// An IP stands for the device that held it most recently, so the history of a
+    /// device with a MAC also covers the addresses it had before.
+    pub fn find(reports: &[Report], query: &HostKey) -> Option<Self> {
+        let key: HostKey = match query {
+            HostKey::Mac(_) => query.clone(),
+            HostKey::Ip(ip) => reports
+                .iter()
+                .rev()
+                .flat_map(hosts)
+                .find(|host| host.ips.contains(ip) || host.primary_ip == *ip)
+                .map(HostKey::of)?,
+        };
+
+        let mut vendor: Option<String> = None;
+        let observations: Vec<Observation> = reports
+            .iter()
+            .flat_map(|report| {
+                hosts(report)
+                    .filter(|host| HostKey::of(host) == key)
+                    .map(move |host| (report, host))
+            })
+            .map(|(report, host)| {
+                vendor = host.vendor.clone().or(vendor.take());
+                Observation::new(report, host)
+            })
+            .collect();
+
+        Some(Self {
+            key,
+            vendor,
+            first_seen: observations.first()?.seen_at,
+            last_seen: observations.last()?.seen_at,
+            observations,
+        })
+    }
+}
+
+fn hosts(report: &Report) -> impl Iterator<Item = &HostRecord> {
+    report.hosts.iter().flatten()
+}
+
+/// The home and ids of the user who ran `sudo`, `None` if that was root itself.
+///
+/// The home comes from `/etc/passwd`. Where users are not listed there (macOS), `sudo`
+/// keeps `$HOME` pointing at the invoking user, so that is used instead.
+fn sudo_user() -> Option<(PathBuf, (u32, u32))> {
+    let name: String = std::env::var("SUDO_USER").ok()?;
+    let uid: u32 = std::env::var("SUDO_UID").ok()?.parse().ok()?;
+    let gid: u32 = std::env::var("SUDO_GID").ok()?.parse().ok()?;
+    if uid == 0 {
+        return None;
+    }
+
+    let home: PathBuf = fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|passwd| home_in_passwd(&passwd, &name))
+        .or_else(|| std::env::var_os("HOME").map(PathBuf::from))?;
+    Some((home, (uid, gid)))
+}
+
+/// The home directory of `name` in the `passwd` format (`name:x:uid:gid:gecos:home:shell`).
+fn home_in_passwd(passwd: &str, name: &str) -> Option<PathBuf> {
+    passwd
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.len() >= 6 && fields[0] == name)
+        .map(|fields| PathBuf::from(fields[5]))
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+    use crate::models::host::Host;
+    use crate::models::port::{Port, PortReason, Protocol};
+
+    const MAC: MacAddr = MacAddr(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3);
+
+    fn run(secs: u64, hosts: &[Host]) -> Report {
+        let started_at: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        Report::new("0.0.0", "discover", started_at, Duration::ZERO).with_hosts(hosts)
+    }
+
+    #[test]
+    fn follows_a_mac_across_addresses() {
+        let reports: Vec<Report> = vec![
+            run(
+                100,
+                &[Host::new(IpAddr::from([192, 168, 1, 5])).with_mac(MAC)],
+            ),
+            run(
+                200,
+                &[Host::new(IpAddr::from([192, 168, 1, 9])).with_mac(MAC)],
+            ),
+            run(300, &[Host::new(IpAddr::from([192, 168, 1, 5]))]),
+        ];
+
+        let query: HostKey = HostKey::parse("192.168.1.9").unwrap();
+        let history: HostHistory = HostHistory::find(&reports, &query).unwrap();
+        assert_eq!(history.key, HostKey::Mac(MAC.to_string()));
+        assert_eq!((history.first_seen, history.last_seen), (100, 200));
+        assert_eq!(history.observations.len(), 2);
+
+        // The latest holder of .5 has no MAC, so it is a different device
+        let query: HostKey = HostKey::parse("192.168.1.5").unwrap();
+        let history: HostHistory = HostHistory::find(&reports, &query).unwrap();
+        assert_eq!(history.key, HostKey::Ip(IpAddr::from([192, 168, 1, 5])));
+        assert_eq!(history.observations.len(), 1);
+    }
+
+    #[test]
+    fn compares_only_ports_both_scans_probed() {
+        let scan = |secs: u64, spec: &str, open: &[u16]| -> Report {
+            let mut host: Host = Host::new(IpAddr::from([192, 168, 1, 5])).with_mac(MAC);
+            for &port in open {
+                host.add_port(Port::new(
+                    port,
+                    Protocol::Tcp,
+                    PortState::Open,
+                    PortReason::SynAck,
+                ));
+            }
+            let started_at: SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+            Report::new("0.0.0", "scan", started_at, Duration::ZERO)
+                .with_ports(&PortSet::parse(spec).unwrap())
+                .with_hosts(&[host])
+        };
+        let reports: Vec<Report> = vec![scan(100, "22,80", &[22, 80]), scan(200, "22,443", &[443])];
+
+        let history: HostHistory =
+            HostHistory::find(&reports, &HostKey::Mac(MAC.to_string())).unwrap();
+        let (opened, closed) = history.observations[1]
+            .port_changes(&history.observations[0])
+            .unwrap();
+        // 80 and 443 were only probed by one of the scans
+        assert!(opened.is_empty());
+        assert_eq!(closed, ["22/tcp"]);
+    }
+
+    #[test]
+    fn finds_the_home_of_a_user() {
+        let passwd: &str = "root:x:0:0:root:/root:/bin/bash\n\
+                            # comment\n\
+                            alice:x:1000:1000:Alice,,,:/home/alice:/bin/zsh\n";
+        assert_eq!(
+            home_in_passwd(passwd, "alice"),
+            Some(PathBuf::from("/home/alice"))
+        );
+        assert_eq!(home_in_passwd(passwd, "bob"), None);
+    }
+
+    #[test]
+    fn appends_and_loads_runs() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("zond-history-{}", std::process::id()));
+        let store: HistoryStore = HistoryStore::new(dir.join(FILE_NAME));
+
+        assert!(store.load().unwrap().is_empty());
+        store
+            .append(&run(200, &[Host::new(IpAddr::from([10, 0, 0, 1]))]))
+            .unwrap();
+        store.append(&run(100, &[])).unwrap();
+        fs::write(
+            store.path(),
+            fs::read_to_string(store.path()).unwrap() + "{broken\n",
+        )
+        .unwrap();
+
+        let runs: Vec<Report> = store.load().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].started_at, 100);
+    }
+
+    #[test]
+    fn refuses_a_symlinked_store() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("zond-history-link-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target: PathBuf = dir.join("target");
+        fs::write(&target, "untouched\n").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join(FILE_NAME)).unwrap();
+
+        let result: anyhow::Result<()> =
+            HistoryStore::new(dir.join(FILE_NAME)).append(&run(100, &[]));
+        let content: String = fs::read_to_string(&target).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+        assert_eq!(content, "untouched\n");
+    }
+}
@@ -1,13 +1,14 @@
-use crate::info;
+use crate::{info, warn};
 #[cfg(target_os = "linux")]
-use linux_impl::{is_physical, is_wireless};
+use linux_impl::{default_gateways, is_physical, is_wireless, routes_via};
 #[cfg(target_os = "macos")]
-use macos_impl::{is_physical, is_wireless};
+use macos_impl::{default_gateways, is_physical, is_wireless, routes_via};
 use pnet::datalink::{self, NetworkInterface};
 use pnet::ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
 use rayon::prelude::*;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
 
 use crate::models::range::IpCollection;
 
@@ -29,6 +30,86 @@ pub enum ViabilityError {
 
 /// Finds the primary LAN network and returns the ipv4 network
 pub fn get_lan_network() -> anyhow::Result<Option<Ipv4Network>> {
+    let interface: NetworkInterface = get_lan_interface()?;
+    info!(
+        verbosity = 1,
+        "Performing LAN scan on interface {}", interface.name
+    );
+
+    let private_v4_net: Option<Ipv4Network> = interface.ips.iter().find_map(|net| match net {
+        IpNetwork::V4(v4) if v4.ip().is_private() => Some(*v4),
+        _ => None,
+    });
+
+    Ok(private_v4_net)
+}
+
+/// The next hops of the default routes (IPv4 and IPv6), read from the kernel routing table.
+pub fn get_default_gateways() -> Vec<IpAddr> {
+    let mut gateways: Vec<IpAddr> = default_gateways();
+    gateways.sort();
+    gateways.dedup();
+    info!(verbosity = 2, "Default gateways: {gateways:?}");
+    gateways
+}
+
+/// Name prefixes of tunnel interfaces that do not always set the point-to-point flag.
+const VPN_PREFIXES: [&str; 4] = ["wg", "tun", "ppp", "utun"];
+
+/// Shortest IPv4 prefix taken from a VPN route. Wider routes (e.g. a full tunnel) are skipped.
+const MIN_VPN_ROUTE_PREFIX_V4: u8 = 16;
+/// Shortest IPv6 prefix taken from a VPN route or address.
+const MIN_VPN_ROUTE_PREFIX_V6: u8 = 108;
+
+/// Finds the networks reachable through VPN interfaces: the networks of their
+/// addresses and the kernel routes pointing at them.
+pub fn get_vpn_networks() -> anyhow::Result<Vec<IpNetwork>> {
+    let interfaces: Vec<NetworkInterface> = pnet::datalink::interfaces()
+        .into_iter()
+        .filter(is_vpn_interface)
+        .collect();
+    anyhow::ensure!(!interfaces.is_empty(), "No VPN interfaces found");
+
+    let names: Vec<&str> = interfaces.iter().map(|intf| intf.name.as_str()).collect();
+    info!(verbosity = 1, "Performing VPN scan on {}", names.join(", "));
+
+    let mut networks: Vec<IpNetwork> = interfaces
+        .iter()
+        .flat_map(|intf| intf.ips.iter().copied())
+        .filter(|net| !matches!(net, IpNetwork::V6(v6) if v6.ip().is_unicast_link_local()))
+        .collect();
+    networks.extend(routes_via(&names));
+
+    networks.retain(|net| {
+        let min_prefix: u8 = match net {
+            IpNetwork::V4(_) => MIN_VPN_ROUTE_PREFIX_V4,
+            IpNetwork::V6(_) => MIN_VPN_ROUTE_PREFIX_V6,
+        };
+        let is_narrow: bool = net.prefix() >= min_prefix;
+        if !is_narrow {
+            warn!("Skipping VPN network {net}, it is wider than /{min_prefix}");
+        }
+        is_narrow
+    });
+    networks.sort();
+    networks.dedup();
+
+    Ok(networks)
+}
+
+/// Whether the interface is an active tunnel: point-to-point or named like one.
+pub fn is_vpn_interface(interface: &NetworkInterface) -> bool {
+    interface.is_up()
+        && !interface.is_loopback()
+        && !interface.ips.is_empty()
+        && (interface.is_point_to_point()
+            || VPN_PREFIXES
+                .iter()
+                .any(|prefix| interface.name.starts_with(prefix)))
+}
+
+/// Picks the interface that is most likely attached to the local LAN, preferring wired ones.
+pub fn get_lan_interface() -> anyhow::Result<NetworkInterface> {
     let interfaces: Vec<NetworkInterface> = pnet::datalink::interfaces();
 
     let interfaces_str: &str = match interfaces.len() {
@@ -53,23 +134,16 @@ pub fn get_lan_network() -> anyhow::Result<Option<Ipv4Network>> {
         )
         .collect();
 
-    let interface: NetworkInterface =
-        if let Some(interface) = select_best_lan_interface(interfaces, is_wired) {
-            info!(
-                verbosity = 1,
-                "Performing LAN scan on interface {}", interface.name
-            );
-            interface
-        } else {
-            anyhow::bail!("No interfaces available for LAN discovery");
-        };
-
-    let private_v4_net: Option<Ipv4Network> = interface.ips.iter().find_map(|net| match net {
-        IpNetwork::V4(v4) if v4.ip().is_private() => Some(*v4),
-        _ => None,
-    });
+    select_best_lan_interface(interfaces, is_wired)
+        .ok_or_else(|| anyhow::anyhow!("No interfaces available for LAN discovery"))
+}
 
-    Ok(private_v4_net)
+/// Looks up an interface by its name (e.g. `eth0`).
+pub fn get_by_name(name: &str) -> anyhow::Result<NetworkInterface> {
+    pnet::datalink::interfaces()
+        .into_iter()
+        .find(|interface| interface.name == name)
+        .ok_or_else(|| anyhow::anyhow!("No interface named '{name}'"))
 }
 
 /// Returns a list of prioritized network interfaces (e.g. wired first, then wireless, etc.)
@@ -105,6 +179,16 @@ pub fn is_on_link(intf: &NetworkInterface, ips: &IpCollection) -> bool {
         }
     }
 
+    for range in &ips.ranges_v6 {
+        let range_covered: bool = intf.ips.iter().any(|iface_ipnet| {
+            matches!(iface_ipnet, IpNetwork::V6(network)
+                if network.contains(range.start_addr) && network.contains(range.end_addr))
+        });
+        if !range_covered {
+            return false;
+        }
+    }
+
     for single_ip in &ips.singles {
         let mut ip_covered = false;
         for iface_ipnet in &intf.ips {
@@ -191,33 +275,28 @@ pub fn map_ips_to_interfaces(
     let mut result_map: HashMap<usize, (IpCollection, IpCollection)> = HashMap::new();
     let mut unmapped_ips = IpCollection::new();
 
-    // 1. Handle Ranges (IPv4 only currently in IpCollection ranges)
-    // Ranges that don't match a local subnet are broken into singles and re-processed
-    // or just dumped into unmapped?
-    // Current logic: if range not local, all IPs are added to 'singles' to be routed individually.
-    // This logic is preserved below.
+    // 1. Handle Ranges
+    // A range inside a local subnet stays a range. Any other range is broken into
+    // singles, which are routed individually below.
     for range in collection.ranges {
-        let start: Ipv4Addr = range.start_addr;
-        let end: Ipv4Addr = range.end_addr;
-        let mut owner_idx: Option<usize> = None;
-
-        for (idx, iface) in interfaces.iter().enumerate() {
-            let is_local_subnet = iface.ips.iter().any(|ip_net| {
-                ip_net.contains(IpAddr::V4(start)) && ip_net.contains(IpAddr::V4(end))
-            });
+        let start: IpAddr = IpAddr::V4(range.start_addr);
+        let end: IpAddr = IpAddr::V4(range.end_addr);
 
-            if is_local_subnet {
-                owner_idx = Some(idx);
-                break;
-            }
+        if let Some(idx) = find_subnet_index(&interfaces, start, end) {
+            result_map.entry(idx).or_default().0.add_range(range);
+        } else {
+            collection.singles.extend(range.to_iter());
         }
+    }
 
-        if let Some(idx) = owner_idx {
-            result_map.entry(idx).or_default().0.add_range(range);
+    for range in collection.ranges_v6 {
+        let start: IpAddr = IpAddr::V6(range.start_addr);
+        let end: IpAddr = IpAddr::V6(range.end_addr);
+
+        if let Some(idx) = find_subnet_index(&interfaces, start, end) {
+            result_map.entry(idx).or_default().0.add_range_v6(range);
         } else {
-            for ip in range.to_iter() {
-                collection.singles.insert(ip);
-            }
+            collection.singles.extend(range.to_iter());
         }
     }
 
@@ -277,6 +356,28 @@ pub fn map_ips_to_interfaces(
     (mapped_interfaces, unmapped_ips)
 }
 
+/// Returns the local address the kernel would use as the source when talking to `target`.
+///
+/// No packets are sent; the route is looked up by connecting a UDP socket.
+pub fn get_route_source_addr(target: IpAddr) -> Option<IpAddr> {
+    thread_local! {
+        static SOCKETS: RefCell<(Option<UdpSocket>, Option<UdpSocket>)> =
+            const { RefCell::new((None, None)) };
+    }
+
+    SOCKETS.with(|sockets| resolve_route_source_ip(target, &mut sockets.borrow_mut()))
+}
+
+/// Index of the interface with a subnet containing both `start` and `end`.
+fn find_subnet_index(interfaces: &[NetworkInterface], start: IpAddr, end: IpAddr) -> Option<usize> {
+    interfaces.iter().position(|iface| {
+        iface
+            .ips
+            .iter()
+            .any(|ip_net| ip_net.contains(start) && ip_net.contains(end))
+    })
+}
+
 fn find_local_index(interfaces: &[NetworkInterface], target: IpAddr) -> Option<usize> {
     interfaces.iter().position(|iface| {
         iface.ips.iter().any(|ip_net| match (target, ip_net.ip()) {
@@ -329,6 +430,109 @@ mod linux_impl {
     pub fn is_wireless(interface: &NetworkInterface) -> bool {
         Path::new(&format!("sys/class/net/{}/wireless", interface.name)).exists()
     }
+
+    /// The kernel routes (IPv4 and IPv6) whose outgoing interface is one of `names`.
+    pub fn routes_via(names: &[&str]) -> Vec<IpNetwork> {
+        let mut routes: Vec<IpNetwork> = Vec::new();
+        if let Ok(table) = std::fs::read_to_string("/proc/net/route") {
+            routes.extend(parse_route_v4(&table, names));
+        }
+        if let Ok(table) = std::fs::read_to_string("/proc/net/ipv6_route") {
+            routes.extend(parse_route_v6(&table, names));
+        }
+        routes
+    }
+
+    /// Next hops of the default routes in `/proc/net/route` and `/proc/net/ipv6_route`.
+    pub fn default_gateways() -> Vec<IpAddr> {
+        let mut gateways: Vec<IpAddr> = Vec::new();
+        if let Ok(table) = std::fs::read_to_string("/proc/net/route") {
+            gateways.extend(parse_gateways_v4(&table));
+        }
+        if let Ok(table) = std::fs::read_to_string("/proc/net/ipv6_route") {
+            gateways.extend(parse_gateways_v6(&table));
+        }
+        gateways
+    }
+
+    const RTF_UP: u16 = 0x1;
+    const RTF_GATEWAY: u16 = 0x2;
+
+    pub(super) fn parse_gateways_v4(table: &str) -> Vec<IpAddr> {
+        table
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 8 || fields[1] != "00000000" || fields[7] != "00000000" {
+                    return None;
+                }
+                let flags: u16 = u16::from_str_radix(fields[3], 16).ok()?;
+                if flags & (RTF_UP | RTF_GATEWAY) != RTF_UP | RTF_GATEWAY {
+                    return None;
+                }
+                let gateway: u32 = u32::from_str_radix(fields[2], 16).ok()?;
+                Some(IpAddr::V4(Ipv4Addr::from(gateway.to_ne_bytes())))
+            })
+            .collect()
+    }
+
+    pub(super) fn parse_gateways_v6(table: &str) -> Vec<IpAddr> {
+        table
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 10 || fields[1] != "00" || fields[9] == "lo" {
+                    return None;
+                }
+                let dest: u128 = u128::from_str_radix(fields[0], 16).ok()?;
+                let next_hop: u128 = u128::from_str_radix(fields[4], 16).ok()?;
+                (dest == 0 && next_hop != 0).then(|| IpAddr::V6(Ipv6Addr::from(next_hop)))
+            })
+            .collect()
+    }
+
+    /// Parses `/proc/net/route`: interface, destination, gateway, flags, ..., mask.
+    /// Addresses are hex in host byte order.
+    pub(super) fn parse_route_v4(table: &str, names: &[&str]) -> Vec<IpNetwork> {
+        table
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 8 || !names.contains(&fields[0]) {
+                    return None;
+                }
+                let parse_addr = |hex: &str| {
+                    u32::from_str_radix(hex, 16)
+                        .ok()
+                        .map(|n| Ipv4Addr::from(n.to_ne_bytes()))
+                };
+                let dest: Ipv4Addr = parse_addr(fields[1])?;
+                let mask: Ipv4Addr = parse_addr(fields[7])?;
+                let prefix: u8 = u32::from(mask).count_ones() as u8;
+                Ipv4Network::new(dest, prefix).ok().map(IpNetwork::V4)
+            })
+            .collect()
+    }
+
+    /// Parses `/proc/net/ipv6_route`: destination, prefix, ..., interface (last).
+    pub(super) fn parse_route_v6(table: &str, names: &[&str]) -> Vec<IpNetwork> {
+        table
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 10 || !names.contains(&fields[9]) {
+                    return None;
+                }
+                let dest: u128 = u128::from_str_radix(fields[0], 16).ok()?;
+                let prefix: u8 = u8::from_str_radix(fields[1], 16).ok()?;
+                Ipv6Network::new(Ipv6Addr::from(dest), prefix)
+                    .ok()
+                    .map(IpNetwork::V6)
+            })
+            .collect()
+    }
 }
 
 #[cfg(target_os = "macos")]
@@ -397,6 +601,110 @@ mod macos_impl {
             .wireless_devices
             .contains(&interface.name)
     }
+
+    /// Gateways of the `default` entries of `netstat -rn`.
+    pub fn default_gateways() -> Vec<IpAddr> {
+        let Ok(output) = Command::new("netstat").arg("-rn").output() else {
+            return Vec::new();
+        };
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                if fields.next() != Some("default") {
+                    return None;
+                }
+                // Link-local IPv6 gateways carry a scope ("fe80::1%en0")
+                let gateway: &str = fields.next()?.split('%').next()?;
+                gateway.parse::<IpAddr>().ok()
+            })
+            .collect()
+    }
+
+    /// The IPv4 routes of `netstat -rn` whose outgoing interface is one of `names`.
+    pub fn routes_via(names: &[&str]) -> Vec<IpNetwork> {
+        let Ok(output) = Command::new("netstat").args(["-rn", "-f", "inet"]).output() else {
+            return Vec::new();
+        };
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() < 4 || !names.contains(&fields[3]) {
+                    return None;
+                }
+                parse_bsd_destination(fields[0])
+            })
+            .collect()
+    }
+
+    /// Parses netstat's abbreviated destinations ("10.8/16", "172.16.4", "10.8.0.1").
+    fn parse_bsd_destination(dest: &str) -> Option<IpNetwork> {
+        let (addr, prefix) = match dest.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+            None => (dest, None),
+        };
+
+        let mut octets: [u8; 4] = [0; 4];
+        let parts: Vec<u8> = addr
+            .split('.')
+            .map(|octet| octet.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .ok()?;
+        if parts.is_empty() || parts.len() > 4 {
+            return None;
+        }
+        octets[..parts.len()].copy_from_slice(&parts);
+
+        let prefix: u8 = prefix.unwrap_or(parts.len() as u8 * 8);
+        Ipv4Network::new(Ipv4Addr::from(octets), prefix)
+            .ok()
+            .map(IpNetwork::V4)
+    }
+}
+
+// Moving generic interface helpers here
+pub trait NetworkInterfaceExtension {
+    fn get_ipv4_nets(&self) -> Vec<Ipv4Network>;
+    fn get_ipv6_nets(&self) -> Vec<Ipv6Network>;
+    fn get_ipv4_range(&self) -> Option<Ipv4Network>;
+}
+
+impl NetworkInterfaceExtension for NetworkInterface {
+    fn get_ipv4_nets(&self) -> Vec<Ipv4Network> {
+        self.ips
+            .iter()
+            .filter_map(|ip| {
+                if let IpNetwork::V4(ipv4) = ip {
+                    Some(*ipv4)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn get_ipv6_nets(&self) -> Vec<Ipv6Network> {
+        self.ips
+            .iter()
+            .filter_map(|ip| {
+                if let IpNetwork::V6(ipv6) = ip {
+                    Some(*ipv6)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn get_ipv4_range(&self) -> Option<Ipv4Network> {
+        // Simple heuristic: pick the first non-loopback IPv4
+        self.get_ipv4_nets()
+            .into_iter()
+            .find(|net| !net.ip().is_loopback())
+    }
 }
 
 // ╔════════════════════════════════════════════╗
@@ -549,6 +857,64 @@ mod tests {
         assert_eq!(result, Err(ViabilityError::IsPointToPoint))
     }
 
+    #[test]
+    fn is_vpn_interface_matches_tunnels() {
+        let ips: Vec<IpNetwork> = vec![IpNetwork::V4("10.8.0.2/24".parse().unwrap())];
+        let tun: NetworkInterface =
+            create_mock_interface("tap9", None, ips.clone(), IFF_UP | IFF_POINTTOPOINT);
+        let wireguard: NetworkInterface = create_mock_interface("wg0", None, ips.clone(), IFF_UP);
+        let down: NetworkInterface = create_mock_interface("utun3", None, ips.clone(), 0);
+        let lan: NetworkInterface =
+            create_mock_interface("eth0", default_mac(), ips, IFF_UP | IFF_BROADCAST);
+
+        assert!(is_vpn_interface(&tun));
+        assert!(is_vpn_interface(&wireguard));
+        assert!(!is_vpn_interface(&down));
+        assert!(!is_vpn_interface(&lan));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_proc_routes_for_vpn_interfaces() {
+        let table_v4: &str = "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask\tMTU\tWindow\tIRTT\n\
+            eth0\t00000000\t010200C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n\
+            wg0\t0000100A\t00000000\t0001\t0\t0\t0\t0000FFFF\t0\t0\t0\n";
+        assert_eq!(
+            linux_impl::parse_route_v4(table_v4, &["wg0"]),
+            vec![IpNetwork::V4("10.16.0.0/16".parse().unwrap())]
+        );
+
+        let table_v6: &str = "fd420000000000000000000000000000 78 00000000000000000000000000000000 00 \
+            00000000000000000000000000000000 00000100 00000001 00000000 00000001 wg0\n\
+            fe800000000000000000000000000000 40 00000000000000000000000000000000 00 \
+            00000000000000000000000000000000 00000100 00000002 00000000 00000001 eth0\n";
+        assert_eq!(
+            linux_impl::parse_route_v6(table_v6, &["wg0"]),
+            vec![IpNetwork::V6("fd42::/120".parse().unwrap())]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_proc_default_gateways() {
+        let table_v4: &str = "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask\tMTU\tWindow\tIRTT\n\
+            eth0\t00000000\t010200C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n\
+            eth0\t000200C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n";
+        assert_eq!(
+            linux_impl::parse_gateways_v4(table_v4),
+            vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))]
+        );
+
+        let table_v6: &str = "00000000000000000000000000000000 00 00000000000000000000000000000000 00 \
+            fe800000000000000000000000000001 00000400 00000001 00000000 00000003 eth0\n\
+            fd000000000000000000000000000000 40 00000000000000000000000000000000 00 \
+            00000000000000000000000000000000 00000100 00000001 00000000 00000001 eth0\n";
+        assert_eq!(
+            linux_impl::parse_gateways_v6(table_v6),
+            vec!["fe80::1".parse::<IpAddr>().unwrap()]
+        );
+    }
+
     #[test]
     fn select_best_lan_interface_selects_first_interface() {
         let interface: NetworkInterface = create_mock_interface(
@@ -650,6 +1016,12 @@ mod tests {
         assert_eq!(result.unwrap(), target);
     }
 
+    #[test]
+    fn get_route_source_addr_loopback() {
+        let target = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        assert_eq!(get_route_source_addr(target), Some(target));
+    }
+
     #[test]
     fn test_resolve_route_public_internet() {
         // Try routing to Google DNS (8.8.8.8).
@@ -691,45 +1063,3 @@ mod tests {
         }
     }
 }
-
-// Moving generic interface helpers here
-pub trait NetworkInterfaceExtension {
-    fn get_ipv4_nets(&self) -> Vec<Ipv4Network>;
-    fn get_ipv6_nets(&self) -> Vec<Ipv6Network>;
-    fn get_ipv4_range(&self) -> Option<Ipv4Network>;
-}
-
-impl NetworkInterfaceExtension for NetworkInterface {
-    fn get_ipv4_nets(&self) -> Vec<Ipv4Network> {
-        self.ips
-            .iter()
-            .filter_map(|ip| {
-                if let IpNetwork::V4(ipv4) = ip {
-                    Some(*ipv4)
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn get_ipv6_nets(&self) -> Vec<Ipv6Network> {
-        self.ips
-            .iter()
-            .filter_map(|ip| {
-                if let IpNetwork::V6(ipv6) = ip {
-                    Some(*ipv6)
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
-
-    fn get_ipv4_range(&self) -> Option<Ipv4Network> {
-        // Simple heuristic: pick the first non-loopback IPv4
-        self.get_ipv4_nets()
-            .into_iter()
-            .find(|net| !net.ip().is_loopback())
-    }
-}
@@ -1,7 +1,10 @@
 pub mod config;
+pub mod diff;
+pub mod history;
 pub mod interface;
 pub mod logging;
 pub mod models;
+pub mod roles;
 pub mod sender;
 pub mod system;
 pub mod utils;
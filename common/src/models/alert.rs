@@ -0,0 +1,247 @@
+//! # Monitor Alert Model
+//!
+//! What `zond monitor` reports when the network changed between two rounds.
+//!
+//! ## Key Concepts
+//! * **Derived From Diffs**: Alerts are the parts of a [`ReportDiff`] worth waking someone
+//!   up for: hosts joining or leaving, a known IP answering with another MAC, a device
+//!   moving to another IP and ports that opened.
+//! * **Versioned**: [`AlertRecord`] wraps an alert with [`SCHEMA_VERSION`] and a timestamp,
+//!   which is the form written as one JSON line per alert.
+
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::{DiffStatus, HostDiff, ReportDiff};
+use crate::models::report::SCHEMA_VERSION;
+
+/// A change on the network seen by the monitor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Alert {
+    /// A host answered that was not up in the previous round.
+    HostJoined {
+        ip: IpAddr,
+        mac: Option<String>,
+        vendor: Option<String>,
+        hostname: Option<String>,
+    },
+    /// A host of the previous round no longer answered.
+    HostLeft {
+        ip: IpAddr,
+        mac: Option<String>,
+        vendor: Option<String>,
+        hostname: Option<String>,
+    },
+    /// A known IP answered with another MAC, or with more than one, as it does under
+    /// ARP spoofing. `previous_mac` is the MAC that held it before or holds it as well.
+    MacChanged {
+        ip: IpAddr,
+        mac: String,
+        previous_mac: String,
+        vendor: Option<String>,
+    },
+    /// A known device answered from another IP.
+    IpChanged {
+        ip: IpAddr,
+        previous_ip: IpAddr,
+        mac: Option<String>,
+    },
+    /// Ports of a known host that were not open in the previous round (e.g. `22/tcp`).
+    PortsOpened {
+        ip: IpAddr,
+        mac: Option<String>,
+        ports: Vec<String>,
+    },
+}
+
+impl Alert {
+    /// The alerts for every host of `diff`, ordered by IP.
+    pub fn from_diff(diff: &ReportDiff) -> Vec<Self> {
+        diff.hosts.iter().flat_map(Self::from_host).collect()
+    }
+
+    fn from_host(host: &HostDiff) -> Vec<Self> {
+        let (ip, mac) = (host.ip, host.mac.clone());
+        let mut alerts: Vec<Self> = Vec::new();
+        match host.status {
+            DiffStatus::New => alerts.push(Self::HostJoined {
+                ip,
+                mac: mac.clone(),
+                vendor: host.vendor.clone(),
+                hostname: host.hostname.clone(),
+            }),
+            DiffStatus::Gone => {
+                return vec![Self::HostLeft {
+                    ip,
+                    mac,
+                    vendor: host.vendor.clone(),
+                    hostname: host.hostname.clone(),
+                }];
+            }
+            DiffStatus::Changed => {
+                if let (Some(mac), Some(previous_mac)) = (&host.mac, &host.previous_mac) {
+                    alerts.push(Self::MacChanged {
+                        ip,
+                        mac: mac.clone(),
+                        previous_mac: previous_mac.clone(),
+                        vendor: host.vendor.clone(),
+                    });
+                }
+                if let Some(previous_ip) = host.previous_ip {
+                    alerts.push(Self::IpChanged {
+                        ip,
+                        previous_ip,
+                        mac: mac.clone(),
+                    });
+                }
+            }
+        }
+
+        // Addresses taken over from other MACs, as a secondary address too
+        if let Some(mac) = &host.mac {
+            for claim in &host.claims {
+                alerts.extend(claim.other_macs.iter().map(|other| Self::MacChanged {
+                    ip: claim.ip,
+                    mac: mac.clone(),
+                    previous_mac: other.clone(),
+                    vendor: host.vendor.clone(),
+                }));
+            }
+        }
+        if !host.opened.is_empty() {
+            alerts.push(Self::PortsOpened {
+                ip,
+                mac,
+                ports: host.opened.clone(),
+            });
+        }
+        alerts
+    }
+
+    /// The name of the alert as written in the `event` field (e.g. `host_joined`).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Alert::HostJoined { .. } => "host_joined",
+            Alert::HostLeft { .. } => "host_left",
+            Alert::MacChanged { .. } => "mac_changed",
+            Alert::IpChanged { .. } => "ip_changed",
+            Alert::PortsOpened { .. } => "ports_opened",
+        }
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        match self {
+            Alert::HostJoined { ip, .. }
+            | Alert::HostLeft { ip, .. }
+            | Alert::MacChanged { ip, .. }
+            | Alert::IpChanged { ip, .. }
+            | Alert::PortsOpened { ip, .. } => *ip,
+        }
+    }
+}
+
+/// An alert as it is written out, stamped with the schema version and time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRecord {
+    pub schema_version: u32,
+    /// Milliseconds since the Unix epoch.
+    pub time_ms: u64,
+    #[serde(flatten)]
+    pub alert: Alert,
+}
+
+impl AlertRecord {
+    pub fn now(alert: Alert) -> Self {
+        let time_ms: u64 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            time_ms,
+            alert,
+        }
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pnet::datalink::MacAddr;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::models::host::Host;
+    use crate::models::port::{Port, PortReason, PortSet, PortState, Protocol};
+    use crate::models::report::Report;
+
+    const ROUTER: MacAddr = MacAddr(0x3c, 0xa6, 0x2f, 0x00, 0x00, 0x01);
+    const ROGUE: MacAddr = MacAddr(0x02, 0x00, 0x00, 0x00, 0x00, 0x66);
+
+    fn round(hosts: &[Host]) -> Report {
+        Report::new("0.0.0", "scan", SystemTime::now(), Duration::ZERO)
+            .with_ports(&PortSet::parse("22").unwrap())
+            .with_hosts(hosts)
+    }
+
+    #[test]
+    fn turns_a_diff_into_alerts() {
+        let mut router: Host = Host::new(IpAddr::from([192, 168, 1, 1])).with_mac(ROGUE);
+        router.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        let old: Report = round(&[
+            Host::new(IpAddr::from([192, 168, 1, 1])).with_mac(ROUTER),
+            Host::new(IpAddr::from([192, 168, 1, 7])),
+        ]);
+        let new: Report = round(&[router, Host::new(IpAddr::from([192, 168, 1, 9]))]);
+
+        let kinds: Vec<(&str, IpAddr)> = Alert::from_diff(&ReportDiff::new(&old, &new))
+            .iter()
+            .map(|alert| (alert.kind(), alert.ip()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("mac_changed", IpAddr::from([192, 168, 1, 1])),
+                ("ports_opened", IpAddr::from([192, 168, 1, 1])),
+                ("host_left", IpAddr::from([192, 168, 1, 7])),
+                ("host_joined", IpAddr::from([192, 168, 1, 9])),
+            ]
+        );
+    }
+
+    #[test]
+    fn serializes_as_flat_tagged_lines() {
+        let record: AlertRecord = AlertRecord::now(Alert::PortsOpened {
+            ip: IpAddr::from([10, 0, 0, 1]),
+            mac: None,
+            ports: vec!["22/tcp".to_string()],
+        });
+        let value: Value = serde_json::to_value(&record).unwrap();
+
+        assert_eq!(value["event"], json!(record.alert.kind()));
+        assert_eq!(value["ports"], json!(["22/tcp"]));
+        assert_eq!(
+            serde_json::from_value::<AlertRecord>(value).unwrap(),
+            record
+        );
+    }
+}
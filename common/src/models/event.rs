@@ -0,0 +1,154 @@
+//! # Scan Event Model
+//!
+//! What the scanners report while they run, as opposed to the [`Report`] written once
+//! they are done.
+//!
+//! ## Key Concepts
+//! * **Incremental**: Events describe single findings (a host answered, a port was
+//!   classified, a name was resolved). The final [`Host`] list is the sum of them.
+//! * **Versioned**: [`EventRecord`] wraps an event with [`SCHEMA_VERSION`] and a timestamp,
+//!   which is the form written as one JSON line per event.
+//!
+//! [`Report`]: crate::models::report::Report
+//! [`Host`]: crate::models::host::Host
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+use pnet::datalink::MacAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::models::port::{Port, PortState, Protocol};
+use crate::models::report::{self, SCHEMA_VERSION};
+
+/// A single finding or status update of a running scan.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ScanEvent {
+    /// A host answered for the first time.
+    HostUp {
+        ip: IpAddr,
+        mac: Option<String>,
+        rtt_ms: Option<f64>,
+    },
+    /// A port of a host that is up was classified.
+    PortState {
+        ip: IpAddr,
+        port: u16,
+        protocol: Protocol,
+        state: PortState,
+        reason: String,
+    },
+    /// Reverse DNS or mDNS named an address.
+    HostnameResolved { ip: IpAddr, hostname: String },
+    /// Sent periodically while the scan runs.
+    Progress { hosts_up: usize, elapsed_ms: u64 },
+    /// The scan is done; no events follow.
+    Finished {
+        hosts_up: usize,
+        open_ports: usize,
+        elapsed_ms: u64,
+    },
+}
+
+impl ScanEvent {
+    pub fn host_up(ip: IpAddr, mac: Option<MacAddr>, rtt: Option<Duration>) -> Self {
+        Self::HostUp {
+            ip,
+            mac: mac.map(|mac| mac.to_string()),
+            rtt_ms: rtt.map(report::millis),
+        }
+    }
+
+    pub fn port_state(ip: IpAddr, port: &Port) -> Self {
+        Self::PortState {
+            ip,
+            port: port.number,
+            protocol: port.protocol,
+            state: port.state,
+            reason: port.reason.to_string(),
+        }
+    }
+}
+
+/// An event as it is written out, stamped with the schema version and time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub schema_version: u32,
+    /// Milliseconds since the Unix epoch.
+    pub time_ms: u64,
+    #[serde(flatten)]
+    pub event: ScanEvent,
+}
+
+impl EventRecord {
+    pub fn now(event: ScanEvent) -> Self {
+        let time_ms: u64 = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            time_ms,
+            event,
+        }
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::models::port::PortReason;
+
+    #[test]
+    fn serializes_as_flat_tagged_lines() {
+        let port: Port = Port::new(
+            53,
+            Protocol::Udp,
+            PortState::Closed,
+            PortReason::Icmp {
+                icmp_type: 3,
+                code: 3,
+            },
+        );
+        let record: EventRecord =
+            EventRecord::now(ScanEvent::port_state(IpAddr::from([10, 0, 0, 1]), &port));
+        let value: Value = serde_json::to_value(&record).unwrap();
+
+        assert_eq!(value["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(value["event"], json!("port_state"));
+        assert_eq!(value["ip"], json!("10.0.0.1"));
+        assert_eq!(value["protocol"], json!("udp"));
+        assert_eq!(value["reason"], json!("port-unreach"));
+        assert_eq!(
+            serde_json::from_value::<EventRecord>(value).unwrap(),
+            record
+        );
+    }
+
+    #[test]
+    fn host_up_carries_mac_and_rtt() {
+        let event: ScanEvent = ScanEvent::host_up(
+            IpAddr::from([10, 0, 0, 1]),
+            Some(MacAddr::new(0, 0x11, 0x22, 0x33, 0x44, 0x55)),
+            Some(Duration::from_micros(1500)),
+        );
+
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "event": "host_up", "ip": "10.0.0.1", "mac": "00:11:22:33:44:55", "rtt_ms": 1.5 })
+        );
+    }
+}
@@ -7,15 +7,18 @@
 //! * **Identity**: A host is primarily identified by its IP address for the duration of a scan.
 //! * **Enrichment**: The model is mutable and strictly additive; scans populate optional fields (hostname, vendor) as data becomes available.
 
+use crate::models::port::{Port, Protocol};
 use crate::utils::mac;
 use pnet::datalink::MacAddr;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeSet, HashSet, VecDeque},
     net::IpAddr,
     time::Duration,
 };
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NetworkRole {
     Gateway,
     DHCP,
@@ -37,9 +40,8 @@ pub struct Host {
     /// All known IP addresses for this host.
     pub ips: BTreeSet<IpAddr>,
 
-    /// Open ports found on the host.
-    /// TODO: Refactor to a rich `Port` struct in a future iteration.
-    pub ports: BTreeSet<u16>,
+    /// Every port that was classified on the host, sorted by protocol and number.
+    pub ports: Vec<Port>,
 
     /// The MAC address (only available if the host is on the same LAN).
     pub mac: Option<MacAddr>,
@@ -64,7 +66,7 @@ impl Host {
             primary_ip,
             hostname: None,
             ips,
-            ports: BTreeSet::new(),
+            ports: Vec::new(),
             mac: None,
             vendor: None,
             network_roles: HashSet::new(),
@@ -83,6 +85,50 @@ impl Host {
         self
     }
 
+    /// Inserts a port, replacing an earlier entry for the same number and protocol.
+    pub fn add_port(&mut self, port: Port) {
+        match self
+            .ports
+            .binary_search_by_key(&(port.protocol, port.number), |p| (p.protocol, p.number))
+        {
+            Ok(idx) => self.ports[idx] = port,
+            Err(idx) => self.ports.insert(idx, port),
+        }
+    }
+
+    pub fn get_port(&self, number: u16, protocol: Protocol) -> Option<&Port> {
+        self.ports
+            .iter()
+            .find(|p| p.number == number && p.protocol == protocol)
+    }
+
+    pub fn open_ports(&self) -> impl Iterator<Item = &Port> {
+        self.ports.iter().filter(|p| p.is_open())
+    }
+
+    /// Folds everything known about `other` into this host.
+    ///
+    /// Existing values win over the other host's optional fields.
+    pub fn merge(&mut self, other: Host) {
+        self.ips.extend(other.ips);
+        self.network_roles.extend(other.network_roles);
+
+        if self.hostname.is_none() {
+            self.hostname = other.hostname;
+        }
+        if self.mac.is_none() {
+            self.mac = other.mac;
+            self.vendor = other.vendor;
+        }
+
+        for port in other.ports {
+            self.add_port(port);
+        }
+        for rtt in other.rtt_history {
+            self.add_rtt(rtt);
+        }
+    }
+
     /// Replaces the RTT history of the host
     pub fn set_rtts(&mut self, rtts: VecDeque<Duration>) {
         self.rtt_history = rtts;
@@ -136,6 +182,7 @@ mod tests {
     };
 
     use super::Host;
+    use crate::models::port::{Port, PortReason, PortState, Protocol};
 
     static IP_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 100));
 
@@ -208,4 +255,89 @@ mod tests {
         let host: Host = Host::new(IP_ADDR);
         assert_eq!(host.average_rtt(), None);
     }
+
+    #[test]
+    fn add_port_keeps_ports_sorted() {
+        let mut host: Host = Host::new(IP_ADDR);
+        host.add_port(Port::new(
+            443,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        host.add_port(Port::new(
+            53,
+            Protocol::Udp,
+            PortState::Open,
+            PortReason::UdpResponse,
+        ));
+        host.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Closed,
+            PortReason::Rst,
+        ));
+
+        let order: Vec<(Protocol, u16)> =
+            host.ports.iter().map(|p| (p.protocol, p.number)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (Protocol::Tcp, 22),
+                (Protocol::Tcp, 443),
+                (Protocol::Udp, 53)
+            ]
+        );
+    }
+
+    #[test]
+    fn add_port_replaces_existing_entry() {
+        let mut host: Host = Host::new(IP_ADDR);
+        host.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Filtered,
+            PortReason::Timeout,
+        ));
+        host.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+
+        assert_eq!(host.ports.len(), 1);
+        assert_eq!(host.open_ports().count(), 1);
+        assert_eq!(
+            host.get_port(22, Protocol::Tcp).unwrap().reason,
+            PortReason::SynAck
+        );
+    }
+
+    #[test]
+    fn merge_combines_ports_and_keeps_existing_fields() {
+        let mut host: Host = Host::new(IP_ADDR);
+        host.hostname = Some("first".to_string());
+        host.add_port(Port::new(
+            22,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+
+        let mut other: Host = Host::new(IP_ADDR).with_rtt(Duration::from_millis(4));
+        other.hostname = Some("second".to_string());
+        other.add_port(Port::new(
+            53,
+            Protocol::Udp,
+            PortState::Open,
+            PortReason::UdpResponse,
+        ));
+
+        host.merge(other);
+
+        assert_eq!(host.hostname.as_deref(), Some("first"));
+        assert_eq!(host.ports.len(), 2);
+        assert_eq!(host.min_rtt(), Some(Duration::from_millis(4)));
+    }
 }
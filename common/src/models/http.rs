@@ -0,0 +1,33 @@
+//! # HTTP Model
+//!
+//! This module defines [`HttpInfo`], what a single page fetch from an HTTP(S) port revealed.
+//!
+//! ## Key Concepts
+//! * **Landing Page**: Only `/` is fetched; redirects are followed while they stay on the
+//!   same host and port, and every `Location` is recorded.
+//! * **Favicon Hash**: The MurmurHash3 of the base64 encoded `/favicon.ico`, computed the
+//!   same way as Shodan's `http.favicon.hash`, so values can be searched there directly.
+
+use serde::{Deserialize, Serialize};
+
+/// What fetching the landing page of a web service revealed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpInfo {
+    /// The status code of the final response.
+    pub status: u16,
+
+    /// The `<title>` of the final page.
+    pub title: Option<String>,
+
+    /// The `Server` header.
+    pub server: Option<String>,
+
+    /// The `X-Powered-By` header.
+    pub powered_by: Option<String>,
+
+    /// Every `Location` the server redirected to, in order.
+    pub redirects: Vec<String>,
+
+    /// Shodan compatible hash of `/favicon.ico`, if the server has one.
+    pub favicon_hash: Option<i32>,
+}
@@ -10,6 +10,8 @@
 use std::collections::HashSet;
 use std::net::IpAddr;
 
+use serde::{Deserialize, Serialize};
+
 /// Represents a group of services running on a specific local IP address.
 #[derive(Debug, Clone)]
 pub struct IpServiceGroup {
@@ -45,7 +47,8 @@ impl Service {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FirewallStatus {
     Active,
     Inactive,
@@ -1,4 +1,11 @@
+pub mod alert;
+pub mod event;
 pub mod host;
+pub mod http;
 pub mod localhost;
+pub mod port;
+pub mod probe;
 pub mod range;
+pub mod report;
 pub mod target;
+pub mod tls;
@@ -0,0 +1,500 @@
+//! # Port Model
+//!
+//! Defines which ports a scan should probe and what was learned about them.
+//!
+//! ## Key Concepts
+//! * **Port Entries**: A [`Port`] records the state of a single port on a host, why it was
+//!   classified that way, and what answered (TTL/window of the reply, detected service).
+//! * **Port Specs**: A [`PortSet`] is parsed from strings like `22,80,443`, `1-1024`, `-`
+//!   (every port), `T:80,U:53` or service names such as `http,ssh`.
+//! * **Protocol Prefixes**: `T:`, `U:` and `S:` switch the protocol for every following entry
+//!   until the next prefix. Entries without a prefix are TCP.
+//! * **Top Ports**: [`PortSet::top`] picks the most frequently open ports of a protocol
+//!   from the embedded service table.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use anyhow::{bail, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::info;
+use crate::models::http::HttpInfo;
+use crate::models::tls::TlsInfo;
+use crate::utils::service;
+
+/// The transport protocol a port belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Tcp,
+    Udp,
+    Sctp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Tcp => write!(f, "tcp"),
+            Protocol::Udp => write!(f, "udp"),
+            Protocol::Sctp => write!(f, "sctp"),
+        }
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "tcp" | "t" => Ok(Protocol::Tcp),
+            "udp" | "u" => Ok(Protocol::Udp),
+            "sctp" | "s" => Ok(Protocol::Sctp),
+            _ => bail!("Unknown protocol '{s}'"),
+        }
+    }
+}
+
+/// The state a port was classified into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortState {
+    /// An application accepts connections or datagrams on this port.
+    Open,
+    /// The port is reachable, but nothing listens on it.
+    Closed,
+    /// A firewall or filter prevents us from telling whether the port is open.
+    Filtered,
+    /// No answer at all, which for connectionless protocols may also mean open.
+    #[serde(rename = "open|filtered")]
+    OpenFiltered,
+}
+
+impl fmt::Display for PortState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortState::Open => write!(f, "open"),
+            PortState::Closed => write!(f, "closed"),
+            PortState::Filtered => write!(f, "filtered"),
+            PortState::OpenFiltered => write!(f, "open|filtered"),
+        }
+    }
+}
+
+/// The evidence a [`PortState`] is based on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortReason {
+    /// The target answered a SYN with a SYN/ACK.
+    SynAck,
+    /// The target answered with a TCP reset.
+    Rst,
+    /// A full `connect()` was actively refused by the target.
+    ConnRefused,
+    /// The target sent data back on a UDP port.
+    UdpResponse,
+    /// An ICMP error referencing our probe came back.
+    Icmp { icmp_type: u8, code: u8 },
+    /// Nothing came back before the probe timed out.
+    Timeout,
+}
+
+impl fmt::Display for PortReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortReason::SynAck => write!(f, "syn-ack"),
+            PortReason::Rst => write!(f, "reset"),
+            PortReason::ConnRefused => write!(f, "conn-refused"),
+            PortReason::UdpResponse => write!(f, "udp-response"),
+            PortReason::Timeout => write!(f, "no-response"),
+            // ICMPv4 destination unreachable codes (RFC 792, RFC 1812)
+            PortReason::Icmp { icmp_type: 3, code } => match code {
+                0 => write!(f, "net-unreach"),
+                1 => write!(f, "host-unreach"),
+                2 => write!(f, "proto-unreach"),
+                3 => write!(f, "port-unreach"),
+                9 | 10 | 13 => write!(f, "admin-prohibited"),
+                _ => write!(f, "icmp-3/{code}"),
+            },
+            PortReason::Icmp { icmp_type, code } => write!(f, "icmp-{icmp_type}/{code}"),
+        }
+    }
+}
+
+/// A single port on a host and everything learned about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    pub number: u16,
+    pub protocol: Protocol,
+    pub state: PortState,
+    pub reason: PortReason,
+
+    /// TTL (or hop limit) of the reply that classified the port.
+    pub ttl: Option<u8>,
+
+    /// TCP window size of the reply.
+    pub window: Option<u16>,
+
+    /// The service on the port; a guess from the service table until detected.
+    pub service: Option<String>,
+
+    /// The detected product/version string of the service.
+    pub version: Option<String>,
+
+    /// Handshake details and certificate, if the port speaks TLS.
+    pub tls: Option<TlsInfo>,
+
+    /// What the landing page revealed, if the port serves HTTP(S).
+    pub http: Option<HttpInfo>,
+
+    /// When the port was last classified.
+    pub last_seen: SystemTime,
+}
+
+impl Port {
+    /// Creates a port entry, naming the service after the embedded service table.
+    pub fn new(number: u16, protocol: Protocol, state: PortState, reason: PortReason) -> Self {
+        Self {
+            number,
+            protocol,
+            state,
+            reason,
+            ttl: None,
+            window: None,
+            service: service::get_name(number, protocol).map(String::from),
+            version: None,
+            tls: None,
+            http: None,
+            last_seen: SystemTime::now(),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: u8) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn with_window(mut self, window: u16) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state == PortState::Open
+    }
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} {}", self.number, self.protocol, self.state)?;
+        if let Some(service) = &self.service {
+            write!(f, " {service}")?;
+        }
+        if let Some(version) = &self.version {
+            write!(f, " ({version})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of ports to probe, grouped by protocol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortSet {
+    pub tcp: BTreeSet<u16>,
+    pub udp: BTreeSet<u16>,
+    pub sctp: BTreeSet<u16>,
+}
+
+impl PortSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a port specification (see the module docs for the syntax).
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let mut set = PortSet::new();
+        let mut protocol: Protocol = Protocol::Tcp;
+
+        for token in spec.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let token: &str = match token.split_once(':') {
+                Some((prefix, rest)) => {
+                    protocol = prefix.parse()?;
+                    let rest: &str = rest.trim();
+                    ensure!(!rest.is_empty(), "Missing ports after '{prefix}:'");
+                    rest
+                }
+                None => token,
+            };
+
+            set.parse_token(token, protocol)?;
+        }
+
+        ensure!(!set.is_empty(), "No valid ports found in '{spec}'");
+        Ok(set)
+    }
+
+    /// The `n` most frequently open ports of a protocol according to the service table.
+    pub fn top(n: usize, protocol: Protocol) -> Self {
+        let mut set = PortSet::new();
+        set.get_mut(protocol)
+            .extend(service::top_ports(n, protocol));
+        set
+    }
+
+    pub fn insert(&mut self, protocol: Protocol, port: u16) {
+        self.get_mut(protocol).insert(port);
+    }
+
+    pub fn get(&self, protocol: Protocol) -> &BTreeSet<u16> {
+        match protocol {
+            Protocol::Tcp => &self.tcp,
+            Protocol::Udp => &self.udp,
+            Protocol::Sctp => &self.sctp,
+        }
+    }
+
+    fn get_mut(&mut self, protocol: Protocol) -> &mut BTreeSet<u16> {
+        match protocol {
+            Protocol::Tcp => &mut self.tcp,
+            Protocol::Udp => &mut self.udp,
+            Protocol::Sctp => &mut self.sctp,
+        }
+    }
+
+    pub fn contains(&self, protocol: Protocol, port: u16) -> bool {
+        self.get(protocol).contains(&port)
+    }
+
+    /// Whether the set holds a port written as in reports (e.g. `22/tcp`).
+    pub fn contains_label(&self, label: &str) -> bool {
+        label
+            .split_once('/')
+            .and_then(|(port, protocol)| Some((port.parse().ok()?, protocol.parse().ok()?)))
+            .is_some_and(|(port, protocol)| self.contains(protocol, port))
+    }
+
+    /// The ports that are in both sets.
+    pub fn intersection(&self, other: &PortSet) -> PortSet {
+        PortSet {
+            tcp: self.tcp.intersection(&other.tcp).copied().collect(),
+            udp: self.udp.intersection(&other.udp).copied().collect(),
+            sctp: self.sctp.intersection(&other.sctp).copied().collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: PortSet) {
+        self.tcp.extend(other.tcp);
+        self.udp.extend(other.udp);
+        self.sctp.extend(other.sctp);
+    }
+
+    pub fn len(&self) -> usize {
+        self.tcp.len() + self.udp.len() + self.sctp.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tcp.is_empty() && self.udp.is_empty() && self.sctp.is_empty()
+    }
+
+    fn parse_token(&mut self, token: &str, protocol: Protocol) -> anyhow::Result<()> {
+        // Service names may contain dashes too (e.g. "http-proxy"), so only
+        // treat the token as numeric if it consists of digits and dashes.
+        if !token.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            let ports: Vec<u16> = service::get_ports(token, protocol);
+            ensure!(!ports.is_empty(), "Unknown {protocol} service '{token}'");
+            self.get_mut(protocol).extend(ports);
+            return Ok(());
+        }
+
+        let Some((start_str, end_str)) = token.split_once('-') else {
+            let port: u16 = parse_port(token)?;
+            self.insert(protocol, port);
+            return Ok(());
+        };
+
+        let mut start: u16 = match start_str {
+            "" => 1,
+            s => parse_port(s)?,
+        };
+        let mut end: u16 = match end_str {
+            "" => u16::MAX,
+            s => parse_port(s)?,
+        };
+
+        if start > end {
+            info!(verbosity = 1, "{start} > {end}. Reversing order.");
+            std::mem::swap(&mut start, &mut end);
+        }
+
+        self.get_mut(protocol).extend(start..=end);
+        Ok(())
+    }
+}
+
+/// Writes the set as a spec [`PortSet::parse`] reads back, with runs of ports collapsed
+/// (e.g. `T:22,80-90,U:53`).
+impl fmt::Display for PortSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens: Vec<String> = Vec::new();
+        for (prefix, ports) in [("T:", &self.tcp), ("U:", &self.udp), ("S:", &self.sctp)] {
+            let mut runs: Vec<(u16, u16)> = Vec::new();
+            for &port in ports {
+                match runs.last_mut() {
+                    Some((_, end)) if port == *end + 1 => *end = port,
+                    _ => runs.push((port, port)),
+                }
+            }
+
+            for (idx, (start, end)) in runs.into_iter().enumerate() {
+                let prefix: &str = if idx == 0 { prefix } else { "" };
+                tokens.push(match start == end {
+                    true => format!("{prefix}{start}"),
+                    false => format!("{prefix}{start}-{end}"),
+                });
+            }
+        }
+        write!(f, "{}", tokens.join(","))
+    }
+}
+
+impl FromStr for PortSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        PortSet::parse(s)
+    }
+}
+
+fn parse_port(s: &str) -> anyhow::Result<u16> {
+    let port: u16 = s
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid port '{s}': {e}"))?;
+    ensure!(port != 0, "Port 0 is not a valid target");
+    Ok(port)
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_list() {
+        let set = PortSet::parse("22, 80,443").unwrap();
+        assert_eq!(set.tcp, BTreeSet::from([22, 80, 443]));
+        assert!(set.udp.is_empty());
+    }
+
+    #[test]
+    fn parse_range() {
+        let set = PortSet::parse("1-1024").unwrap();
+        assert_eq!(set.tcp.len(), 1024);
+    }
+
+    #[test]
+    fn parse_reversed_range() {
+        let set = PortSet::parse("30-20").unwrap();
+        assert_eq!(set.tcp.len(), 11);
+    }
+
+    #[test]
+    fn parse_open_ended_ranges() {
+        assert_eq!(PortSet::parse("-").unwrap().tcp.len(), 65535);
+        assert_eq!(PortSet::parse("-100").unwrap().tcp.len(), 100);
+        assert_eq!(PortSet::parse("65530-").unwrap().tcp.len(), 6);
+    }
+
+    #[test]
+    fn parse_protocol_prefixes() {
+        let set = PortSet::parse("T:80,8080,U:53,161").unwrap();
+        assert_eq!(set.tcp, BTreeSet::from([80, 8080]));
+        assert_eq!(set.udp, BTreeSet::from([53, 161]));
+    }
+
+    #[test]
+    fn parse_sctp_prefix() {
+        let set = PortSet::parse("S:2905,T:22").unwrap();
+        assert_eq!(set.sctp, BTreeSet::from([2905]));
+        assert_eq!(set.tcp, BTreeSet::from([22]));
+    }
+
+    #[test]
+    fn parse_service_names() {
+        let set = PortSet::parse("http,ssh,U:snmp").unwrap();
+        assert_eq!(set.tcp, BTreeSet::from([22, 80]));
+        assert_eq!(set.udp, BTreeSet::from([161]));
+    }
+
+    #[test]
+    fn parse_should_reject_invalid_input() {
+        assert!(PortSet::parse("0").is_err());
+        assert!(PortSet::parse("65536").is_err());
+        assert!(PortSet::parse("not-a-service").is_err());
+        assert!(PortSet::parse("X:80").is_err());
+        assert!(PortSet::parse("T:").is_err());
+        assert!(PortSet::parse(",").is_err());
+    }
+
+    #[test]
+    fn port_should_guess_service_from_table() {
+        let port = Port::new(22, Protocol::Tcp, PortState::Open, PortReason::SynAck);
+        assert_eq!(port.service.as_deref(), Some("ssh"));
+        assert_eq!(port.to_string(), "22/tcp open ssh");
+    }
+
+    #[test]
+    fn reason_should_name_icmp_unreachables() {
+        let port_unreach = PortReason::Icmp {
+            icmp_type: 3,
+            code: 3,
+        };
+        let filtered = PortReason::Icmp {
+            icmp_type: 3,
+            code: 13,
+        };
+        let other = PortReason::Icmp {
+            icmp_type: 11,
+            code: 0,
+        };
+
+        assert_eq!(port_unreach.to_string(), "port-unreach");
+        assert_eq!(filtered.to_string(), "admin-prohibited");
+        assert_eq!(other.to_string(), "icmp-11/0");
+    }
+
+    #[test]
+    fn top_should_pick_most_common_tcp_ports() {
+        let set = PortSet::top(3, Protocol::Tcp);
+        assert_eq!(set.tcp, BTreeSet::from([23, 80, 443]));
+        assert!(set.udp.is_empty());
+    }
+
+    #[test]
+    fn top_should_pick_most_common_udp_ports() {
+        let set = PortSet::top(2, Protocol::Udp);
+        assert_eq!(set.udp, BTreeSet::from([161, 631]));
+        assert!(set.tcp.is_empty());
+    }
+
+    #[test]
+    fn display_should_round_trip_through_parse() {
+        let set = PortSet::parse("22,80-90,U:53,S:2905").unwrap();
+        assert_eq!(set.to_string(), "T:22,80-90,U:53,S:2905");
+        assert_eq!(PortSet::parse(&set.to_string()).unwrap(), set);
+        assert_eq!(PortSet::parse("-").unwrap().to_string(), "T:1-65535");
+
+        let probed = set.intersection(&PortSet::parse("22,U:53-60").unwrap());
+        assert!(probed.contains_label("53/udp"));
+        assert!(!probed.contains_label("80/tcp"));
+        assert!(!probed.contains_label("22"));
+    }
+}
@@ -0,0 +1,189 @@
+//! # Discovery Probes
+//!
+//! The techniques host discovery may use to elicit a response from a target.
+//! They are named after nmap's `-P` flags: `-PE` selects ICMP echo requests and
+//! `-PS22,80` TCP SYNs to ports 22 and 80.
+//!
+//! ## Key Concepts
+//! * **Port Lists**: `S`, `A` and `U` take an optional port list in `-p` syntax. Without one,
+//!   the probe falls back to [`DEFAULT_SYN_PORT`], [`DEFAULT_ACK_PORT`] or [`DEFAULT_UDP_PORT`].
+//!   Open-ended ranges such as `-` are left to `-p`, and a list holds at most
+//!   [`MAX_PROBE_PORTS`] ports, since every port costs one probe per target.
+//! * **Link Layer**: `R` (ARP) and `N` (NDP) only reach hosts on a directly attached network.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::models::port::PortSet;
+
+pub const DEFAULT_SYN_PORT: u16 = 443;
+pub const DEFAULT_ACK_PORT: u16 = 80;
+/// An unlikely-to-be-open port, so that the target answers with an ICMP port unreachable.
+pub const DEFAULT_UDP_PORT: u16 = 40_125;
+/// The most ports a single discovery probe may list.
+pub const MAX_PROBE_PORTS: usize = 100;
+
+/// A single discovery technique. A host is up if any selected probe gets an answer.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DiscoveryProbe {
+    /// TCP SYN to each port, answered by a SYN/ACK or RST.
+    TcpSyn(Vec<u16>),
+    /// TCP ACK to each port, answered by a RST.
+    TcpAck(Vec<u16>),
+    /// Empty UDP datagram to each port, answered by a reply or an ICMP port unreachable.
+    Udp(Vec<u16>),
+    /// ICMP echo request (ping).
+    IcmpEcho,
+    /// ICMP timestamp request.
+    IcmpTimestamp,
+    /// ICMP address mask request.
+    IcmpAddressMask,
+    /// ARP request to each on-link IPv4 target.
+    Arp,
+    /// Neighbor Solicitation to each on-link IPv6 target (plus a link-wide sweep for `lan`).
+    Ndp,
+}
+
+impl fmt::Display for DiscoveryProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |ports: &[u16]| -> String {
+            ports
+                .iter()
+                .map(u16::to_string)
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        match self {
+            DiscoveryProbe::TcpSyn(ports) => write!(f, "tcp-syn:{}", join(ports)),
+            DiscoveryProbe::TcpAck(ports) => write!(f, "tcp-ack:{}", join(ports)),
+            DiscoveryProbe::Udp(ports) => write!(f, "udp:{}", join(ports)),
+            DiscoveryProbe::IcmpEcho => write!(f, "icmp-echo"),
+            DiscoveryProbe::IcmpTimestamp => write!(f, "icmp-timestamp"),
+            DiscoveryProbe::IcmpAddressMask => write!(f, "icmp-mask"),
+            DiscoveryProbe::Arp => write!(f, "arp"),
+            DiscoveryProbe::Ndp => write!(f, "ndp"),
+        }
+    }
+}
+
+impl FromStr for DiscoveryProbe {
+    type Err = anyhow::Error;
+
+    /// Accepts the letter following nmap's `-P` (`S`, `A`, `U`, `E`, `P`, `M`, `R`) or `N`,
+    /// with a port list directly after `S`, `A` and `U`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut chars = s.chars();
+        let Some(kind) = chars.next() else {
+            bail!("Empty discovery probe");
+        };
+        let ports: &str = chars.as_str();
+
+        let probe: DiscoveryProbe = match kind {
+            'S' => DiscoveryProbe::TcpSyn(parse_ports(ports, DEFAULT_SYN_PORT)?),
+            'A' => DiscoveryProbe::TcpAck(parse_ports(ports, DEFAULT_ACK_PORT)?),
+            'U' => DiscoveryProbe::Udp(parse_ports(ports, DEFAULT_UDP_PORT)?),
+            'E' | 'P' | 'M' | 'R' | 'N' if !ports.is_empty() => {
+                bail!("Discovery probe '{kind}' takes no ports")
+            }
+            'E' => DiscoveryProbe::IcmpEcho,
+            'P' => DiscoveryProbe::IcmpTimestamp,
+            'M' => DiscoveryProbe::IcmpAddressMask,
+            'R' => DiscoveryProbe::Arp,
+            'N' => DiscoveryProbe::Ndp,
+            _ => bail!("Unknown discovery probe '{s}' (expected S, A, U, E, P, M, R or N)"),
+        };
+        Ok(probe)
+    }
+}
+
+/// Parses a port list in `-p` syntax, falling back to `default` if it is empty.
+fn parse_ports(spec: &str, default: u16) -> anyhow::Result<Vec<u16>> {
+    if spec.is_empty() {
+        return Ok(vec![default]);
+    }
+
+    let open_ended: bool = spec
+        .split(',')
+        .map(str::trim)
+        .any(|token| token.starts_with('-') || token.ends_with('-'));
+    if open_ended {
+        bail!("Open-ended port ranges are not allowed in discovery probe ports '{spec}'");
+    }
+
+    let port_set: PortSet = PortSet::parse(spec)?;
+    if !port_set.udp.is_empty() || !port_set.sctp.is_empty() {
+        bail!("Protocol prefixes are not allowed in discovery probe ports '{spec}'");
+    }
+    if port_set.tcp.len() > MAX_PROBE_PORTS {
+        bail!(
+            "Discovery probe ports '{spec}' list {} ports, at most {MAX_PROBE_PORTS} are allowed",
+            port_set.tcp.len()
+        );
+    }
+    Ok(port_set.tcp.into_iter().collect())
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nmap_letters() {
+        assert_eq!(
+            "E".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::IcmpEcho
+        );
+        assert_eq!(
+            "P".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::IcmpTimestamp
+        );
+        assert_eq!("R".parse::<DiscoveryProbe>().unwrap(), DiscoveryProbe::Arp);
+    }
+
+    #[test]
+    fn parses_port_lists_and_defaults() {
+        assert_eq!(
+            "S22,80-81".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::TcpSyn(vec![22, 80, 81])
+        );
+        assert_eq!(
+            "A".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::TcpAck(vec![DEFAULT_ACK_PORT])
+        );
+        assert_eq!(
+            "U53".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::Udp(vec![53])
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_probes() {
+        assert!("X".parse::<DiscoveryProbe>().is_err());
+        assert!("".parse::<DiscoveryProbe>().is_err());
+        assert!("E80".parse::<DiscoveryProbe>().is_err());
+        assert!("SU:53".parse::<DiscoveryProbe>().is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_port_lists() {
+        assert!("S-".parse::<DiscoveryProbe>().is_err());
+        assert!("A1024-".parse::<DiscoveryProbe>().is_err());
+        assert!("U1-65535".parse::<DiscoveryProbe>().is_err());
+        assert_eq!(
+            "S1-100".parse::<DiscoveryProbe>().unwrap(),
+            DiscoveryProbe::TcpSyn((1..=100).collect())
+        );
+    }
+}
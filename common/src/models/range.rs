@@ -1,5 +1,5 @@
-use std::collections::HashSet;
-use std::net::{IpAddr, Ipv4Addr};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 use crate::info;
 
@@ -57,6 +57,55 @@ impl Ipv4Range {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ipv6Range {
+    pub start_addr: Ipv6Addr,
+    pub end_addr: Ipv6Addr,
+}
+
+impl Ipv6Range {
+    pub fn new(start: Ipv6Addr, end: Ipv6Addr) -> Self {
+        if start <= end {
+            Self {
+                start_addr: start,
+                end_addr: end,
+            }
+        } else {
+            info!(verbosity = 1, "{start} > {end}. Reversing order.");
+            Self {
+                start_addr: end,
+                end_addr: start,
+            }
+        }
+    }
+
+    pub fn to_iter(&self) -> impl Iterator<Item = IpAddr> {
+        let start: u128 = self.start_addr.into();
+        let end: u128 = self.end_addr.into();
+        (start..=end).map(|ip| IpAddr::V6(Ipv6Addr::from(ip)))
+    }
+
+    pub fn contains(&self, ip: &Ipv6Addr) -> bool {
+        *ip >= self.start_addr && *ip <= self.end_addr
+    }
+
+    /// Number of addresses in the range, saturating at `u128::MAX` for `::/0`.
+    pub fn len(&self) -> u128 {
+        let s_u128: u128 = u128::from(self.start_addr);
+        let e_u128: u128 = u128::from(self.end_addr);
+
+        if e_u128 >= s_u128 {
+            (e_u128 - s_u128).saturating_add(1)
+        } else {
+            0
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 pub fn cidr_range(ip: Ipv4Addr, prefix: u8) -> anyhow::Result<Ipv4Range> {
     let network = pnet::ipnetwork::Ipv4Network::new(ip, prefix)?;
     let start = network.network();
@@ -65,10 +114,24 @@ pub fn cidr_range(ip: Ipv4Addr, prefix: u8) -> anyhow::Result<Ipv4Range> {
     Ok(Ipv4Range::new(start, end))
 }
 
+pub fn cidr_range_v6(ip: Ipv6Addr, prefix: u8) -> anyhow::Result<Ipv6Range> {
+    let network = pnet::ipnetwork::Ipv6Network::new(ip, prefix)?;
+    let start: u128 = network.network().into();
+    let host_mask: u128 = u128::MAX.checked_shr(u32::from(prefix)).unwrap_or(0);
+
+    Ok(Ipv6Range::new(
+        Ipv6Addr::from(start),
+        Ipv6Addr::from(start | host_mask),
+    ))
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct IpCollection {
     pub ranges: Vec<Ipv4Range>,
+    pub ranges_v6: Vec<Ipv6Range>,
     pub singles: HashSet<IpAddr>,
+    /// The hostname each address was resolved from, for targets given by name.
+    pub names: HashMap<IpAddr, String>,
 }
 
 impl IpCollection {
@@ -82,6 +145,15 @@ impl IpCollection {
         }
     }
 
+    /// Remembers that `ip` was resolved from `name`. The first name wins.
+    pub fn add_name(&mut self, ip: IpAddr, name: &str) {
+        self.names.entry(ip).or_insert_with(|| name.to_string());
+    }
+
+    pub fn name_of(&self, ip: &IpAddr) -> Option<&str> {
+        self.names.get(ip).map(String::as_str)
+    }
+
     pub fn add_range(&mut self, range: Ipv4Range) {
         info!(
             verbosity = 2,
@@ -93,65 +165,98 @@ impl IpCollection {
         self.ranges.push(range);
     }
 
+    pub fn add_range_v6(&mut self, range: Ipv6Range) {
+        info!(
+            verbosity = 2,
+            "Adding {} - {} to collection (Size: {})",
+            range.start_addr,
+            range.end_addr,
+            range.len()
+        );
+        self.ranges_v6.push(range);
+    }
+
     pub fn extend(&mut self, other: IpCollection) {
         info!(
             verbosity = 2,
             "Extending collection: Adding {} ranges and {} singles",
-            other.ranges.len(),
+            other.ranges.len() + other.ranges_v6.len(),
             other.singles.len()
         );
         self.ranges.extend(other.ranges);
+        self.ranges_v6.extend(other.ranges_v6);
         self.singles.extend(other.singles);
+        for (ip, name) in other.names {
+            self.names.entry(ip).or_insert(name);
+        }
     }
 
+    /// Total number of addresses, saturating at `usize::MAX`.
     pub fn len(&self) -> usize {
         let ranges_count: usize = self.ranges.iter().map(|r| r.len() as usize).sum();
+        let ranges_v6_count: usize = self
+            .ranges_v6
+            .iter()
+            .map(|r| usize::try_from(r.len()).unwrap_or(usize::MAX))
+            .fold(0, usize::saturating_add);
+
+        ranges_count
+            .saturating_add(ranges_v6_count)
+            .saturating_add(self.singles.len())
+    }
 
-        ranges_count + self.singles.len()
+    /// Number of IPv6 addresses, counting each only once.
+    pub fn len_v6(&self) -> u128 {
+        let mut compacted: IpCollection = IpCollection {
+            ranges_v6: self.ranges_v6.clone(),
+            singles: self
+                .singles
+                .iter()
+                .filter(|ip| ip.is_ipv6())
+                .copied()
+                .collect(),
+            ..IpCollection::default()
+        };
+        compacted.compact();
+        compacted
+            .ranges_v6
+            .iter()
+            .map(Ipv6Range::len)
+            .fold(0, u128::saturating_add)
     }
 
     /// Merges overlapping ranges and combines singles into ranges where possible.
     pub fn compact(&mut self) {
         let mut v4_singles: Vec<Ipv4Addr> = Vec::new();
-        self.singles.retain(|ip| {
-            if let IpAddr::V4(addr) = ip {
-                v4_singles.push(*addr);
-                false
-            } else {
-                true
-            }
+        let mut v6_singles: Vec<Ipv6Addr> = Vec::new();
+        self.singles.drain().for_each(|ip| match ip {
+            IpAddr::V4(addr) => v4_singles.push(addr),
+            IpAddr::V6(addr) => v6_singles.push(addr),
         });
 
         for ip in v4_singles {
             self.ranges.push(Ipv4Range::new(ip, ip));
         }
+        for ip in v6_singles {
+            self.ranges_v6.push(Ipv6Range::new(ip, ip));
+        }
 
         self.ranges.sort_by_key(|r| r.start_addr);
+        self.ranges = merge_sorted(&self.ranges);
 
-        if self.ranges.is_empty() {
-            return;
-        }
-
-        let mut merged: Vec<Ipv4Range> = Vec::new();
-        let mut current = self.ranges[0];
+        self.ranges_v6.sort_by_key(|r| r.start_addr);
+        self.ranges_v6 = merge_sorted(&self.ranges_v6);
+    }
 
-        for next in self.ranges.iter().skip(1) {
-            let curr_end = u32::from(current.end_addr);
-            let next_start = u32::from(next.start_addr);
+    /// Removes every address of `other` from the collection, splitting ranges as needed.
+    /// The collection ends up compacted.
+    pub fn subtract(&mut self, mut other: IpCollection) {
+        self.compact();
+        other.compact();
 
-            if next_start <= curr_end.saturating_add(1) {
-                let next_end = u32::from(next.end_addr);
-                if next_end > curr_end {
-                    current.end_addr = next.end_addr;
-                }
-            } else {
-                merged.push(current);
-                current = *next;
-            }
-        }
-        merged.push(current);
-
-        self.ranges = merged;
+        self.ranges = subtract_sorted(&self.ranges, &other.ranges);
+        self.ranges_v6 = subtract_sorted(&self.ranges_v6, &other.ranges_v6);
+        self.names.retain(|ip, _| !other.contains(ip));
     }
 
     pub fn contains(&self, ip: &IpAddr) -> bool {
@@ -159,37 +264,125 @@ impl IpCollection {
             return true;
         }
 
-        if let IpAddr::V4(ipv4_addr) = ip {
-            for range in &self.ranges {
-                if range.contains(ipv4_addr) {
-                    return true;
-                }
-            }
+        match ip {
+            IpAddr::V4(ipv4_addr) => self.ranges.iter().any(|r| r.contains(ipv4_addr)),
+            IpAddr::V6(ipv6_addr) => self.ranges_v6.iter().any(|r| r.contains(ipv6_addr)),
         }
-        false
     }
 
     pub fn is_empty(&self) -> bool {
-        self.ranges.is_empty() && self.singles.is_empty()
+        self.ranges.is_empty() && self.ranges_v6.is_empty() && self.singles.is_empty()
     }
 
     pub fn iter(&self) -> impl Iterator<Item = IpAddr> + '_ {
         let range_iter = self.ranges.iter().flat_map(|range| range.to_iter());
+        let range_v6_iter = self.ranges_v6.iter().flat_map(|range| range.to_iter());
 
         let single_iter = self
             .singles
             .iter()
-            .filter(|ip| {
-                if let IpAddr::V4(v4) = ip {
-                    !self.ranges.iter().any(|r| r.contains(v4))
-                } else {
-                    true
-                }
+            .filter(|ip| match ip {
+                IpAddr::V4(v4) => !self.ranges.iter().any(|r| r.contains(v4)),
+                IpAddr::V6(v6) => !self.ranges_v6.iter().any(|r| r.contains(v6)),
             })
             .copied();
 
-        range_iter.chain(single_iter)
+        range_iter.chain(range_v6_iter).chain(single_iter)
+    }
+}
+
+/// Shared view of [`Ipv4Range`] and [`Ipv6Range`] for compaction and subtraction.
+trait AddrRange: Copy {
+    /// First and last address as integers.
+    fn bounds(&self) -> (u128, u128);
+    /// Builds a range from integer bounds that fit the address family.
+    fn from_bounds(start: u128, end: u128) -> Self;
+}
+
+impl AddrRange for Ipv4Range {
+    fn bounds(&self) -> (u128, u128) {
+        (
+            u32::from(self.start_addr).into(),
+            u32::from(self.end_addr).into(),
+        )
+    }
+
+    fn from_bounds(start: u128, end: u128) -> Self {
+        let to_addr = |n: u128| Ipv4Addr::from(u32::try_from(n).unwrap_or(u32::MAX));
+        Self::new(to_addr(start), to_addr(end))
+    }
+}
+
+impl AddrRange for Ipv6Range {
+    fn bounds(&self) -> (u128, u128) {
+        (self.start_addr.into(), self.end_addr.into())
+    }
+
+    fn from_bounds(start: u128, end: u128) -> Self {
+        Self::new(Ipv6Addr::from(start), Ipv6Addr::from(end))
+    }
+}
+
+/// Merges overlapping or adjacent ranges of a list sorted by start address.
+fn merge_sorted<R: AddrRange>(ranges: &[R]) -> Vec<R> {
+    let Some(first) = ranges.first() else {
+        return Vec::new();
+    };
+
+    let mut merged: Vec<R> = Vec::new();
+    let mut current: R = *first;
+
+    for next in ranges.iter().skip(1) {
+        let (_, curr_end) = current.bounds();
+        let (next_start, next_end) = next.bounds();
+
+        if next_start <= curr_end.saturating_add(1) {
+            if next_end > curr_end {
+                current = R::from_bounds(current.bounds().0, next_end);
+            }
+        } else {
+            merged.push(current);
+            current = *next;
+        }
     }
+    merged.push(current);
+
+    merged
+}
+
+/// Removes the `holes` from `ranges`, splitting ranges that contain a hole.
+/// Both lists must be compacted (sorted and non-overlapping).
+fn subtract_sorted<R: AddrRange>(ranges: &[R], holes: &[R]) -> Vec<R> {
+    let mut remaining: Vec<R> = Vec::new();
+
+    for range in ranges {
+        let (start, end) = range.bounds();
+        let mut cursor: Option<u128> = Some(start);
+
+        for hole in holes {
+            let Some(curr_start) = cursor else {
+                break;
+            };
+            let (hole_start, hole_end) = hole.bounds();
+            if hole_end < curr_start {
+                continue;
+            }
+            if hole_start > end {
+                break;
+            }
+
+            if hole_start > curr_start {
+                remaining.push(R::from_bounds(curr_start, hole_start - 1));
+            }
+            cursor = hole_end.checked_add(1).filter(|next| *next <= end);
+        }
+
+        if let Some(curr_start) = cursor {
+            remaining.push(R::from_bounds(curr_start, end));
+        }
+    }
+
+    remaining
 }
 
 impl IntoIterator for IpCollection {
@@ -205,6 +398,9 @@ impl IntoIterator for IpCollection {
         for range in self.ranges {
             all_ips.extend(range.to_iter());
         }
+        for range in self.ranges_v6 {
+            all_ips.extend(range.to_iter());
+        }
 
         all_ips.into_iter()
     }
@@ -229,7 +425,90 @@ mod tests {
         let mut collection = IpCollection::new();
         let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
         collection.add_single(ip);
-        
-        assert!(collection.contains(&ip), "Collection should contain the single IP");
+
+        assert!(
+            collection.contains(&ip),
+            "Collection should contain the single IP"
+        );
+    }
+
+    #[test]
+    fn compact_merges_ipv6_ranges_and_singles() {
+        let mut collection = IpCollection::new();
+        collection.add_range_v6(Ipv6Range::new(
+            "2001:db8::10".parse().unwrap(),
+            "2001:db8::1f".parse().unwrap(),
+        ));
+        collection.add_range_v6(Ipv6Range::new(
+            "2001:db8::18".parse().unwrap(),
+            "2001:db8::2f".parse().unwrap(),
+        ));
+        collection.add_single("2001:db8::30".parse().unwrap());
+        collection.add_single("2001:db8::ff".parse().unwrap());
+
+        collection.compact();
+
+        assert_eq!(collection.ranges_v6.len(), 2);
+        assert!(collection.singles.is_empty());
+        assert_eq!(collection.len(), 0x21 + 1);
+        assert_eq!(collection.iter().count(), collection.len());
+        assert!(collection.contains(&"2001:db8::20".parse().unwrap()));
+        assert!(!collection.contains(&"2001:db8::31".parse().unwrap()));
+    }
+
+    #[test]
+    fn subtract_splits_ranges() {
+        let mut collection = IpCollection::new();
+        collection.add_range(cidr_range(Ipv4Addr::new(10, 0, 0, 0), 24).unwrap());
+        collection.add_range_v6(cidr_range_v6(Ipv6Addr::UNSPECIFIED, 120).unwrap());
+
+        let mut excluded = IpCollection::new();
+        excluded.add_single(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        excluded.add_range(Ipv4Range::new(
+            Ipv4Addr::new(10, 0, 0, 100),
+            Ipv4Addr::new(10, 0, 1, 5),
+        ));
+        excluded.add_range_v6(Ipv6Range::new(
+            Ipv6Addr::UNSPECIFIED,
+            Ipv6Addr::from(0xf_u128),
+        ));
+
+        collection.subtract(excluded);
+
+        assert_eq!(
+            collection.ranges,
+            vec![
+                Ipv4Range::new(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(10, 0, 0, 0)),
+                Ipv4Range::new(Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 99)),
+            ]
+        );
+        assert_eq!(collection.len(), 1 + 98 + 240);
+        assert!(!collection.contains(&IpAddr::V6(Ipv6Addr::from(0xf_u128))));
+        assert!(collection.contains(&IpAddr::V6(Ipv6Addr::from(0x10_u128))));
+    }
+
+    #[test]
+    fn subtract_everything_leaves_nothing() {
+        let mut collection = IpCollection::new();
+        collection.add_single(IpAddr::V4(Ipv4Addr::BROADCAST));
+        collection.add_name(IpAddr::V4(Ipv4Addr::BROADCAST), "all");
+
+        let mut excluded = IpCollection::new();
+        excluded.add_range(cidr_range(Ipv4Addr::UNSPECIFIED, 0).unwrap());
+
+        collection.subtract(excluded);
+
+        assert!(collection.is_empty());
+        assert!(collection.names.is_empty());
+    }
+
+    #[test]
+    fn ipv6_cidr_len_is_u128() {
+        let full: Ipv6Range = cidr_range_v6(Ipv6Addr::UNSPECIFIED, 0).unwrap();
+        assert_eq!(full.len(), u128::MAX);
+
+        let small: Ipv6Range = cidr_range_v6("2001:db8::1".parse().unwrap(), 126).unwrap();
+        assert_eq!(small.start_addr, "2001:db8::".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(small.len(), 4);
     }
 }
@@ -0,0 +1,382 @@
+//! # Report Model
+//!
+//! The structured form of a zond run, meant for other programs rather than the terminal.
+//!
+//! ## Key Concepts
+//! * **Versioned**: Every [`Report`] carries [`SCHEMA_VERSION`], which is bumped whenever a
+//!   field is renamed, removed or changes its meaning. Adding fields does not bump it.
+//! * **Stable Shape**: Fields without a value are present as `null` instead of being left out.
+//! * **Plain Values**: Durations are milliseconds and timestamps are seconds since the Unix epoch.
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime};
+
+use pnet::datalink::NetworkInterface;
+use serde::{Deserialize, Serialize};
+
+use crate::models::host::{Host, NetworkRole};
+use crate::models::http::HttpInfo;
+use crate::models::localhost::{FirewallStatus, IpServiceGroup, Service};
+use crate::models::port::{Port, PortSet, PortState, Protocol};
+use crate::models::tls::{Certificate, TlsInfo};
+
+/// The version of the report schema produced by this build.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Everything one run of a command found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub zond_version: String,
+    /// The command that produced the report (`discover`, `scan` or `info`).
+    pub command: String,
+    pub started_at: u64,
+    pub elapsed_ms: u64,
+    /// The number of addresses probed, `null` for commands without targets.
+    pub targets: Option<usize>,
+    /// The ports probed on every host as a port spec (e.g. `T:22,80-90,U:53`), `null` for
+    /// runs without a port scan.
+    #[serde(default)]
+    pub ports: Option<String>,
+    /// The hosts that responded, `null` for commands that do not look for hosts.
+    pub hosts: Option<Vec<HostRecord>>,
+    /// The local machine, only set by `info`.
+    pub system: Option<SystemRecord>,
+}
+
+impl Report {
+    pub fn new(
+        zond_version: &str,
+        command: &str,
+        started_at: SystemTime,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            zond_version: zond_version.to_string(),
+            command: command.to_string(),
+            started_at: unix_secs(started_at),
+            elapsed_ms: elapsed.as_millis() as u64,
+            targets: None,
+            ports: None,
+            hosts: None,
+            system: None,
+        }
+    }
+
+    pub fn with_targets(mut self, targets: usize) -> Self {
+        self.targets = Some(targets);
+        self
+    }
+
+    pub fn with_ports(mut self, ports: &PortSet) -> Self {
+        self.ports = Some(ports.to_string());
+        self
+    }
+
+    /// The ports the run probed, `None` if it did not scan ports or predates recording them.
+    pub fn probed_ports(&self) -> Option<PortSet> {
+        PortSet::parse(self.ports.as_deref()?).ok()
+    }
+
+    pub fn with_hosts(mut self, hosts: &[Host]) -> Self {
+        self.hosts = Some(hosts.iter().map(HostRecord::from).collect());
+        self
+    }
+
+    pub fn with_system(mut self, system: SystemRecord) -> Self {
+        self.system = Some(system);
+        self
+    }
+}
+
+/// A discovered host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostRecord {
+    pub primary_ip: IpAddr,
+    pub ips: Vec<IpAddr>,
+    pub mac: Option<String>,
+    pub vendor: Option<String>,
+    pub hostname: Option<String>,
+    pub roles: Vec<NetworkRole>,
+    pub rtt: Option<RttStats>,
+    pub ports: Vec<PortRecord>,
+}
+
+impl From<&Host> for HostRecord {
+    fn from(host: &Host) -> Self {
+        let mut roles: Vec<NetworkRole> = host.network_roles.iter().copied().collect();
+        roles.sort();
+
+        Self {
+            primary_ip: host.primary_ip,
+            ips: host.ips.iter().copied().collect(),
+            mac: host.mac.map(|mac| mac.to_string()),
+            vendor: host.vendor.clone(),
+            hostname: host.hostname.clone(),
+            roles,
+            rtt: RttStats::of(host),
+            ports: host.ports.iter().map(PortRecord::from).collect(),
+        }
+    }
+}
+
+/// Round-trip times over the last measurements of a host, in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RttStats {
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+impl RttStats {
+    fn of(host: &Host) -> Option<Self> {
+        Some(Self {
+            min_ms: millis(host.min_rtt()?),
+            avg_ms: millis(host.average_rtt()?),
+            max_ms: millis(host.max_rtt()?),
+        })
+    }
+}
+
+/// A classified port of a host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortRecord {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub state: PortState,
+    /// The evidence for the state (e.g. `syn-ack`, `port-unreach`).
+    pub reason: String,
+    pub ttl: Option<u8>,
+    pub window: Option<u16>,
+    pub service: Option<String>,
+    pub version: Option<String>,
+    pub tls: Option<TlsRecord>,
+    pub http: Option<HttpInfo>,
+}
+
+impl From<&Port> for PortRecord {
+    fn from(port: &Port) -> Self {
+        Self {
+            port: port.number,
+            protocol: port.protocol,
+            state: port.state,
+            reason: port.reason.to_string(),
+            ttl: port.ttl,
+            window: port.window,
+            service: port.service.clone(),
+            version: port.version.clone(),
+            tls: port.tls.as_ref().map(TlsRecord::from),
+            http: port.http.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TlsRecord {
+    pub version: String,
+    pub cipher: String,
+    pub certificate: Option<CertificateRecord>,
+}
+
+impl From<&TlsInfo> for TlsRecord {
+    fn from(tls: &TlsInfo) -> Self {
+        Self {
+            version: tls.version.clone(),
+            cipher: tls.cipher.clone(),
+            certificate: tls.certificate.as_ref().map(CertificateRecord::from),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CertificateRecord {
+    pub subject: String,
+    pub issuer: String,
+    pub sans: Vec<String>,
+    pub not_before: u64,
+    pub not_after: u64,
+    pub key_type: String,
+    pub key_bits: Option<usize>,
+}
+
+impl From<&Certificate> for CertificateRecord {
+    fn from(cert: &Certificate) -> Self {
+        Self {
+            subject: cert.subject.clone(),
+            issuer: cert.issuer.clone(),
+            sans: cert.sans.clone(),
+            not_before: unix_secs(cert.not_before),
+            not_after: unix_secs(cert.not_after),
+            key_type: cert.key_type.clone(),
+            key_bits: cert.key_bits,
+        }
+    }
+}
+
+/// The local machine as reported by `info`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemRecord {
+    pub hostname: Option<String>,
+    pub os: Option<String>,
+    pub user: Option<String>,
+    /// Only known when running as root.
+    pub firewall: Option<FirewallStatus>,
+    pub interfaces: Vec<InterfaceRecord>,
+    /// Listening services, only known when running as root.
+    pub services: Vec<ServiceRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceRecord {
+    pub name: String,
+    pub index: u32,
+    pub mac: Option<String>,
+    /// Addresses with their prefix length (e.g. `192.168.1.20/24`).
+    pub addrs: Vec<String>,
+    pub up: bool,
+    pub loopback: bool,
+}
+
+impl From<&NetworkInterface> for InterfaceRecord {
+    fn from(intf: &NetworkInterface) -> Self {
+        Self {
+            name: intf.name.clone(),
+            index: intf.index,
+            mac: intf.mac.map(|mac| mac.to_string()),
+            addrs: intf.ips.iter().map(|net| net.to_string()).collect(),
+            up: intf.is_up(),
+            loopback: intf.is_loopback(),
+        }
+    }
+}
+
+/// A local process listening on one or more ports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRecord {
+    pub name: String,
+    pub protocol: Protocol,
+    pub addr: IpAddr,
+    pub ports: Vec<u16>,
+}
+
+impl ServiceRecord {
+    /// Flattens the per-address service groups into one record per service and protocol.
+    pub fn from_groups(groups: &[IpServiceGroup]) -> Vec<Self> {
+        groups
+            .iter()
+            .flat_map(|group| {
+                let tcp = group
+                    .tcp_services
+                    .iter()
+                    .map(|s| Self::new(s, Protocol::Tcp));
+                let udp = group
+                    .udp_services
+                    .iter()
+                    .map(|s| Self::new(s, Protocol::Udp));
+                tcp.chain(udp)
+            })
+            .collect()
+    }
+
+    fn new(service: &Service, protocol: Protocol) -> Self {
+        let mut ports: Vec<u16> = service.local_ports.iter().copied().collect();
+        ports.sort_unstable();
+        Self {
+            name: service.name.clone(),
+            protocol,
+            addr: service.local_addr,
+            ports,
+        }
+    }
+}
+
+pub(crate) fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+pub(crate) fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pnet::datalink::MacAddr;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::models::port::PortReason;
+
+    fn sample_host() -> Host {
+        let mut host: Host = Host::new(IpAddr::from([192, 168, 1, 1]))
+            .with_mac(MacAddr::new(0x2c, 0xcf, 0x67, 0xf2, 0x51, 0xe3))
+            .with_rtt(Duration::from_millis(2))
+            .with_rtt(Duration::from_millis(4));
+        host.hostname = Some("router.lan".to_string());
+        host.network_roles = HashSet::from([NetworkRole::DNS, NetworkRole::Gateway]);
+        host.add_port(Port::new(
+            53,
+            Protocol::Udp,
+            PortState::OpenFiltered,
+            PortReason::Timeout,
+        ));
+        host.add_port(
+            Port::new(22, Protocol::Tcp, PortState::Open, PortReason::SynAck).with_ttl(64),
+        );
+        host
+    }
+
+    #[test]
+    fn serializes_hosts_with_stable_field_names() {
+        let report: Report = Report::new(
+            "0.0.0",
+            "scan",
+            SystemTime::UNIX_EPOCH,
+            Duration::from_secs(2),
+        )
+        .with_hosts(&[sample_host()]);
+        let value: Value = serde_json::to_value(&report).unwrap();
+        let host: &Value = &value["hosts"][0];
+
+        assert_eq!(value["schema_version"], json!(SCHEMA_VERSION));
+        assert_eq!(value["elapsed_ms"], json!(2000));
+        assert_eq!(value["system"], Value::Null);
+        assert_eq!(host["mac"], json!("2c:cf:67:f2:51:e3"));
+        assert_eq!(host["roles"], json!(["gateway", "dns"]));
+        assert_eq!(
+            host["rtt"],
+            json!({ "min_ms": 2.0, "avg_ms": 3.0, "max_ms": 4.0 })
+        );
+        assert_eq!(
+            host["ports"][0],
+            json!({
+                "port": 22, "protocol": "tcp", "state": "open", "reason": "syn-ack",
+                "ttl": 64, "window": null, "service": "ssh", "version": null,
+                "tls": null, "http": null
+            })
+        );
+        assert_eq!(host["ports"][1]["state"], json!("open|filtered"));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let report: Report = Report::new("0.0.0", "discover", SystemTime::now(), Duration::ZERO)
+            .with_hosts(&[sample_host(), Host::new(IpAddr::from([10, 0, 0, 7]))]);
+        let json: String = serde_json::to_string(&report).unwrap();
+
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    }
+}
@@ -2,21 +2,38 @@
 //!
 //! Defines the possible inputs for a network scan.
 //!
-//! This module handles parsing strings (IPs, ranges, CIDRs, keywords)
+//! This module handles parsing strings (IPs, ranges, CIDRs, keywords, hostnames)
 //! and converting them into a unified collection of IP addresses.
+//!
+//! Hostnames are not resolved here: [`hostnames`] lists them so the caller can look them up,
+//! and [`to_collection_with`] takes the results as a [`NameTable`].
+//!
+//! Targets and exclusions can also be read from files with [`read_specs`].
 
-use std::net::{IpAddr, Ipv4Addr};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use anyhow::{bail, ensure};
+use anyhow::{Context, bail, ensure};
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
 
 use crate::interface;
-use crate::models::range::{self, IpCollection, Ipv4Range};
+use crate::models::range::{self, IpCollection, Ipv4Range, Ipv6Range};
 use crate::{info, success, warn};
 
 /// Global flag to indicate if we are strictly scanning the LAN.
 pub static IS_LAN_SCAN: AtomicBool = AtomicBool::new(false);
 
+/// Addresses of each hostname target, as returned by a forward DNS lookup.
+pub type NameTable = HashMap<String, Vec<IpAddr>>;
+
+/// Most IPv6 addresses accepted as targets, per range and in total (a /108).
+/// Anything bigger cannot be swept.
+pub const MAX_IPV6_RANGE_LEN: u128 = 1 << 20;
+
 /// Represents a validated network target.
 ///
 /// Simplified to only contain concrete network data.
@@ -26,6 +43,8 @@ pub enum Target {
     Host(IpAddr),
     /// A range of hosts (e.g., 192.168.1.0/24 or 10-20)
     Range(Ipv4Range),
+    /// A range of IPv6 hosts (e.g., 2001:db8::/120 or fe80::1-ff)
+    RangeV6(Ipv6Range),
 }
 
 /// The main entry point. Converts CLI arguments into an IP collection.
@@ -35,31 +54,70 @@ pub enum Target {
 /// * Space-separated strings ("1.1.1.1 2.2.2.2")
 /// * Keywords ("lan")
 /// * CIDR and Range parsing
+///
+/// Hostname targets are skipped, use [`to_collection_with`] to include them.
 pub fn to_collection<S: AsRef<str>>(inputs: &[S]) -> anyhow::Result<IpCollection> {
+    to_collection_with(inputs, &NameTable::new())
+}
+
+/// Like [`to_collection`], but hostname targets ("router.lan", "example.com/28")
+/// are expanded to the addresses found in `names`.
+pub fn to_collection_with<S: AsRef<str>>(
+    inputs: &[S],
+    names: &NameTable,
+) -> anyhow::Result<IpCollection> {
+    to_collection_excluding(inputs, &[] as &[&str], names)
+}
+
+/// Like [`to_collection_with`], minus every address matched by `excludes`.
+/// Exclusions take the same syntax as targets, except for keywords. Unlike a target,
+/// an excluded hostname missing from `names` is an error.
+pub fn to_collection_excluding<S: AsRef<str>, E: AsRef<str>>(
+    inputs: &[S],
+    excludes: &[E],
+    names: &NameTable,
+) -> anyhow::Result<IpCollection> {
     let mut collection = IpCollection::new();
 
-    for input in inputs {
-        let s = input.as_ref().trim();
-        if s.is_empty() {
-            continue;
-        }
+    parse_many_into(&split_inputs(inputs), names, &mut collection)?;
 
-        if s.contains(',') {
-            let split_inputs: Vec<&str> = s
-                .split(',')
-                .map(|part| part.trim())
-                .filter(|part| !part.is_empty())
-                .collect();
+    ensure!(!collection.is_empty(), "No valid targets found");
 
-            parse_many_into(&split_inputs, &mut collection)?;
-        } else {
-            parse_single_into(s, &mut collection)?;
+    let excludes: Vec<&str> = split_inputs(excludes);
+    if !excludes.is_empty() {
+        if let Some(keyword) = excludes
+            .iter()
+            .find(|s| s.eq_ignore_ascii_case("lan") || s.eq_ignore_ascii_case("vpn"))
+        {
+            bail!("Keyword '{keyword}' cannot be excluded");
+        }
+        // Scanning an address the user meant to skip is worse than not scanning at all
+        if let Some(name) = hostnames(&excludes)
+            .into_iter()
+            .find(|name| names.get(name).is_none_or(Vec::is_empty))
+        {
+            bail!("Exclusion '{name}' could not be resolved, refusing to scan without it");
         }
-    }
 
-    ensure!(!collection.is_empty(), "No valid targets found");
+        let mut excluded = IpCollection::new();
+        parse_many_into(&excludes, names, &mut excluded)?;
+
+        let before: usize = collection.len();
+        collection.subtract(excluded);
+        info!(
+            verbosity = 1,
+            "Excluded {} addresses",
+            before.saturating_sub(collection.len())
+        );
+        ensure!(!collection.is_empty(), "No targets left after exclusions");
+    }
 
     collection.compact();
+    let len_v6: u128 = collection.len_v6();
+    ensure!(
+        len_v6 <= MAX_IPV6_RANGE_LEN,
+        "The targets add up to {len_v6} IPv6 addresses, the limit is {MAX_IPV6_RANGE_LEN}"
+    );
 
     let len = collection.len();
     let unit = if len == 1 {
@@ -72,25 +130,83 @@ pub fn to_collection<S: AsRef<str>>(inputs: &[S]) -> anyhow::Result<IpCollection
     Ok(collection)
 }
 
+/// The hostnames among the targets that need a forward lookup, without CIDR suffixes.
+pub fn hostnames<S: AsRef<str>>(inputs: &[S]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for s in split_inputs(inputs) {
+        if let Some((name, _)) = split_name_target(s)
+            && !names.iter().any(|known| known == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Reads target specs from a file, or from stdin if `path` is "-".
+///
+/// Specs are separated by newlines, commas or whitespace; `#` starts a comment.
+pub fn read_specs(path: &Path) -> anyhow::Result<Vec<String>> {
+    if path == Path::new("-") {
+        return parse_specs(io::stdin().lock());
+    }
+
+    let file: File =
+        File::open(path).with_context(|| format!("Failed to open '{}'", path.display()))?;
+    parse_specs(BufReader::new(file))
+        .with_context(|| format!("Failed to read '{}'", path.display()))
+}
+
+fn parse_specs(reader: impl BufRead) -> anyhow::Result<Vec<String>> {
+    let mut specs: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line: String = line?;
+        let content: &str = line
+            .split_once('#')
+            .map_or(line.as_str(), |(content, _)| content);
+        specs.extend(content.split_whitespace().map(str::to_string));
+    }
+    Ok(specs)
+}
+
+/// Splits comma-separated inputs into trimmed, non-empty targets.
+fn split_inputs<S: AsRef<str>>(inputs: &[S]) -> Vec<&str> {
+    inputs
+        .iter()
+        .flat_map(|input| input.as_ref().split(','))
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
 /// Helper to parse a list of strings directly into the collection
 fn parse_many_into<S: AsRef<str>>(
     inputs: &[S],
+    names: &NameTable,
     collection: &mut IpCollection,
 ) -> anyhow::Result<()> {
     for input in inputs {
-        parse_single_into(input.as_ref(), collection)?;
+        parse_single_into(input.as_ref(), names, collection)?;
     }
     Ok(())
 }
 
 /// Parses a single string and adds it to the collection.
-fn parse_single_into(s: &str, collection: &mut IpCollection) -> anyhow::Result<()> {
+fn parse_single_into(
+    s: &str,
+    names: &NameTable,
+    collection: &mut IpCollection,
+) -> anyhow::Result<()> {
     if s.eq_ignore_ascii_case("lan") {
         return resolve_lan(collection);
     }
 
     if s.eq_ignore_ascii_case("vpn") {
-        bail!("VPN scan target not yet implemented");
+        return resolve_vpn(collection);
+    }
+
+    if let Some((name, prefix)) = split_name_target(s) {
+        return add_name_target(name, prefix, names, collection);
     }
 
     if let Some(target) = parse_as_target(s)? {
@@ -109,6 +225,16 @@ fn parse_single_into(s: &str, collection: &mut IpCollection) -> anyhow::Result<(
                 );
                 collection.add_range(range)
             }
+            Target::RangeV6(range) => {
+                info!(
+                    verbosity = 2,
+                    "Parsed '{s}' as a range: {} to {} ({} hosts)",
+                    range.start_addr,
+                    range.end_addr,
+                    range.len()
+                );
+                collection.add_range_v6(range)
+            }
         }
         return Ok(());
     }
@@ -124,18 +250,99 @@ fn parse_as_target(s: &str) -> anyhow::Result<Option<Target>> {
     }
 
     // IP Range? (Start-End)
-    if let Some(range) = parse_ip_range(s)? {
-        return Ok(Some(Target::Range(range)));
+    if let Some(target) = parse_ip_range(s)? {
+        return Ok(Some(target));
     }
 
     // CIDR? (Network/Prefix)
-    if let Some(range) = parse_cidr_range(s)? {
-        return Ok(Some(Target::Range(range)));
+    if let Some(target) = parse_cidr_range(s)? {
+        return Ok(Some(target));
     }
 
     Ok(None)
 }
 
+/// Splits a hostname target into the name and an optional CIDR prefix
+/// ("example.com/28"). Returns `None` if `s` is not a hostname.
+fn split_name_target(s: &str) -> Option<(&str, Option<&str>)> {
+    if s.eq_ignore_ascii_case("lan") || s.eq_ignore_ascii_case("vpn") {
+        return None;
+    }
+
+    let (name, prefix) = match s.split_once('/') {
+        Some((name, prefix)) => (name, Some(prefix)),
+        None => (s, None),
+    };
+    is_hostname(name).then_some((name, prefix))
+}
+
+/// Whether `s` is a syntactically valid DNS name that cannot be read as an address.
+///
+/// The last label must not be all digits, so "10.0.0.1-5" is never taken for a name.
+fn is_hostname(s: &str) -> bool {
+    let name: &str = s.strip_suffix('.').unwrap_or(s);
+    if name.is_empty() || name.len() > 253 {
+        return false;
+    }
+
+    let is_label = |label: &str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let tld_has_letter: bool = name
+        .rsplit('.')
+        .next()
+        .is_some_and(|tld| tld.chars().any(|c| c.is_ascii_alphabetic()));
+
+    tld_has_letter && name.split('.').all(is_label)
+}
+
+/// Adds the resolved addresses of a hostname (or the IPv4 networks around them) to the collection.
+fn add_name_target(
+    name: &str,
+    prefix: Option<&str>,
+    names: &NameTable,
+    collection: &mut IpCollection,
+) -> anyhow::Result<()> {
+    let Some(addrs) = names.get(name).filter(|addrs| !addrs.is_empty()) else {
+        warn!("Skipping '{name}': hostname could not be resolved");
+        return Ok(());
+    };
+
+    let prefix: Option<u8> = prefix
+        .map(|prefix_str| {
+            prefix_str
+                .parse::<u8>()
+                .map_err(|e| anyhow::anyhow!("Invalid prefix in CIDR '{prefix_str}': {e}"))
+        })
+        .transpose()?;
+
+    // The prefix is an IPv4 one, so AAAA records are scanned as single hosts
+    if let Some(prefix) = prefix
+        && addrs.iter().any(IpAddr::is_ipv6)
+    {
+        warn!(
+            "Scanning the IPv6 addresses of '{name}' as single hosts, /{prefix} applies to IPv4 only"
+        );
+    }
+
+    for addr in addrs {
+        info!(verbosity = 2, "Resolved '{name}' to {addr}");
+        collection.add_name(*addr, name);
+
+        match (addr, prefix) {
+            (IpAddr::V4(ipv4_addr), Some(prefix)) => {
+                collection.add_range(range::cidr_range(*ipv4_addr, prefix)?)
+            }
+            _ => collection.add_single(*addr),
+        }
+    }
+
+    Ok(())
+}
+
 /// Logic for the "lan" keyword.
 fn resolve_lan(collection: &mut IpCollection) -> anyhow::Result<()> {
     let Some(net) = interface::get_lan_network()? else {
@@ -164,19 +371,58 @@ fn resolve_lan(collection: &mut IpCollection) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Parses a range string like "1.1.1.1-2.2.2.2" or "1.1.1.1-50".
-fn parse_ip_range(s: &str) -> anyhow::Result<Option<Ipv4Range>> {
+/// Logic for the "vpn" keyword: every network reachable through a tunnel interface.
+fn resolve_vpn(collection: &mut IpCollection) -> anyhow::Result<()> {
+    for network in interface::get_vpn_networks()? {
+        info!(verbosity = 1, "Scanning VPN network {network}");
+        match network {
+            IpNetwork::V4(net) => collection.add_range(usable_range(net)),
+            IpNetwork::V6(net) => {
+                collection.add_range_v6(range::cidr_range_v6(net.ip(), net.prefix())?)
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The hosts of a network, without the network and broadcast addresses if it has room for them.
+fn usable_range(net: Ipv4Network) -> Ipv4Range {
+    let net_u32: u32 = u32::from(net.network());
+    let broadcast_u32: u32 = u32::from(net.broadcast());
+
+    if net.prefix() <= 30 {
+        Ipv4Range::new(
+            Ipv4Addr::from(net_u32 + 1),
+            Ipv4Addr::from(broadcast_u32 - 1),
+        )
+    } else {
+        Ipv4Range::new(net.network(), net.broadcast())
+    }
+}
+
+/// Parses a range string like "1.1.1.1-2.2.2.2", "1.1.1.1-50" or "fe80::1-ff".
+fn parse_ip_range(s: &str) -> anyhow::Result<Option<Target>> {
     let Some((start_str, end_str)) = s.split_once('-') else {
         return Ok(None);
     };
 
     let start_addr = start_str
-        .parse::<Ipv4Addr>()
+        .parse::<IpAddr>()
         .map_err(|e| anyhow::anyhow!("Invalid start IP in range '{start_str}': {e}"))?;
 
-    let end_addr = parse_range_end_addr(end_str, &start_addr, s)?;
-
-    Ok(Some(Ipv4Range::new(start_addr, end_addr)))
+    match start_addr {
+        IpAddr::V4(start_addr) => {
+            let end_addr = parse_range_end_addr(end_str, &start_addr, s)?;
+            Ok(Some(Target::Range(Ipv4Range::new(start_addr, end_addr))))
+        }
+        IpAddr::V6(start_addr) => {
+            let end_addr = parse_range_end_addr_v6(end_str, &start_addr, s)?;
+            let range = Ipv6Range::new(start_addr, end_addr);
+            ensure_ipv6_range_len(&range, s)?;
+            Ok(Some(Target::RangeV6(range)))
+        }
+    }
 }
 
 /// Helper to parse the end address of a range.
@@ -212,23 +458,76 @@ fn parse_range_end_addr(
     Ok(Ipv4Addr::from(end_octets))
 }
 
-/// Parses CIDR notation like "192.168.1.0/24".
-fn parse_cidr_range(s: &str) -> anyhow::Result<Option<Ipv4Range>> {
+/// Helper to parse the end address of an IPv6 range.
+///
+/// An abbreviated suffix ("ff", "1:ff") replaces the last groups of the start address.
+fn parse_range_end_addr_v6(
+    end_str: &str,
+    start_addr: &Ipv6Addr,
+    original_s: &str,
+) -> anyhow::Result<Ipv6Addr> {
+    if let Ok(full_addr) = end_str.parse::<Ipv6Addr>() {
+        return Ok(full_addr);
+    }
+
+    if end_str.is_empty() {
+        bail!("End range cannot be empty: {original_s}");
+    }
+
+    let mut end_segments = start_addr.segments();
+    let partial_segments: Vec<u16> = end_str
+        .split(':')
+        .map(|group_str| u16::from_str_radix(group_str, 16))
+        .collect::<Result<Vec<u16>, _>>()
+        .map_err(|e| anyhow::anyhow!("Invalid end range '{end_str}': {e}"))?;
+
+    if partial_segments.len() > 8 {
+        bail!("End range has too many groups: {end_str}");
+    }
+
+    let start_index = 8 - partial_segments.len();
+    end_segments[start_index..].copy_from_slice(&partial_segments);
+
+    Ok(Ipv6Addr::from(end_segments))
+}
+
+/// Parses CIDR notation like "192.168.1.0/24" or "2001:db8::/120".
+fn parse_cidr_range(s: &str) -> anyhow::Result<Option<Target>> {
     let Some((ip_str, prefix_str)) = s.split_once('/') else {
         return Ok(None);
     };
 
-    let ipv4_addr = ip_str
-        .parse::<Ipv4Addr>()
+    let ip_addr = ip_str
+        .parse::<IpAddr>()
         .map_err(|e| anyhow::anyhow!("Invalid IP in CIDR '{ip_str}': {e}"))?;
 
     let prefix = prefix_str
         .parse::<u8>()
         .map_err(|e| anyhow::anyhow!("Invalid prefix in CIDR '{prefix_str}': {e}"))?;
 
-    let ipv4_range = range::cidr_range(ipv4_addr, prefix).map_err(|e| anyhow::anyhow!("{}", e))?;
+    match ip_addr {
+        IpAddr::V4(ipv4_addr) => {
+            let ipv4_range =
+                range::cidr_range(ipv4_addr, prefix).map_err(|e| anyhow::anyhow!("{}", e))?;
+            Ok(Some(Target::Range(ipv4_range)))
+        }
+        IpAddr::V6(ipv6_addr) => {
+            let ipv6_range =
+                range::cidr_range_v6(ipv6_addr, prefix).map_err(|e| anyhow::anyhow!("{}", e))?;
+            ensure_ipv6_range_len(&ipv6_range, s)?;
+            Ok(Some(Target::RangeV6(ipv6_range)))
+        }
+    }
+}
 
-    Ok(Some(ipv4_range))
+/// Rejects IPv6 ranges too large to sweep address by address.
+fn ensure_ipv6_range_len(range: &Ipv6Range, original_s: &str) -> anyhow::Result<()> {
+    ensure!(
+        range.len() <= MAX_IPV6_RANGE_LEN,
+        "IPv6 range '{original_s}' has {} addresses, the limit is {MAX_IPV6_RANGE_LEN} (a /108)",
+        range.len()
+    );
+    Ok(())
 }
 
 // ╔════════════════════════════════════════════╗
@@ -281,6 +580,139 @@ mod tests {
         assert_eq!(col.len(), 3);
     }
 
+    #[test]
+    fn test_parse_ipv6_cidr() {
+        let input = vec!["2001:db8::/120"];
+        let col = to_collection(&input).unwrap();
+        assert_eq!(col.len(), 256);
+        assert!(col.contains(&"2001:db8::ff".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_parse_ipv6_range_short() {
+        let input = vec!["fe80::1-ff"];
+        let col = to_collection(&input).unwrap();
+        assert_eq!(col.len(), 255);
+
+        let input = vec!["fe80::1:0-2:ffff"];
+        let col = to_collection(&input).unwrap();
+        assert_eq!(col.len(), 2 * 65536);
+    }
+
+    #[test]
+    fn test_rejects_huge_ipv6_ranges() {
+        assert!(to_collection(&["2001:db8::/64"]).is_err());
+        assert!(to_collection(&["::-ffff::"]).is_err());
+        assert!(to_collection(&["2001:db8::/108"]).is_ok());
+        // Each range is within the limit, together they are not
+        assert!(to_collection(&["2001:db8::/108, 2001:db8:1::/108"]).is_err());
+        assert!(to_collection(&["2001:db8::/108, 2001:db8::/109"]).is_ok());
+    }
+
+    #[test]
+    fn test_hostnames_are_detected() {
+        let input = vec!["router.lan, 10.0.0.1-5", "example.com/28 fe80::1-ff", "lan"];
+        assert_eq!(hostnames(&input), vec!["router.lan", "example.com"]);
+
+        assert!(!is_hostname("10.0.0.1"));
+        assert!(!is_hostname("-bad.example"));
+        assert!(is_hostname("my-router"));
+        assert!(is_hostname("example.com."));
+    }
+
+    #[test]
+    fn test_parse_resolved_hostnames() {
+        let names: NameTable = NameTable::from([
+            (
+                "dual.example".to_string(),
+                vec![
+                    "192.0.2.10".parse().unwrap(),
+                    "2001:db8::10".parse().unwrap(),
+                ],
+            ),
+            (
+                "net.example".to_string(),
+                vec!["198.51.100.7".parse().unwrap()],
+            ),
+        ]);
+
+        let input = vec!["dual.example, net.example/30"];
+        let col = to_collection_with(&input, &names).unwrap();
+
+        assert_eq!(col.len(), 2 + 4);
+        assert!(col.contains(&"198.51.100.4".parse().unwrap()));
+        assert_eq!(
+            col.name_of(&"2001:db8::10".parse().unwrap()),
+            Some("dual.example")
+        );
+        assert_eq!(col.name_of(&"198.51.100.4".parse().unwrap()), None);
+
+        // The prefix widens the A record only, the AAAA record stays a single host
+        let col = to_collection_with(&["dual.example/28"], &names).unwrap();
+        assert_eq!(col.len(), 16 + 1);
+        assert!(col.contains(&"192.0.2.15".parse().unwrap()));
+        assert!(col.contains(&"2001:db8::10".parse().unwrap()));
+        assert!(!col.contains(&"2001:db8::11".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_unresolved_hostnames_are_skipped() {
+        assert!(to_collection(&["nowhere.example"]).is_err());
+        assert_eq!(
+            to_collection(&["nowhere.example", "10.0.0.1"])
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_exclusions_are_subtracted() {
+        let col = to_collection_excluding(
+            &["10.0.0.0/24"],
+            &["10.0.0.1, 10.0.0.128/25"],
+            &NameTable::new(),
+        )
+        .unwrap();
+        assert_eq!(col.len(), 127);
+        assert!(!col.contains(&"10.0.0.1".parse().unwrap()));
+        assert!(col.contains(&"10.0.0.2".parse().unwrap()));
+
+        assert!(
+            to_collection_excluding(&["10.0.0.1"], &["10.0.0.0/30"], &NameTable::new()).is_err()
+        );
+        assert!(to_collection_excluding(&["10.0.0.1"], &["lan"], &NameTable::new()).is_err());
+    }
+
+    #[test]
+    fn test_unresolved_exclusions_fail_closed() {
+        let mut names = NameTable::new();
+        names.insert("printer.lan".to_string(), vec!["10.0.0.9".parse().unwrap()]);
+
+        let col =
+            to_collection_excluding(&["10.0.0.0/28, nas.lan"], &["printer.lan"], &names).unwrap();
+        assert!(!col.contains(&"10.0.0.9".parse().unwrap()));
+        assert!(to_collection_excluding(&["10.0.0.0/28"], &["nas.lan"], &names).is_err());
+    }
+
+    #[test]
+    fn test_parse_specs_skips_comments() {
+        let file: &[u8] = b"# production\n10.0.0.1  # db\n\n10.0.1.0/24 10.0.2.1,10.0.2.2\n";
+        let specs: Vec<String> = parse_specs(file).unwrap();
+        assert_eq!(specs, vec!["10.0.0.1", "10.0.1.0/24", "10.0.2.1,10.0.2.2"]);
+        assert_eq!(to_collection(&specs).unwrap().len(), 1 + 256 + 2);
+    }
+
+    #[test]
+    fn test_usable_range_strips_network_and_broadcast() {
+        let range: Ipv4Range = usable_range("10.8.0.2/24".parse().unwrap());
+        assert_eq!(range.start_addr, Ipv4Addr::new(10, 8, 0, 1));
+        assert_eq!(range.len(), 254);
+
+        let point_to_point: Ipv4Range = usable_range("10.8.0.2/31".parse().unwrap());
+        assert_eq!(point_to_point.len(), 2);
+    }
+
     #[test]
     fn test_comma_splitting() {
         let input = vec!["1.1.1.1, 1.1.1.2"];
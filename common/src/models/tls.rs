@@ -0,0 +1,123 @@
+//! # TLS Model
+//!
+//! This module defines [`TlsInfo`], the outcome of a completed TLS handshake with a port,
+//! and the [`Certificate`] the server presented during it.
+//!
+//! ## Key Concepts
+//! * **Leaf Only**: Only the server's own certificate is kept, not the chain above it.
+//! * **Unverified**: Certificates are recorded as presented; nothing here says they are trusted.
+
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime},
+};
+
+/// What a TLS handshake with a port revealed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsInfo {
+    /// The negotiated protocol version (e.g. `TLSv1.3`).
+    pub version: String,
+
+    /// The negotiated cipher suite (e.g. `TLS13_AES_256_GCM_SHA384`).
+    pub cipher: String,
+
+    /// The leaf certificate, if the server sent one that could be parsed.
+    pub certificate: Option<Certificate>,
+}
+
+/// The leaf certificate presented by a server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Certificate {
+    /// The subject distinguished name (e.g. `CN=example.com, O=Example`).
+    pub subject: String,
+
+    /// The issuer distinguished name.
+    pub issuer: String,
+
+    /// DNS names and IP addresses of the Subject Alternative Name extension.
+    pub sans: Vec<String>,
+
+    pub not_before: SystemTime,
+    pub not_after: SystemTime,
+
+    /// The public key algorithm (e.g. `RSA`, `EC`, `Ed25519`).
+    pub key_type: String,
+
+    /// The public key size in bits, if known.
+    pub key_bits: Option<usize>,
+}
+
+impl Certificate {
+    pub fn is_expired(&self) -> bool {
+        SystemTime::now() > self.not_after
+    }
+
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer
+    }
+
+    /// Time left until the certificate expires, `None` once it has.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.not_after.duration_since(SystemTime::now()).ok()
+    }
+
+    /// SAN entries that name a single host, skipping IP addresses and wildcards.
+    pub fn dns_names(&self) -> impl Iterator<Item = &str> {
+        self.sans
+            .iter()
+            .map(String::as_str)
+            .filter(|san| !san.starts_with("*.") && san.parse::<IpAddr>().is_err())
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    fn certificate(not_after: SystemTime) -> Certificate {
+        Certificate {
+            subject: "CN=example.com".to_string(),
+            issuer: "CN=Example CA".to_string(),
+            sans: vec![
+                "*.example.com".to_string(),
+                "example.com".to_string(),
+                "192.0.2.1".to_string(),
+                "www.example.com".to_string(),
+            ],
+            not_before: SystemTime::UNIX_EPOCH,
+            not_after,
+            key_type: "RSA".to_string(),
+            key_bits: Some(2048),
+        }
+    }
+
+    #[test]
+    fn dns_names_skip_wildcards_and_ips() {
+        let cert: Certificate = certificate(SystemTime::now() + DAY);
+        let names: Vec<&str> = cert.dns_names().collect();
+        assert_eq!(names, vec!["example.com", "www.example.com"]);
+    }
+
+    #[test]
+    fn expiry_is_derived_from_not_after() {
+        let valid: Certificate = certificate(SystemTime::now() + 30 * DAY);
+        assert!(!valid.is_expired());
+        assert!(valid.remaining().unwrap() > 29 * DAY);
+
+        let expired: Certificate = certificate(SystemTime::now() - DAY);
+        assert!(expired.is_expired());
+        assert_eq!(expired.remaining(), None);
+        assert!(!expired.is_self_signed());
+    }
+}
@@ -0,0 +1,213 @@
+//! **Network Roles** known from this machine's own configuration.
+//!
+//! * **Gateway**: next hops of the default routes (see [`interface::get_default_gateways`]).
+//! * **DNS**: the `nameserver` entries of the resolver configuration.
+//! * **DHCP**: the server identifiers recorded in DHCP lease files.
+//!
+//! Roles learned from traffic (router advertisements, DHCP offers) are tagged by the scanners.
+
+use std::collections::HashSet;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::info;
+use crate::interface;
+use crate::models::host::{Host, NetworkRole};
+
+const RESOLV_CONF_PATHS: [&str; 2] = ["/etc/resolv.conf", "/run/systemd/resolve/resolv.conf"];
+
+/// Directories of dhclient, NetworkManager and systemd-networkd leases.
+const LEASE_DIRS: [&str; 4] = [
+    "/var/lib/dhcp",
+    "/var/lib/dhclient",
+    "/var/lib/NetworkManager",
+    "/run/systemd/netif/leases",
+];
+
+/// The addresses this machine uses as gateway, DNS and DHCP servers.
+#[derive(Debug, Clone, Default)]
+pub struct KnownRoles {
+    pub gateways: HashSet<IpAddr>,
+    pub dns_servers: HashSet<IpAddr>,
+    pub dhcp_servers: HashSet<IpAddr>,
+}
+
+impl KnownRoles {
+    /// Reads the routing table, resolver configuration and DHCP leases.
+    pub fn detect() -> Self {
+        let roles: KnownRoles = Self {
+            gateways: interface::get_default_gateways().into_iter().collect(),
+            dns_servers: read_dns_servers(),
+            dhcp_servers: read_dhcp_servers(),
+        };
+        info!(
+            verbosity = 2,
+            "Known roles: gateways {:?}, DNS {:?}, DHCP {:?}",
+            roles.gateways,
+            roles.dns_servers,
+            roles.dhcp_servers
+        );
+        roles
+    }
+
+    /// Adds the roles matching any address of each host.
+    pub fn tag(&self, hosts: &mut [Host]) {
+        for host in hosts {
+            for ip in &host.ips {
+                if self.gateways.contains(ip) {
+                    host.network_roles.insert(NetworkRole::Gateway);
+                }
+                if self.dns_servers.contains(ip) {
+                    host.network_roles.insert(NetworkRole::DNS);
+                }
+                if self.dhcp_servers.contains(ip) {
+                    host.network_roles.insert(NetworkRole::DHCP);
+                }
+            }
+        }
+    }
+}
+
+fn read_dns_servers() -> HashSet<IpAddr> {
+    RESOLV_CONF_PATHS
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| parse_resolv_conf(&content))
+        .collect()
+}
+
+/// The `nameserver` lines of a resolv.conf, skipping local stub resolvers.
+fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("nameserver") {
+                return None;
+            }
+            // Link-local IPv6 servers carry a scope ("fe80::1%eth0")
+            let addr: &str = fields.next()?.split('%').next()?;
+            addr.parse::<IpAddr>().ok()
+        })
+        .filter(|ip| !ip.is_loopback())
+        .collect()
+}
+
+fn read_dhcp_servers() -> HashSet<IpAddr> {
+    let mut servers: HashSet<IpAddr> = LEASE_DIRS
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_lease_file(&entry.path()))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .flat_map(|content| parse_lease(&content))
+        .collect();
+
+    #[cfg(target_os = "macos")]
+    servers.extend(macos_dhcp_servers());
+
+    servers.retain(|ip| !ip.is_unspecified());
+    servers
+}
+
+fn is_lease_file(path: &Path) -> bool {
+    let in_networkd_dir: bool = path.starts_with("/run/systemd/netif/leases");
+    let has_lease_ext: bool = path
+        .extension()
+        .is_some_and(|ext| ext == "lease" || ext == "leases");
+    path.is_file() && (in_networkd_dir || has_lease_ext)
+}
+
+/// Server identifiers of a lease file, in dhclient (`option dhcp-server-identifier 10.0.0.1;`)
+/// or systemd-networkd/NetworkManager (`SERVER_ADDRESS=10.0.0.1`) format.
+fn parse_lease(content: &str) -> Vec<IpAddr> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line: &str = line.trim();
+            let value: &str = line
+                .strip_prefix("option dhcp-server-identifier")
+                .or_else(|| line.strip_prefix("SERVER_ADDRESS="))?;
+            value.trim().trim_end_matches(';').parse::<IpAddr>().ok()
+        })
+        .collect()
+}
+
+/// Asks `ipconfig` for the server identifier of each interface's lease.
+#[cfg(target_os = "macos")]
+fn macos_dhcp_servers() -> Vec<IpAddr> {
+    use std::process::Command;
+
+    pnet::datalink::interfaces()
+        .iter()
+        .filter(|intf| intf.is_up() && !intf.is_loopback())
+        .filter_map(|intf| {
+            Command::new("ipconfig")
+                .args(["getoption", &intf.name, "server_identifier"])
+                .output()
+                .ok()
+        })
+        .filter_map(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+        .collect()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_resolv_conf() {
+        let content: &str = "# generated\nnameserver 10.0.0.53\nnameserver 127.0.0.53\n\
+                             nameserver fe80::1%eth0\nsearch lan\n";
+        assert_eq!(
+            parse_resolv_conf(content),
+            vec![
+                "10.0.0.53".parse::<IpAddr>().unwrap(),
+                "fe80::1".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_dhclient_and_networkd_leases() {
+        let dhclient: &str =
+            "lease {\n  interface \"eth0\";\n  option dhcp-server-identifier 192.168.1.1;\n}\n";
+        let networkd: &str = "ADDRESS=192.168.1.50\nSERVER_ADDRESS=192.168.1.2\n";
+
+        assert_eq!(parse_lease(dhclient), vec![IpAddr::from([192, 168, 1, 1])]);
+        assert_eq!(parse_lease(networkd), vec![IpAddr::from([192, 168, 1, 2])]);
+    }
+
+    #[test]
+    fn tags_hosts_by_address() {
+        let gateway: IpAddr = IpAddr::from([192, 168, 1, 1]);
+        let roles: KnownRoles = KnownRoles {
+            gateways: HashSet::from([gateway]),
+            dns_servers: HashSet::from([gateway]),
+            dhcp_servers: HashSet::new(),
+        };
+        let mut hosts: Vec<Host> = vec![
+            Host::new(gateway),
+            Host::new(IpAddr::from([192, 168, 1, 9])),
+        ];
+
+        roles.tag(&mut hosts);
+
+        assert_eq!(
+            hosts[0].network_roles,
+            HashSet::from([NetworkRole::Gateway, NetworkRole::DNS])
+        );
+        assert!(hosts[1].network_roles.is_empty());
+    }
+}
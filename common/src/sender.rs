@@ -14,7 +14,10 @@ use crate::interface::NetworkInterfaceExtension;
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum PacketType {
     ARP,
+    /// Link-wide IPv6 sweep: all-nodes echo request and router solicitation.
     ICMPv6,
+    /// Neighbor solicitation to each IPv6 target.
+    NDP,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -70,6 +73,10 @@ impl SenderConfig {
         self.targets_v4.clone()
     }
 
+    pub fn get_targets_v6(&self) -> HashSet<Ipv6Addr> {
+        self.targets_v6.clone()
+    }
+
     pub fn len(&self) -> usize {
         self.targets_v4.len() + self.targets_v6.len()
     }
@@ -2,4 +2,5 @@ pub mod input;
 pub mod ip;
 pub mod mac;
 pub mod redact;
+pub mod service;
 pub mod timing;
@@ -58,8 +58,3 @@ pub fn reverse_address_to_ptr(ip_addr: &IpAddr) -> String {
         }
     }
 }
-
-pub fn get_gateway_addr(_ip_addr: &IpAddr) -> IpAddr {
-    // Simplified stub as per original implementation
-    std::net::IpAddr::V4(std::net::Ipv4Addr::new(192, 168, 0, 1))
-}
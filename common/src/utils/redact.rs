@@ -8,6 +8,8 @@ use std::net::Ipv6Addr;
 
 use pnet::util::MacAddr;
 
+use crate::utils::ip::{self, Ipv6AddressType};
+
 /// Redacts a hostname to protect privacy while maintaining some recognizability.
 ///
 /// It preserves the first 2 and last 2 characters, replacing the middle with a fixed
@@ -118,6 +120,27 @@ pub fn unique_local(addr: &Ipv6Addr) -> String {
     format!("{:x}::XXXX", segments[0])
 }
 
+/// Redacts an IPv6 address according to its type, leaving loopback and unspecified
+/// addresses untouched.
+///
+/// # Examples
+/// ```
+/// use std::net::Ipv6Addr;
+/// use zond_common::utils::redact;
+///
+/// let ip = "fe80::ca52:61ff:fec7:594".parse::<Ipv6Addr>().unwrap();
+/// assert_eq!(redact::ipv6_addr(&ip), "fe80::ca52:61ff:XXXX:XXXX");
+/// assert_eq!(redact::ipv6_addr(&Ipv6Addr::LOCALHOST), "::1");
+/// ```
+pub fn ipv6_addr(addr: &Ipv6Addr) -> String {
+    match ip::get_ipv6_type(addr) {
+        Ipv6AddressType::GlobalUnicast => global_unicast(addr),
+        Ipv6AddressType::UniqueLocal => unique_local(addr),
+        Ipv6AddressType::LinkLocal => link_local(addr),
+        _ => addr.to_string(),
+    }
+}
+
 // ╔════════════════════════════════════════════╗
 // ║ ████████╗███████╗███████╗████████╗███████╗ ║
 // ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
@@ -0,0 +1,132 @@
+//! Lookups against the embedded **port/service table**.
+//!
+//! The table ships inside the binary (`data/services.txt`) and is ranked by how often
+//! a port is found open, which is what powers `--top-ports` and named port specs.
+
+use std::sync::OnceLock;
+
+use crate::models::port::Protocol;
+
+const SERVICES_TXT: &str = include_str!("../../data/services.txt");
+
+static SERVICE_DB: OnceLock<Vec<ServiceEntry>> = OnceLock::new();
+
+/// A single row of the service table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServiceEntry {
+    pub name: &'static str,
+    pub port: u16,
+    pub protocol: Protocol,
+}
+
+/// Retrieves or initializes the service table, preserving its ranked order.
+fn get_service_db() -> &'static [ServiceEntry] {
+    SERVICE_DB.get_or_init(|| parse_table(SERVICES_TXT).expect("failed to load service table"))
+}
+
+fn parse_table(table: &'static str) -> anyhow::Result<Vec<ServiceEntry>> {
+    let mut entries: Vec<ServiceEntry> = Vec::new();
+
+    for (idx, line) in table.lines().enumerate() {
+        let line: &str = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (fields.next(), fields.next()) else {
+            anyhow::bail!("line {}: expected '<service> <port>/<protocol>'", idx + 1);
+        };
+
+        let Some((port, protocol)) = port_proto.split_once('/') else {
+            anyhow::bail!("line {}: missing protocol in '{port_proto}'", idx + 1);
+        };
+
+        entries.push(ServiceEntry {
+            name,
+            port: port.parse()?,
+            protocol: protocol.parse()?,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Returns the service name registered for a port.
+pub fn get_name(port: u16, protocol: Protocol) -> Option<&'static str> {
+    get_service_db()
+        .iter()
+        .find(|entry| entry.port == port && entry.protocol == protocol)
+        .map(|entry| entry.name)
+}
+
+/// Returns every port registered under a service name (case-insensitive).
+pub fn get_ports(name: &str, protocol: Protocol) -> Vec<u16> {
+    get_service_db()
+        .iter()
+        .filter(|entry| entry.protocol == protocol && entry.name.eq_ignore_ascii_case(name))
+        .map(|entry| entry.port)
+        .collect()
+}
+
+/// Returns the `n` most frequently open ports, most common first.
+pub fn top_ports(n: usize, protocol: Protocol) -> Vec<u16> {
+    get_service_db()
+        .iter()
+        .filter(|entry| entry.protocol == protocol)
+        .map(|entry| entry.port)
+        .take(n)
+        .collect()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn embedded_table_should_parse() {
+        assert!(parse_table(SERVICES_TXT).is_ok());
+    }
+
+    #[test]
+    fn embedded_table_should_not_contain_duplicates() {
+        let mut seen: HashSet<(u16, Protocol)> = HashSet::new();
+        for entry in get_service_db() {
+            assert!(
+                seen.insert((entry.port, entry.protocol)),
+                "{}/{} is listed twice",
+                entry.port,
+                entry.protocol
+            );
+        }
+    }
+
+    #[test]
+    fn get_name_should_respect_protocol() {
+        assert_eq!(get_name(22, Protocol::Tcp), Some("ssh"));
+        assert_eq!(get_name(161, Protocol::Udp), Some("snmp"));
+        assert_eq!(get_name(161, Protocol::Tcp), None);
+    }
+
+    #[test]
+    fn get_ports_should_be_case_insensitive() {
+        assert_eq!(get_ports("HTTPS", Protocol::Tcp), vec![443]);
+        assert!(get_ports("not-a-service", Protocol::Tcp).is_empty());
+    }
+
+    #[test]
+    fn top_ports_should_follow_ranking() {
+        let top: Vec<u16> = top_ports(5, Protocol::Tcp);
+        assert_eq!(top, vec![80, 23, 443, 21, 22]);
+    }
+}
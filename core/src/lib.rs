@@ -1,4 +1,5 @@
 pub mod info;
+pub mod listener;
 pub mod network;
 pub mod scanner;
 pub mod system;
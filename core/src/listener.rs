@@ -0,0 +1,545 @@
+//! A **passive listener** that builds a host inventory from overheard traffic.
+//!
+//! Nothing is ever sent: the interface is opened with [`channel::start_capture`] and every
+//! frame is inspected for what it reveals about its sender:
+//! - **ARP** requests and replies map MAC to IPv4 addresses.
+//! - **IPv4/IPv6** source addresses within the interface's networks do the same.
+//! - **DHCP** requests name the client, replies hand it an address and reveal the server.
+//! - **mDNS** and **NetBIOS** announcements carry hostnames, **SSDP** marks UPnP devices.
+//!
+//! Hosts are keyed by MAC address, so a device that changes or adds addresses stays one host.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::ensure;
+use is_root::is_root;
+use pnet::{
+    datalink::NetworkInterface,
+    ipnetwork::IpNetwork,
+    packet::{
+        Packet,
+        arp::ArpPacket,
+        ethernet::{EtherTypes, EthernetPacket},
+        ip::IpNextHeaderProtocols,
+        ipv4::Ipv4Packet,
+        ipv6::Ipv6Packet,
+        udp::UdpPacket,
+    },
+    util::MacAddr,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use zond_common::{
+    config::Config,
+    interface,
+    models::host::{Host, NetworkRole},
+    roles::KnownRoles,
+    success,
+};
+use zond_protocols::{dhcp, mdns, netbios, ssdp};
+
+use crate::{
+    network::channel::{self, EthernetHandle},
+    scanner::{self, ScanState},
+};
+
+const MDNS_PORT: u16 = 5353;
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The protocol a host was learned from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Source {
+    Arp,
+    Ip,
+    Dhcp,
+    Mdns,
+    Ssdp,
+    Netbios,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Arp => write!(f, "arp"),
+            Source::Ip => write!(f, "ip"),
+            Source::Dhcp => write!(f, "dhcp"),
+            Source::Mdns => write!(f, "mdns"),
+            Source::Ssdp => write!(f, "ssdp"),
+            Source::Netbios => write!(f, "netbios"),
+        }
+    }
+}
+
+/// A host learned from traffic, and when it was heard from.
+#[derive(Debug, Clone)]
+pub struct Sighting {
+    pub host: Host,
+    pub first_seen: SystemTime,
+    pub last_seen: SystemTime,
+    pub sources: BTreeSet<Source>,
+}
+
+/// A single fact a frame revealed about a device.
+#[derive(Debug, Clone, PartialEq)]
+struct Observation {
+    mac: MacAddr,
+    ip: Option<IpAddr>,
+    hostname: Option<String>,
+    role: Option<NetworkRole>,
+    source: Source,
+}
+
+impl Observation {
+    fn new(mac: MacAddr, ip: Option<IpAddr>, source: Source) -> Self {
+        Self {
+            mac,
+            ip,
+            hostname: None,
+            role: None,
+            source,
+        }
+    }
+
+    fn with_hostname(mut self, hostname: Option<String>) -> Self {
+        self.hostname = hostname;
+        self
+    }
+
+    fn with_role(mut self, role: NetworkRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+}
+
+pub struct PassiveListener {
+    eth_handle: EthernetHandle,
+    networks: Vec<IpNetwork>,
+    inventory: Inventory,
+    state: Arc<ScanState>,
+}
+
+/// Every host heard from so far, keyed by MAC address.
+struct Inventory {
+    local_mac: Option<MacAddr>,
+    sightings: HashMap<MacAddr, Sighting>,
+    /// Hostnames of devices that did not reveal an address yet (e.g. DHCP discovers).
+    pending_names: HashMap<MacAddr, String>,
+    state: Arc<ScanState>,
+}
+
+/// Listens on `intf` until `duration` elapsed or the user stops it.
+///
+/// ### Integration Notes
+/// - **Privileges**: Requires root to open the interface in promiscuous mode.
+/// - **Updates**: Every new host and every change to one is sent to `updates`.
+/// - **State**: Counts hosts in `state` and stops once [`ScanState::stop`] was called.
+pub async fn perform_listen(
+    intf: &NetworkInterface,
+    duration: Option<Duration>,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    updates: Option<UnboundedSender<Sighting>>,
+) -> anyhow::Result<Vec<Sighting>> {
+    ensure!(is_root(), "Passive listening requires root privileges");
+
+    if !cfg.disable_input {
+        scanner::spawn_user_input_listener(state.clone());
+    }
+
+    let listener: PassiveListener = PassiveListener::new(intf, state)?;
+    success!("Listening passively on {}", intf.name);
+    Ok(listener.run(duration, updates).await)
+}
+
+/// The interface named by the user, or the best LAN interface.
+pub fn select_interface(name: Option<&str>) -> anyhow::Result<NetworkInterface> {
+    match name {
+        Some(name) => interface::get_by_name(name),
+        None => interface::get_lan_interface(),
+    }
+}
+
+impl PassiveListener {
+    pub fn new(intf: &NetworkInterface, state: Arc<ScanState>) -> anyhow::Result<Self> {
+        Ok(Self {
+            eth_handle: channel::start_capture(intf)?,
+            networks: intf.ips.clone(),
+            inventory: Inventory::new(intf.mac, state.clone()),
+            state,
+        })
+    }
+
+    pub async fn run(
+        mut self,
+        duration: Option<Duration>,
+        updates: Option<UnboundedSender<Sighting>>,
+    ) -> Vec<Sighting> {
+        let deadline = tokio::time::sleep(duration.unwrap_or(Duration::MAX));
+        tokio::pin!(deadline);
+
+        loop {
+            if self.state.is_stopped() {
+                break;
+            }
+
+            tokio::select! {
+                frame = self.eth_handle.rx.recv() => {
+                    let Some(frame) = frame else {
+                        break;
+                    };
+                    for observation in observe(&frame, &self.networks) {
+                        if let Some(sighting) = self.inventory.record(observation)
+                            && let Some(tx) = &updates
+                        {
+                            let _ = tx.send(sighting.clone());
+                        }
+                    }
+                }
+                _ = &mut deadline, if duration.is_some() => break,
+                _ = tokio::time::sleep(STOP_POLL_INTERVAL) => {}
+            }
+        }
+
+        let mut sightings: Vec<Sighting> = self.inventory.sightings.into_values().collect();
+        let roles: KnownRoles = KnownRoles::detect();
+        for sighting in &mut sightings {
+            roles.tag(std::slice::from_mut(&mut sighting.host));
+        }
+        sightings
+    }
+}
+
+impl Inventory {
+    fn new(local_mac: Option<MacAddr>, state: Arc<ScanState>) -> Self {
+        Self {
+            local_mac,
+            sightings: HashMap::new(),
+            pending_names: HashMap::new(),
+            state,
+        }
+    }
+
+    /// Folds an observation into the inventory, returning the host if anything new was learned.
+    fn record(&mut self, observation: Observation) -> Option<&Sighting> {
+        let Observation {
+            mac,
+            ip,
+            hostname,
+            role,
+            source,
+        } = observation;
+
+        if Some(mac) == self.local_mac || mac == MacAddr::zero() || mac == MacAddr::broadcast() {
+            return None;
+        }
+
+        let now: SystemTime = SystemTime::now();
+        let mut changed: bool = false;
+
+        if !self.sightings.contains_key(&mac) {
+            let Some(ip) = ip else {
+                if let Some(name) = hostname {
+                    self.pending_names.insert(mac, name);
+                }
+                return None;
+            };
+
+            self.state.add_host();
+            self.sightings.insert(
+                mac,
+                Sighting {
+                    host: Host::new(ip).with_mac(mac),
+                    first_seen: now,
+                    last_seen: now,
+                    sources: BTreeSet::new(),
+                },
+            );
+            changed = true;
+        }
+
+        let pending_name: Option<String> = self.pending_names.remove(&mac);
+        let sighting: &mut Sighting = self.sightings.get_mut(&mac)?;
+        let host: &mut Host = &mut sighting.host;
+        sighting.last_seen = now;
+        sighting.sources.insert(source);
+
+        if let Some(ip) = ip {
+            changed |= host.ips.insert(ip);
+            if ip.is_ipv4() && host.primary_ip.is_ipv6() {
+                host.primary_ip = ip;
+            }
+        }
+
+        if host.hostname.is_none()
+            && let Some(name) = hostname.or(pending_name)
+        {
+            host.hostname = Some(name);
+            changed = true;
+        }
+
+        if let Some(role) = role {
+            changed |= host.network_roles.insert(role);
+        }
+
+        changed.then_some(&*sighting)
+    }
+}
+
+/// Extracts everything a single Ethernet frame reveals about the devices involved.
+fn observe(frame: &[u8], networks: &[IpNetwork]) -> Vec<Observation> {
+    let Some(eth) = EthernetPacket::new(frame) else {
+        return Vec::new();
+    };
+    let src_mac: MacAddr = eth.get_source();
+
+    match eth.get_ethertype() {
+        EtherTypes::Arp => {
+            let Some(arp) = ArpPacket::new(eth.payload()) else {
+                return Vec::new();
+            };
+            let sender: Ipv4Addr = arp.get_sender_proto_addr();
+            // ARP probes announce no address of their own yet
+            let ip: Option<IpAddr> = (!sender.is_unspecified()).then_some(IpAddr::V4(sender));
+            vec![Observation::new(arp.get_sender_hw_addr(), ip, Source::Arp)]
+        }
+        EtherTypes::Ipv4 => {
+            let Some(ipv4) = Ipv4Packet::new(eth.payload()) else {
+                return Vec::new();
+            };
+            let src_ip: IpAddr = IpAddr::V4(ipv4.get_source());
+            let mut observations: Vec<Observation> = Vec::new();
+
+            if is_on_link(src_ip, networks) {
+                observations.push(Observation::new(src_mac, Some(src_ip), Source::Ip));
+            }
+            if ipv4.get_next_level_protocol() == IpNextHeaderProtocols::Udp
+                && let Some(udp) = UdpPacket::new(ipv4.payload())
+            {
+                observations.extend(observe_udp(src_mac, src_ip, &udp, networks));
+            }
+
+            observations
+        }
+        EtherTypes::Ipv6 => {
+            let Some(ipv6) = Ipv6Packet::new(eth.payload()) else {
+                return Vec::new();
+            };
+            let src_ip: IpAddr = IpAddr::V6(ipv6.get_source());
+            let mut observations: Vec<Observation> = Vec::new();
+
+            if is_on_link(src_ip, networks) {
+                observations.push(Observation::new(src_mac, Some(src_ip), Source::Ip));
+            }
+            if ipv6.get_next_header() == IpNextHeaderProtocols::Udp
+                && let Some(udp) = UdpPacket::new(ipv6.payload())
+            {
+                observations.extend(observe_udp(src_mac, src_ip, &udp, networks));
+            }
+
+            observations
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn observe_udp(
+    src_mac: MacAddr,
+    src_ip: IpAddr,
+    udp: &UdpPacket,
+    networks: &[IpNetwork],
+) -> Vec<Observation> {
+    let payload: &[u8] = udp.payload();
+    let on_link_ip: Option<IpAddr> = is_on_link(src_ip, networks).then_some(src_ip);
+
+    match (udp.get_source(), udp.get_destination()) {
+        (dhcp::CLIENT_PORT, dhcp::SERVER_PORT) | (dhcp::SERVER_PORT, dhcp::CLIENT_PORT) => {
+            let Ok(message) = dhcp::parse(payload) else {
+                return Vec::new();
+            };
+
+            if !message.is_reply {
+                let ip: Option<IpAddr> = message
+                    .client_ip
+                    .or(message.requested_ip)
+                    .map(IpAddr::V4)
+                    .filter(|ip| is_on_link(*ip, networks));
+                return vec![
+                    Observation::new(message.client_mac, ip, Source::Dhcp)
+                        .with_hostname(message.hostname),
+                ];
+            }
+
+            let server_ip: Option<IpAddr> = message.server_id.map(IpAddr::V4).or(on_link_ip);
+            let mut observations: Vec<Observation> = vec![
+                Observation::new(src_mac, server_ip, Source::Dhcp).with_role(NetworkRole::DHCP),
+            ];
+            if message.message_type == Some(dhcp::MessageType::Ack)
+                && let Some(ip) = message.your_ip
+            {
+                observations.push(Observation::new(
+                    message.client_mac,
+                    Some(IpAddr::V4(ip)),
+                    Source::Dhcp,
+                ));
+            }
+            observations
+        }
+        (MDNS_PORT, _) => {
+            let Ok(record) = mdns::extract_resource(payload) else {
+                return Vec::new();
+            };
+            let mut ips: Vec<IpAddr> = record
+                .ips
+                .into_iter()
+                .filter(|ip| is_on_link(*ip, networks))
+                .collect();
+            ips.extend(on_link_ip);
+            ips.sort();
+            ips.dedup();
+
+            ips.into_iter()
+                .map(|ip| {
+                    Observation::new(src_mac, Some(ip), Source::Mdns)
+                        .with_hostname(record.hostname.clone())
+                })
+                .collect()
+        }
+        (_, ssdp::PORT) | (ssdp::PORT, _) if ssdp::is_message(payload) => {
+            vec![Observation::new(src_mac, on_link_ip, Source::Ssdp)]
+        }
+        (port @ (netbios::NAME_SERVICE_PORT | netbios::DATAGRAM_PORT), _) => {
+            match netbios::get_announced_name(port, payload) {
+                Some(name) => vec![
+                    Observation::new(src_mac, on_link_ip, Source::Netbios)
+                        .with_hostname(Some(name)),
+                ],
+                None => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Returns `true` if `ip` belongs to a device on the link rather than behind a router.
+fn is_on_link(ip: IpAddr, networks: &[IpNetwork]) -> bool {
+    match ip {
+        IpAddr::V4(v4) if v4.is_unspecified() || v4.is_broadcast() || v4.is_multicast() => false,
+        IpAddr::V6(v6) if v6.is_unicast_link_local() => true,
+        IpAddr::V6(v6) if v6.is_unspecified() || v6.is_multicast() => false,
+        _ => networks.iter().any(|net| net.contains(ip)),
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::{
+        ethernet::MutableEthernetPacket, ipv4::MutableIpv4Packet, udp::MutableUdpPacket,
+    };
+    use zond_protocols::arp;
+
+    const HOST_MAC: MacAddr = MacAddr(0x02, 0x11, 0x22, 0x33, 0x44, 0x55);
+    const HOST_IP: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 42);
+
+    fn networks() -> Vec<IpNetwork> {
+        vec!["192.168.1.10/24".parse().unwrap()]
+    }
+
+    fn udp_frame(src_ip: Ipv4Addr, src_port: u16, dst_port: u16, payload: &[u8]) -> Vec<u8> {
+        let udp_len: usize = 8 + payload.len();
+        let mut frame: Vec<u8> = vec![0; 14 + 20 + udp_len];
+        {
+            let mut eth = MutableEthernetPacket::new(&mut frame).unwrap();
+            eth.set_source(HOST_MAC);
+            eth.set_destination(MacAddr::broadcast());
+            eth.set_ethertype(EtherTypes::Ipv4);
+        }
+        {
+            let mut ipv4 = MutableIpv4Packet::new(&mut frame[14..]).unwrap();
+            ipv4.set_version(4);
+            ipv4.set_header_length(5);
+            ipv4.set_total_length((20 + udp_len) as u16);
+            ipv4.set_next_level_protocol(IpNextHeaderProtocols::Udp);
+            ipv4.set_source(src_ip);
+            ipv4.set_destination(Ipv4Addr::BROADCAST);
+        }
+        {
+            let mut udp = MutableUdpPacket::new(&mut frame[34..]).unwrap();
+            udp.set_source(src_port);
+            udp.set_destination(dst_port);
+            udp.set_length(udp_len as u16);
+            udp.set_payload(payload);
+        }
+        frame
+    }
+
+    #[test]
+    fn arp_frames_map_mac_to_ip() {
+        let frame: Vec<u8> = arp::create_packet(
+            HOST_MAC,
+            MacAddr::zero(),
+            HOST_IP,
+            Ipv4Addr::new(192, 168, 1, 1),
+        )
+        .unwrap();
+
+        assert_eq!(
+            observe(&frame, &networks()),
+            vec![Observation::new(
+                HOST_MAC,
+                Some(IpAddr::V4(HOST_IP)),
+                Source::Arp
+            )]
+        );
+    }
+
+    #[test]
+    fn off_link_sources_are_ignored() {
+        let frame: Vec<u8> = udp_frame(Ipv4Addr::new(8, 8, 8, 8), 53, 40000, b"");
+        assert!(observe(&frame, &networks()).is_empty());
+    }
+
+    #[test]
+    fn ssdp_announcements_are_attributed_to_the_sender() {
+        let frame: Vec<u8> = udp_frame(HOST_IP, 1900, 1900, b"NOTIFY * HTTP/1.1\r\n\r\n");
+        let sources: Vec<Source> = observe(&frame, &networks())
+            .into_iter()
+            .map(|observation| observation.source)
+            .collect();
+        assert_eq!(sources, vec![Source::Ip, Source::Ssdp]);
+    }
+
+    #[test]
+    fn pending_names_are_applied_once_an_address_is_known() {
+        let mut inventory: Inventory = Inventory::new(None, ScanState::new());
+
+        let discover: Observation = Observation::new(HOST_MAC, None, Source::Dhcp)
+            .with_hostname(Some("laptop".to_string()));
+        assert!(inventory.record(discover).is_none());
+
+        let arp: Observation = Observation::new(HOST_MAC, Some(IpAddr::V4(HOST_IP)), Source::Arp);
+        let sighting: &Sighting = inventory.record(arp).unwrap();
+        assert_eq!(sighting.host.hostname.as_deref(), Some("laptop"));
+        assert_eq!(
+            sighting.sources.iter().copied().collect::<Vec<Source>>(),
+            vec![Source::Arp]
+        );
+
+        let again: Observation = Observation::new(HOST_MAC, Some(IpAddr::V4(HOST_IP)), Source::Ip);
+        assert!(inventory.record(again).is_none());
+    }
+}
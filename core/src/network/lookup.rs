@@ -0,0 +1,103 @@
+//! **Forward DNS** for hostname targets.
+//!
+//! Names are looked up concurrently through the system resolver configuration
+//! (including the hosts file), keeping every A and AAAA record.
+
+use std::net::IpAddr;
+
+use hickory_resolver::{TokioResolver, config::LookupIpStrategy};
+use tokio::task::JoinSet;
+use zond_common::{
+    info,
+    models::{
+        range::IpCollection,
+        target::{self, NameTable},
+    },
+    warn,
+};
+
+/// Resolves the hostnames among `inputs` and `excludes`, then parses the targets
+/// into one collection without the excluded addresses.
+pub async fn to_collection<S: AsRef<str>, E: AsRef<str>>(
+    inputs: &[S],
+    excludes: &[E],
+) -> anyhow::Result<IpCollection> {
+    let mut names: Vec<String> = target::hostnames(inputs);
+    for name in target::hostnames(excludes) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let table: NameTable = match names.is_empty() {
+        true => NameTable::new(),
+        false => resolve_names(names).await?,
+    };
+
+    target::to_collection_excluding(inputs, excludes, &table)
+}
+
+/// Looks up all names at once. Names that fail to resolve are left out of the table.
+pub async fn resolve_names(names: Vec<String>) -> anyhow::Result<NameTable> {
+    let mut builder = TokioResolver::builder_tokio()?;
+    builder.options_mut().ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+    let resolver: TokioResolver = builder.build();
+
+    let mut lookups: JoinSet<(String, anyhow::Result<Vec<IpAddr>>)> = JoinSet::new();
+    for name in names {
+        let resolver: TokioResolver = resolver.clone();
+        lookups.spawn(async move {
+            let result: anyhow::Result<Vec<IpAddr>> = resolver
+                .lookup_ip(name.as_str())
+                .await
+                .map(|lookup| lookup.iter().collect())
+                .map_err(anyhow::Error::from);
+            (name, result)
+        });
+    }
+
+    let mut table: NameTable = NameTable::new();
+    while let Some(joined) = lookups.join_next().await {
+        let (name, result) = joined?;
+        match result {
+            Ok(mut addrs) => {
+                addrs.sort();
+                addrs.dedup();
+                info!(
+                    verbosity = 1,
+                    "Resolved {name} to {} addresses",
+                    addrs.len()
+                );
+                table.insert(name, addrs);
+            }
+            Err(e) => warn!("Failed to resolve {name}: {e}"),
+        }
+    }
+
+    Ok(table)
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn resolves_names_from_hosts_file() {
+        let collection: IpCollection = to_collection(&["localhost, 10.0.0.1"], &["10.0.0.2"])
+            .await
+            .unwrap();
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert!(collection.contains(&loopback));
+        assert_eq!(collection.name_of(&loopback), Some("localhost"));
+        assert!(collection.contains(&"10.0.0.1".parse().unwrap()));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod channel;
+pub mod lookup;
 pub mod transport;
 pub mod utils;
@@ -19,11 +19,19 @@ const CHANNEL_TYPE_UDP: TransportChannelType =
     TransportChannelType::Layer4(TransportProtocol::Ipv4(IpNextHeaderProtocols::Udp));
 const CHANNEL_TYPE_TCP: TransportChannelType =
     TransportChannelType::Layer4(TransportProtocol::Ipv4(IpNextHeaderProtocols::Tcp));
+const CHANNEL_TYPE_ICMP: TransportChannelType =
+    TransportChannelType::Layer4(TransportProtocol::Ipv4(IpNextHeaderProtocols::Icmp));
+const CHANNEL_TYPE_TCP_L3: TransportChannelType =
+    TransportChannelType::Layer3(IpNextHeaderProtocols::Tcp);
 
 #[derive(Debug, Clone, Copy)]
 pub enum TransportType {
     TcpLayer4,
-    UdpLayer4
+    /// Receives TCP segments together with their IPv4 header (e.g. for the TTL).
+    /// Its sender expects complete IPv4 packets.
+    TcpLayer3,
+    UdpLayer4,
+    IcmpLayer4,
 }
 
 pub struct TransportHandle {
@@ -52,7 +60,9 @@ pub fn start_packet_capture(transport_type: TransportType) -> anyhow::Result<Tra
 
     match transport_type {
         TransportType::TcpLayer4 => spawn_listener!(queue_tx, rx_socket, pnet::transport::tcp_packet_iter),
+        TransportType::TcpLayer3 => spawn_listener!(queue_tx, rx_socket, pnet::transport::ipv4_packet_iter),
         TransportType::UdpLayer4 => spawn_listener!(queue_tx, rx_socket, pnet::transport::udp_packet_iter),
+        TransportType::IcmpLayer4 => spawn_listener!(queue_tx, rx_socket, pnet::transport::icmp_packet_iter),
     };
 
     Ok(TransportHandle {
@@ -61,10 +71,18 @@ pub fn start_packet_capture(transport_type: TransportType) -> anyhow::Result<Tra
     })
 }
 
+/// Opens a channel for sending only; nothing is read from its socket.
+pub fn open_sender(transport_type: TransportType) -> anyhow::Result<std::sync::Arc<std::sync::Mutex<TransportSender>>> {
+    let (tx, _) = open_channel(transport_type)?;
+    Ok(std::sync::Arc::new(std::sync::Mutex::new(tx)))
+}
+
 fn open_channel(transport_type: TransportType) -> anyhow::Result<(TransportSender, TransportReceiver)> {
     let channel_type: TransportChannelType = match transport_type {
         TransportType::TcpLayer4 => CHANNEL_TYPE_TCP,
+        TransportType::TcpLayer3 => CHANNEL_TYPE_TCP_L3,
         TransportType::UdpLayer4 => CHANNEL_TYPE_UDP,
+        TransportType::IcmpLayer4 => CHANNEL_TYPE_ICMP,
     };
     let (tx, rx) = transport::transport_channel(TRANSPORT_BUFFER_SIZE, channel_type)?;
     Ok((tx, rx))
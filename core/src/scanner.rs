@@ -1,48 +1,103 @@
-//! Orchestration logic for network discovery.
+//! Orchestration logic for network discovery and port scanning.
 //!
 //! This module coordinates the execution of various scanning strategies:
-//! - **Privileged**: High-speed raw socket scans ([`LocalScanner`] for ARP/ICMP, [`RoutedScanner`] for TCP SYN).
-//! - **Unprivileged**: Standard TCP handshake fallback via [`handshake`].
+//! - **Privileged**: High-speed raw socket scans ([`LocalScanner`] for ARP/NDP, [`RoutedScanner`] for
+//!   TCP SYN/ACK, UDP and ICMP probes), as selected by the [`ProbePlan`].
+//! - **Unprivileged**: Concurrent TCP connect() scans via [`ConnectScanner`].
+//! - **Port Scanning**: Half-open TCP SYN scans via [`SynScanner`] (or [`ConnectScanner`] without root)
+//!   and payload-based UDP scans via [`UdpScanner`].
+//! - **Service Detection**: Banner grabbing and probe matching on open ports via [`ServiceDetector`].
 //!
 //! It manages the lifecycle of a scan by partitioning targets by interface,
 //! spawning concurrent explorers, and piping results through a background
-//! [`HostnameResolver`].use std::net::IpAddr;
+//! [`HostnameResolver`]. Callers may pass an [`EventTx`] to follow the scan as
+//! [`ScanEvent`]s while it runs, and share a [`ScanState`] to stop it or count its hosts.
 
+use std::collections::{HashMap, hash_map::Entry};
 use std::net::IpAddr;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use anyhow::ensure;
 use async_trait::async_trait;
 use is_root::is_root;
 use zond_common::config::Config;
 use zond_common::interface;
+use zond_common::models::event::ScanEvent;
 use zond_common::models::host::Host;
+use zond_common::models::port::Protocol;
+use zond_common::models::probe::{DEFAULT_SYN_PORT, DiscoveryProbe};
 use zond_common::models::range::IpCollection;
+use zond_common::roles::KnownRoles;
 use zond_common::utils::input::InputHandle;
 use zond_common::{error, info, success, warn};
 
 mod handshake;
 mod local;
+mod port_results;
+mod probe;
 mod resolver;
 mod routed;
+pub mod service;
+mod syn;
+mod udp;
 
+use handshake::ConnectScanner;
 use local::LocalScanner;
+use probe::ProbePlan;
 use routed::RoutedScanner;
+use service::{ServiceDetector, SignatureDb};
+use syn::SynScanner;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::task::JoinHandle;
+use udp::UdpScanner;
 
 use crate::scanner::resolver::HostnameResolver;
 
-pub static FOUND_HOST_COUNT: AtomicUsize = AtomicUsize::new(0);
-pub static STOP_SIGNAL: AtomicBool = AtomicBool::new(false);
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
 
-pub fn increment_host_count() {
-    FOUND_HOST_COUNT.fetch_add(1, Ordering::Relaxed);
+/// Receives the [`ScanEvent`]s of a running scan.
+pub type EventTx = mpsc::UnboundedSender<ScanEvent>;
+
+/// The stop flag and host counter of one run, shared by its scanners and the caller.
+///
+/// Every run gets its own state, so a stopped or finished run does not affect the next.
+#[derive(Debug, Default)]
+pub struct ScanState {
+    stop: AtomicBool,
+    found_hosts: AtomicUsize,
+}
+
+impl ScanState {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Asks every scanner of the run to finish with the hosts found so far.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn add_host(&self) {
+        self.found_hosts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The hosts found so far.
+    pub fn host_count(&self) -> usize {
+        self.found_hosts.load(Ordering::Relaxed)
+    }
 }
 
-pub fn get_host_count() -> usize {
-    FOUND_HOST_COUNT.load(Ordering::Relaxed)
+fn emit(events: &Option<EventTx>, event: ScanEvent) {
+    if let Some(tx) = events {
+        let _ = tx.send(event);
+    }
 }
 
 #[async_trait]
@@ -53,34 +108,72 @@ trait NetworkExplorer {
 /// The primary entry point for network discovery.
 ///
 /// ### Capabilities
-/// - **Privilege Aware**: Uses raw sockets (ARP/TCP SYN) if root; falls back to standard TCP handshakes if not.
+/// - **Privilege Aware**: Uses raw sockets (ARP/TCP SYN) if root; falls back to concurrent TCP connects if not.
+/// - **Configurable Probes**: [`Config::probes`] selects SYN/ACK/UDP/ICMP/ARP/NDP probes; any answer marks a host up.
 /// - **Multi-Interface**: Automatically partitions targets across available network adapters.
 /// - **Parallel Resolver**: Streams found IPs to a background DNS task for zero-latency lookups.
+/// - **Live Events**: Sends hosts, names and progress to `events` as they are found, ending with
+///   [`ScanEvent::Finished`].
 ///
 /// ### Integration Notes
-/// - **State**: Updates [`FOUND_HOST_COUNT`] and reacts to [`STOP_SIGNAL`].
+/// - **State**: Counts hosts in `state` and stops once [`ScanState::stop`] was called.
 /// - **Concurrency**: Spawns multiple Tokio tasks; ensure the caller is within a multi-threaded runtime.
-pub async fn perform_discovery(targets: IpCollection, cfg: &Config) -> anyhow::Result<Vec<Host>> {
+pub async fn perform_discovery(
+    targets: IpCollection,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
+) -> anyhow::Result<Vec<Host>> {
+    let started: Instant = Instant::now();
+    let progress: Option<JoinHandle<()>> = spawn_progress_reporter(&events, &state, started);
+    let result: anyhow::Result<Vec<Host>> =
+        run_discovery(targets, cfg, state, events.clone()).await;
+    finish_events(progress, &events, &result, started);
+    result
+}
+
+async fn run_discovery(
+    targets: IpCollection,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
+) -> anyhow::Result<Vec<Host>> {
     if !cfg.disable_input {
-        spawn_user_input_listener();
+        spawn_user_input_listener(state.clone());
     }
 
+    let plan: ProbePlan = ProbePlan::from_config(cfg);
+    let max_in_flight: usize = max_in_flight(cfg);
+
     if !is_root() {
         warn!("Root privileges missing, defaulting to unprivileged TCP scan");
-        return handshake::range_discovery(targets, handshake::prober).await;
+        if cfg
+            .probes
+            .iter()
+            .any(|probe| !matches!(probe, DiscoveryProbe::TcpSyn(_)))
+        {
+            warn!("Ignoring discovery probes other than TCP SYN, they require root privileges");
+        }
+        let names: HashMap<IpAddr, String> = targets.names.clone();
+        let mut scanner = ConnectScanner::for_discovery(
+            targets,
+            connect_ports(&plan),
+            max_in_flight,
+            None,
+            state,
+        )
+        .with_events(events);
+        let mut hosts: Vec<Host> = scanner.discover_hosts().await?;
+        apply_target_names(&mut hosts, &names);
+        KnownRoles::detect().tag(&mut hosts);
+        return Ok(hosts);
     }
     success!("Root privileges detected, raw socket scan enabled");
 
-    let (dns_tx, resolver_task) = if !cfg.no_dns {
-        let (tx, rx) = mpsc::unbounded_channel();
-        let task = spawn_resolver(rx).await;
-        (Some(tx), Some(task))
-    } else {
-        info!("DNS resolution skipped by user flag");
-        (None, None)
-    };
-
-    let scanner_handles = spawn_explorers(targets, dns_tx).await;
+    let names: HashMap<IpAddr, String> = targets.names.clone();
+    let (dns_tx, resolver_task) = start_resolver(cfg, &events).await;
+    let scanner_handles =
+        spawn_explorers(targets, plan, max_in_flight, dns_tx, state, events).await;
 
     let mut hosts = Vec::new();
     for handle in scanner_handles {
@@ -91,59 +184,256 @@ pub async fn perform_discovery(targets: IpCollection, cfg: &Config) -> anyhow::R
         }
     }
 
-    if let Some(task) = resolver_task
-        && let Ok(Some(mut resolver)) = task.await
-    {
-        resolver.resolve_hosts(&mut hosts);
+    apply_target_names(&mut hosts, &names);
+    KnownRoles::detect().tag(&mut hosts);
+    finish_resolver(resolver_task, &mut hosts).await;
+
+    Ok(hosts)
+}
+
+/// The primary entry point for port scanning.
+///
+/// ### Capabilities
+/// - **Half-Open**: Sends raw TCP SYNs and never completes a handshake.
+/// - **UDP**: Sends protocol-specific payloads and listens for replies and ICMP errors.
+/// - **Classification**: Open (SYN/ACK), closed (RST) and filtered (silence or ICMP unreachable).
+/// - **Parallel Resolver**: Hosts that answer are streamed to the background DNS task.
+/// - **Service Detection**: Fingerprints open TCP ports afterwards if [`Config::service_detection`] is set.
+/// - **Live Events**: Sends hosts, port states, names and progress to `events` as they are found,
+///   ending with [`ScanEvent::Finished`].
+///
+/// ### Integration Notes
+/// - **Ports**: Probes the TCP and UDP ports of [`Config::ports`] in parallel.
+/// - **Privilege Aware**: Falls back to concurrent TCP connects without root.
+/// - **State**: Counts hosts in `state` and stops once [`ScanState::stop`] was called.
+pub async fn perform_port_scan(
+    targets: IpCollection,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
+) -> anyhow::Result<Vec<Host>> {
+    let started: Instant = Instant::now();
+    let progress: Option<JoinHandle<()>> = spawn_progress_reporter(&events, &state, started);
+    let result: anyhow::Result<Vec<Host>> =
+        run_port_scan(targets, cfg, state, events.clone()).await;
+    finish_events(progress, &events, &result, started);
+    result
+}
+
+async fn run_port_scan(
+    targets: IpCollection,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
+) -> anyhow::Result<Vec<Host>> {
+    ensure!(
+        !cfg.ports.tcp.is_empty() || !cfg.ports.udp.is_empty(),
+        "No TCP or UDP ports to scan"
+    );
+    if !cfg.ports.sctp.is_empty() {
+        warn!(
+            "Ignoring {} SCTP ports, SCTP scanning is not supported",
+            cfg.ports.sctp.len()
+        );
+    }
+
+    if !cfg.disable_input {
+        spawn_user_input_listener(state.clone());
+    }
+
+    let tcp_ports: Vec<u16> = cfg.ports.tcp.iter().copied().collect();
+    let udp_ports: Vec<u16> = cfg.ports.udp.iter().copied().collect();
+    let names: HashMap<IpAddr, String> = targets.names.clone();
+
+    let mut hosts: Vec<Host> = if is_root() {
+        success!("Root privileges detected, raw socket scan enabled");
+        raw_port_scan(targets, tcp_ports, udp_ports, cfg, state.clone(), events).await?
+    } else {
+        warn!("Root privileges missing, defaulting to unprivileged TCP connect scan");
+        if !udp_ports.is_empty() {
+            warn!(
+                "Skipping {} UDP ports, UDP scanning requires root privileges",
+                udp_ports.len()
+            );
+        }
+        let mut scanner = ConnectScanner::for_port_scan(
+            targets,
+            tcp_ports,
+            max_in_flight(cfg),
+            None,
+            state.clone(),
+        )
+        .with_events(events);
+        scanner.discover_hosts().await?
+    };
+
+    apply_target_names(&mut hosts, &names);
+    KnownRoles::detect().tag(&mut hosts);
+
+    if cfg.service_detection && !state.is_stopped() {
+        detect_services(&mut hosts, cfg, state).await?;
     }
 
     Ok(hosts)
 }
 
+/// Runs the SYN and UDP scanners in parallel and merges their hosts.
+async fn raw_port_scan(
+    targets: IpCollection,
+    tcp_ports: Vec<u16>,
+    udp_ports: Vec<u16>,
+    cfg: &Config,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
+) -> anyhow::Result<Vec<Host>> {
+    let (dns_tx, resolver_task) = start_resolver(cfg, &events).await;
+    let mut handles: Vec<JoinHandle<anyhow::Result<Vec<Host>>>> = Vec::new();
+
+    if !tcp_ports.is_empty() {
+        let (targets_c, tx, events_c) = (targets.clone(), dns_tx.clone(), events.clone());
+        let state_c: Arc<ScanState> = state.clone();
+        handles.push(tokio::spawn(async move {
+            let mut scanner =
+                SynScanner::new(targets_c, tcp_ports, tx, state_c)?.with_events(events_c);
+            scanner.discover_hosts().await
+        }));
+    }
+
+    if !udp_ports.is_empty() {
+        let tx = dns_tx.clone();
+        handles.push(tokio::spawn(async move {
+            let mut scanner = UdpScanner::new(targets, udp_ports, tx, state)?.with_events(events);
+            scanner.discover_hosts().await
+        }));
+    }
+    drop(dns_tx);
+
+    let mut hosts_map: HashMap<IpAddr, Host> = HashMap::new();
+    for handle in handles {
+        for host in handle.await?? {
+            match hosts_map.entry(host.primary_ip) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(host),
+                Entry::Vacant(entry) => _ = entry.insert(host),
+            }
+        }
+    }
+
+    let mut hosts: Vec<Host> = hosts_map.into_values().collect();
+    finish_resolver(resolver_task, &mut hosts).await;
+
+    Ok(hosts)
+}
+
+/// Identifies the services behind open TCP ports, using the user's signatures if given.
+async fn detect_services(
+    hosts: &mut [Host],
+    cfg: &Config,
+    state: Arc<ScanState>,
+) -> anyhow::Result<()> {
+    let db: SignatureDb = match &cfg.signatures {
+        Some(path) => SignatureDb::with_user_file(path)?,
+        None => SignatureDb::embedded(),
+    };
+
+    let open_ports: usize = hosts
+        .iter()
+        .flat_map(|host| host.open_ports())
+        .filter(|port| port.protocol == Protocol::Tcp)
+        .count();
+    info!("Detecting services on {open_ports} open TCP ports");
+
+    let max_in_flight: usize = cfg
+        .max_parallelism
+        .unwrap_or(service::DEFAULT_MAX_IN_FLIGHT);
+    ServiceDetector::new(db, max_in_flight, state)?
+        .detect(hosts)
+        .await;
+    Ok(())
+}
+
+/// Connect() scans can only send SYNs, so they fall back to [`DEFAULT_SYN_PORT`].
+fn connect_ports(plan: &ProbePlan) -> Vec<u16> {
+    match plan.syn_ports.is_empty() {
+        true => vec![DEFAULT_SYN_PORT],
+        false => plan.syn_ports.clone(),
+    }
+}
+
+/// In-flight limit for connect() scans, falling back to [`handshake::DEFAULT_MAX_IN_FLIGHT`].
+fn max_in_flight(cfg: &Config) -> usize {
+    cfg.max_parallelism
+        .unwrap_or(handshake::DEFAULT_MAX_IN_FLIGHT)
+}
+
 async fn spawn_explorers(
     targets: IpCollection,
+    plan: ProbePlan,
+    max_in_flight: usize,
     dns_tx: Option<mpsc::UnboundedSender<IpAddr>>,
+    state: Arc<ScanState>,
+    events: Option<EventTx>,
 ) -> Vec<JoinHandle<anyhow::Result<Vec<Host>>>> {
     let mut handles = Vec::new();
 
     let (interface_map, unmapped_ips) = interface::map_ips_to_interfaces(targets);
 
-    for (intf, (local_ips, routed_ips)) in interface_map {
-        // Local Scanner (ARP/ICMP)
-        if !local_ips.is_empty() {
+    for (intf, (local_ips, mut routed_ips)) in interface_map {
+        // Without ARP, on-link IPv4 hosts can only be reached by the IP probes
+        if !plan.arp && plan.has_ip_probes() {
+            routed_ips.extend(local_ips.clone());
+        }
+
+        // Local Scanner (ARP/NDP)
+        if !local_ips.is_empty() && plan.has_link_probes() {
             info!(verbosity = 1, "Spawning LOCAL scanner for {}", intf.name);
             let tx = dns_tx.clone();
+            let events_c = events.clone();
+            let state_c = state.clone();
             let intf_c = intf.clone();
+            let plan_c = plan.clone();
 
             let handle = tokio::spawn(async move {
-                let mut scanner = LocalScanner::new(intf_c, local_ips, tx)?;
+                let mut scanner = LocalScanner::new(intf_c, local_ips, &plan_c, tx, state_c)?
+                    .with_events(events_c);
                 scanner.discover_hosts().await
             });
             handles.push(handle);
         }
 
-        // Routed Scanner (TCP Syn Scan)
-        if !routed_ips.is_empty() {
+        // Routed Scanner (TCP SYN/ACK, UDP, ICMP)
+        if !routed_ips.is_empty() && plan.has_ip_probes() {
             info!(verbosity = 1, "Spawning ROUTED scanner for {}", intf.name);
             let tx = dns_tx.clone();
+            let events_c = events.clone();
+            let state_c = state.clone();
             let intf_c = intf.clone();
+            let plan_c = plan.clone();
 
             let handle = tokio::spawn(async move {
-                let mut scanner = RoutedScanner::new(intf_c, routed_ips, tx)?;
+                let mut scanner = RoutedScanner::new(intf_c, routed_ips, plan_c, tx, state_c)?
+                    .with_events(events_c);
                 scanner.discover_hosts().await
             });
             handles.push(handle);
         }
     }
 
-    // Fallback Scanner (Unprivileged TCP Handshake) for unmapped IPs (e.g. localhost)
+    // Fallback Scanner (Unprivileged TCP Connect) for unmapped IPs (e.g. localhost)
     if !unmapped_ips.is_empty() {
         info!(
             verbosity = 1,
             "Spawning FALLBACK scanner for unmapped targets"
         );
         let handle = tokio::spawn(async move {
-            handshake::range_discovery(unmapped_ips, handshake::prober).await
+            let mut scanner = ConnectScanner::for_discovery(
+                unmapped_ips,
+                connect_ports(&plan),
+                max_in_flight,
+                None,
+                state,
+            )
+            .with_events(events);
+            scanner.discover_hosts().await
         });
         handles.push(handle);
     }
@@ -151,10 +441,52 @@ async fn spawn_explorers(
     handles
 }
 
-async fn spawn_resolver(dns_rx: UnboundedReceiver<IpAddr>) -> JoinHandle<Option<HostnameResolver>> {
+async fn start_resolver(
+    cfg: &Config,
+    events: &Option<EventTx>,
+) -> (
+    Option<mpsc::UnboundedSender<IpAddr>>,
+    Option<JoinHandle<Option<HostnameResolver>>>,
+) {
+    if cfg.no_dns {
+        info!("DNS resolution skipped by user flag");
+        return (None, None);
+    }
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    let task = spawn_resolver(rx, events.clone()).await;
+    (Some(tx), Some(task))
+}
+
+/// Names hosts after the hostname target they were resolved from.
+/// The name the user gave wins over one found by reverse DNS.
+fn apply_target_names(hosts: &mut [Host], names: &HashMap<IpAddr, String>) {
+    for host in hosts.iter_mut() {
+        if let Some(name) = host.ips.iter().find_map(|ip| names.get(ip)) {
+            host.hostname = Some(name.clone());
+        }
+    }
+}
+
+async fn finish_resolver(
+    resolver_task: Option<JoinHandle<Option<HostnameResolver>>>,
+    hosts: &mut Vec<Host>,
+) {
+    if let Some(task) = resolver_task
+        && let Ok(Some(mut resolver)) = task.await
+    {
+        resolver.resolve_hosts(hosts);
+    }
+}
+
+async fn spawn_resolver(
+    dns_rx: UnboundedReceiver<IpAddr>,
+    events: Option<EventTx>,
+) -> JoinHandle<Option<HostnameResolver>> {
     tokio::spawn(async move {
         match HostnameResolver::new(dns_rx) {
             Ok(resolver) => {
+                let resolver: HostnameResolver = resolver.with_events(events);
                 success!("Successfully initialized hostname resolver");
                 Some(resolver.run().await)
             }
@@ -166,13 +498,58 @@ async fn spawn_resolver(dns_rx: UnboundedReceiver<IpAddr>) -> JoinHandle<Option<
     })
 }
 
-fn spawn_user_input_listener() {
-    std::thread::spawn(|| {
+/// Reports the number of hosts found so far every [`PROGRESS_INTERVAL`].
+fn spawn_progress_reporter(
+    events: &Option<EventTx>,
+    state: &Arc<ScanState>,
+    started: Instant,
+) -> Option<JoinHandle<()>> {
+    let events: EventTx = events.clone()?;
+    let state: Arc<ScanState> = state.clone();
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let progress: ScanEvent = ScanEvent::Progress {
+                hosts_up: state.host_count(),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            };
+            if events.send(progress).is_err() {
+                break;
+            }
+        }
+    }))
+}
+
+/// Stops the progress reports and announces the end of a successful scan.
+fn finish_events(
+    progress: Option<JoinHandle<()>>,
+    events: &Option<EventTx>,
+    result: &anyhow::Result<Vec<Host>>,
+    started: Instant,
+) {
+    if let Some(progress) = progress {
+        progress.abort();
+    }
+    if let Ok(hosts) = result {
+        let finished: ScanEvent = ScanEvent::Finished {
+            hosts_up: hosts.len(),
+            open_ports: hosts.iter().map(|host| host.open_ports().count()).sum(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        };
+        emit(events, finished);
+    }
+}
+
+/// Stops `state` once the user presses 'q' or Ctrl-C.
+pub fn spawn_user_input_listener(state: Arc<ScanState>) {
+    std::thread::spawn(move || {
         let mut input_handle = InputHandle::new();
         input_handle.start();
         loop {
             if input_handle.should_interrupt() {
-                STOP_SIGNAL.store(true, Ordering::Relaxed);
+                state.stop();
                 break;
             }
             std::thread::sleep(Duration::from_millis(50));
@@ -1,48 +1,232 @@
-use std::future::Future;
-use std::net::{IpAddr, SocketAddr};
-use std::sync::atomic::Ordering;
-use std::time::{Duration, Instant};
-use tokio::net::TcpStream;
-use tokio::time::timeout;
-use zond_common::models::host::Host;
-use zond_common::models::range::IpCollection;
+//! An unprivileged **TCP connect()** scanner.
+//!
+//! Runs full `connect()` handshakes through the operating system, so no raw sockets
+//! (and therefore no root privileges) are needed. Many attempts run concurrently,
+//! bounded by a configurable in-flight limit.
+//!
+//! Every attempt ends in one of three ways:
+//! * **Connected**: the port is open.
+//! * **Refused**: the host is up, but the port is closed.
+//! * **Timed out** (or otherwise failed): the port is filtered, or the host is down.
 
-use super::STOP_SIGNAL;
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use crate::scanner::increment_host_count;
+use async_trait::async_trait;
+use tokio::{net::TcpStream, sync::mpsc::UnboundedSender, task::JoinSet, time::timeout};
+use zond_common::{
+    error, info,
+    models::{
+        event::ScanEvent,
+        host::Host,
+        port::{Port, PortReason, PortState, Protocol},
+        range::IpCollection,
+    },
+    success,
+};
 
-pub async fn range_discovery<F, Fut>(
+use super::{
+    EventTx, NetworkExplorer, ScanState,
+    port_results::{self, Probe, ProbeIter},
+};
+
+/// In-flight limit used when the user did not configure one.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 256;
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(750);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+enum Outcome {
+    Connected,
+    Refused,
+    TimedOut,
+    Failed(std::io::Error),
+}
+
+struct ProbeResult {
+    probe: Probe,
+    outcome: Outcome,
+    rtt: Duration,
+}
+
+pub struct ConnectScanner {
     targets: IpCollection,
-    mut prober: F,
-) -> anyhow::Result<Vec<Host>>
-where
-    F: FnMut(IpAddr) -> Fut,
-    Fut: Future<Output = anyhow::Result<Option<Host>>>,
-{
-    let mut result: Vec<Host> = Vec::new();
-    for target in targets {
-        if STOP_SIGNAL.load(Ordering::Relaxed) {
-            break;
+    ports: Vec<u16>,
+    max_in_flight: usize,
+    record_ports: bool,
+    dns_tx: Option<UnboundedSender<IpAddr>>,
+    events: Option<EventTx>,
+    state: Arc<ScanState>,
+    hosts: HashMap<IpAddr, Host>,
+    silent: Vec<Probe>,
+}
+
+#[async_trait]
+impl NetworkExplorer for ConnectScanner {
+    async fn discover_hosts(&mut self) -> anyhow::Result<Vec<Host>> {
+        let mut probe_iter: ProbeIter =
+            port_results::interleave(self.targets.iter().collect(), self.ports.clone());
+        let mut in_flight: JoinSet<ProbeResult> = JoinSet::new();
+
+        loop {
+            if self.state.is_stopped() {
+                in_flight.shutdown().await;
+                break;
+            }
+
+            while in_flight.len() < self.max_in_flight {
+                let Some((ip, port)) = probe_iter.next() else {
+                    break;
+                };
+
+                // Discovery only needs one answer per host
+                if !self.record_ports && self.hosts.contains_key(&ip) {
+                    continue;
+                }
+                in_flight.spawn(probe((ip, port)));
+            }
+
+            if in_flight.is_empty() {
+                break;
+            }
+
+            tokio::select! {
+                Some(res) = in_flight.join_next() => match res {
+                    Ok(result) => self.process_result(result),
+                    Err(e) => error!(verbosity = 2, "Connect probe failed: {e}"),
+                },
+                _ = tokio::time::sleep(STOP_POLL_INTERVAL) => {}
+            }
         }
-        if let Some(found) = prober(target).await? {
-            result.push(found);
+
+        if self.record_ports {
+            for (ip, number) in std::mem::take(&mut self.silent) {
+                if let Some(host) = self.hosts.get_mut(&ip) {
+                    let port: Port = Port::new(
+                        number,
+                        Protocol::Tcp,
+                        PortState::Filtered,
+                        PortReason::Timeout,
+                    );
+                    super::emit(&self.events, ScanEvent::port_state(ip, &port));
+                    host.add_port(port);
+                }
+            }
         }
+
+        Ok(self.hosts.drain().map(|(_, host)| host).collect())
     }
-    Ok(result)
 }
 
-pub async fn prober(ip: IpAddr) -> anyhow::Result<Option<Host>> {
-    let socket_addr: SocketAddr = SocketAddr::new(ip, 443);
-    let probe_timeout: Duration = Duration::from_millis(100);
+impl ConnectScanner {
+    /// Creates a scanner that stops probing a host as soon as it answered once.
+    pub fn for_discovery(
+        targets: IpCollection,
+        ports: Vec<u16>,
+        max_in_flight: usize,
+        dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
+    ) -> Self {
+        Self::new(targets, ports, max_in_flight, false, dns_tx, state)
+    }
 
-    let start: Instant = Instant::now();
-    match timeout(probe_timeout, TcpStream::connect(socket_addr)).await {
-        Ok(Ok(_)) | Ok(Err(_)) => {
-            increment_host_count();
-            let host: Host = Host::new(ip).with_rtt(start.elapsed());
-            Ok(Some(host))
+    /// Creates a scanner that probes every port and records each result on the host.
+    pub fn for_port_scan(
+        targets: IpCollection,
+        ports: Vec<u16>,
+        max_in_flight: usize,
+        dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
+    ) -> Self {
+        Self::new(targets, ports, max_in_flight, true, dns_tx, state)
+    }
+
+    fn new(
+        targets: IpCollection,
+        ports: Vec<u16>,
+        max_in_flight: usize,
+        record_ports: bool,
+        dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
+    ) -> Self {
+        Self {
+            targets,
+            ports,
+            max_in_flight: max_in_flight.max(1),
+            record_ports,
+            dns_tx,
+            events: None,
+            state,
+            hosts: HashMap::new(),
+            silent: Vec::new(),
+        }
+    }
+
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.events = events;
+        self
+    }
+
+    fn process_result(&mut self, result: ProbeResult) {
+        let (ip, number) = result.probe;
+
+        let (state, reason) = match result.outcome {
+            Outcome::Connected => (PortState::Open, PortReason::SynAck),
+            Outcome::Refused => (PortState::Closed, PortReason::ConnRefused),
+            Outcome::TimedOut => {
+                self.silent.push(result.probe);
+                return;
+            }
+            Outcome::Failed(e) => {
+                info!(verbosity = 2, "Connecting to {ip}:{number} failed: {e}");
+                self.silent.push(result.probe);
+                return;
+            }
+        };
+
+        let host: &mut Host = self.hosts.entry(ip).or_insert_with(|| {
+            self.state.add_host();
+            if let Some(dns_tx) = &self.dns_tx {
+                let _ = dns_tx.send(ip);
+            }
+            super::emit(&self.events, ScanEvent::host_up(ip, None, Some(result.rtt)));
+            Host::new(ip)
+        });
+
+        host.add_rtt(result.rtt);
+
+        if !self.record_ports {
+            return;
+        }
+
+        if state == PortState::Open {
+            success!(verbosity = 1, "Discovered open port {number}/tcp on {ip}");
         }
-        Err(_elapsed) => Ok(None),
+        let port: Port = Port::new(number, Protocol::Tcp, state, reason);
+        super::emit(&self.events, ScanEvent::port_state(ip, &port));
+        host.add_port(port);
+    }
+}
+
+async fn probe((ip, port): Probe) -> ProbeResult {
+    let socket_addr: SocketAddr = SocketAddr::new(ip, port);
+
+    let start: Instant = Instant::now();
+    let outcome: Outcome = match timeout(CONNECT_TIMEOUT, TcpStream::connect(socket_addr)).await {
+        Ok(Ok(_)) => Outcome::Connected,
+        Ok(Err(e)) if e.kind() == ErrorKind::ConnectionRefused => Outcome::Refused,
+        Ok(Err(e)) => Outcome::Failed(e),
+        Err(_elapsed) => Outcome::TimedOut,
+    };
+
+    ProbeResult {
+        probe: (ip, port),
+        outcome,
+        rtt: start.elapsed(),
     }
 }
 
@@ -59,21 +243,118 @@ pub async fn prober(ip: IpAddr) -> anyhow::Result<Option<Host>> {
 mod tests {
     use super::*;
     use std::net::{IpAddr, Ipv4Addr};
+    use tokio::net::TcpListener;
     use zond_common::models::host::Host;
 
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn single_target(ip: IpAddr) -> IpCollection {
+        let mut targets: IpCollection = IpCollection::new();
+        targets.add_single(ip);
+        targets
+    }
+
+    /// Returns a port that was free a moment ago, and therefore refuses connections.
+    async fn closed_port() -> u16 {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn port_scan_should_tell_open_from_closed() {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let open: u16 = listener.local_addr().unwrap().port();
+        let closed: u16 = closed_port().await;
+
+        let mut scanner = ConnectScanner::for_port_scan(
+            single_target(LOCALHOST),
+            vec![open, closed],
+            8,
+            None,
+            ScanState::new(),
+        );
+        let hosts: Vec<Host> = scanner.discover_hosts().await.unwrap();
+
+        assert_eq!(hosts.len(), 1);
+        let host: &Host = &hosts[0];
+        assert_eq!(
+            host.get_port(open, Protocol::Tcp).unwrap().state,
+            PortState::Open
+        );
+
+        let closed_port: &Port = host.get_port(closed, Protocol::Tcp).unwrap();
+        assert_eq!(closed_port.state, PortState::Closed);
+        assert_eq!(closed_port.reason, PortReason::ConnRefused);
+    }
+
+    #[tokio::test]
+    async fn port_scan_should_emit_host_then_ports() {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let open: u16 = listener.local_addr().unwrap().port();
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut scanner = ConnectScanner::for_port_scan(
+            single_target(LOCALHOST),
+            vec![open],
+            8,
+            None,
+            ScanState::new(),
+        )
+        .with_events(Some(tx));
+        scanner.discover_hosts().await.unwrap();
+        drop(scanner);
+
+        let mut events: Vec<ScanEvent> = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+
+        assert!(matches!(events[0], ScanEvent::HostUp { ip: LOCALHOST, .. }));
+        assert!(matches!(
+            events[1],
+            ScanEvent::PortState { port, state: PortState::Open, .. } if port == open
+        ));
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn discovery_should_count_refused_as_up() {
+        let closed: u16 = closed_port().await;
+
+        let mut scanner = ConnectScanner::for_discovery(
+            single_target(LOCALHOST),
+            vec![closed],
+            8,
+            None,
+            ScanState::new(),
+        );
+        let hosts: Vec<Host> = scanner.discover_hosts().await.unwrap();
+
+        assert_eq!(hosts.len(), 1);
+        assert!(
+            hosts[0].ports.is_empty(),
+            "Discovery should not record ports"
+        );
+        assert!(hosts[0].min_rtt().is_some());
+    }
+
     #[tokio::test]
     #[ignore]
-    async fn handshake_probe_should_find_known_open_port() {
+    async fn discovery_should_find_known_open_port() {
         let ip: IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
-        let result: Option<Host> = prober(ip).await.unwrap();
-        assert!(result.is_some());
+        let mut scanner =
+            ConnectScanner::for_discovery(single_target(ip), vec![443], 8, None, ScanState::new());
+        let hosts: Vec<Host> = scanner.discover_hosts().await.unwrap();
+        assert_eq!(hosts.len(), 1);
     }
 
     #[tokio::test]
     #[ignore]
-    async fn handshake_probe_should_timeout_on_unreachable_ip() {
+    async fn discovery_should_timeout_on_unreachable_ip() {
         let ip: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1));
-        let result: Option<Host> = prober(ip).await.unwrap();
-        assert!(result.is_none());
+        let mut scanner =
+            ConnectScanner::for_discovery(single_target(ip), vec![443], 8, None, ScanState::new());
+        let hosts: Vec<Host> = scanner.discover_hosts().await.unwrap();
+        assert!(hosts.is_empty());
     }
 }
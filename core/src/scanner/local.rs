@@ -9,7 +9,7 @@
 use std::{
     collections::{HashMap, HashSet},
     net::{IpAddr, Ipv6Addr},
-    sync::atomic::Ordering,
+    sync::{Arc, atomic::Ordering},
     time::{Duration, Instant},
 };
 
@@ -25,8 +25,13 @@ use pnet::{
 };
 
 use zond_common::{
-    error,
-    models::{host::Host, range::IpCollection, target::IS_LAN_SCAN},
+    error, info,
+    models::{
+        event::ScanEvent,
+        host::{Host, NetworkRole},
+        range::IpCollection,
+        target::IS_LAN_SCAN,
+    },
     sender::{PacketType, SenderConfig},
     success,
     utils::timing::ScanTimer,
@@ -37,11 +42,14 @@ use tokio::{
     sync::mpsc::UnboundedSender,
     time::{Interval, Sleep},
 };
-use zond_protocols::{self as protocol, ip};
+use zond_protocols::{
+    self as protocol, ip,
+    ndp::{self, NdpMessage},
+};
 
 use crate::network::channel::{self, EthernetHandle};
 
-use super::NetworkExplorer;
+use super::{EventTx, NetworkExplorer, ScanState, probe::ProbePlan};
 use async_trait::async_trait;
 
 const MAX_CHANNEL_TIME: Duration = Duration::from_millis(7_500);
@@ -55,7 +63,13 @@ pub struct LocalScanner {
     eth_handle: EthernetHandle,
     timer: ScanTimer,
     dns_tx: Option<UnboundedSender<IpAddr>>,
+    events: Option<EventTx>,
+    state: Arc<ScanState>,
     rtt_map: HashMap<IpAddr, Instant>,
+    /// Accept IPv6 hosts that are not targets (only when sweeping the whole link).
+    accept_ipv6: bool,
+    /// DNS servers announced in router advertisements.
+    dns_servers: HashSet<IpAddr>,
 }
 
 #[async_trait]
@@ -70,9 +84,7 @@ impl NetworkExplorer for LocalScanner {
         tokio::pin!(scan_deadline);
 
         loop {
-            if (!self.should_continue() && sending_finished)
-                || super::STOP_SIGNAL.load(Ordering::Relaxed)
-            {
+            if (!self.should_continue() && sending_finished) || self.state.is_stopped() {
                 break;
             }
 
@@ -100,6 +112,7 @@ impl NetworkExplorer for LocalScanner {
             }
         }
 
+        self.tag_dns_servers();
         Ok(self.hosts_map.drain().map(|(_, v)| v).collect())
     }
 }
@@ -108,30 +121,28 @@ impl LocalScanner {
     pub fn new(
         intf: NetworkInterface,
         collection: IpCollection,
+        plan: &ProbePlan,
         dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
     ) -> anyhow::Result<Self> {
         let eth_handle: EthernetHandle = channel::start_capture(&intf)?;
         let timer: ScanTimer = ScanTimer::new(MAX_CHANNEL_TIME, MIN_CHANNEL_TIME, MAX_SILENCE_MS);
         let ips_len: usize = collection.len();
 
         let mut sender_cfg: SenderConfig = SenderConfig::from(&intf);
-        sender_cfg.add_packet_type(PacketType::ARP);
-        if IS_LAN_SCAN.load(Ordering::Relaxed) {
+        if plan.arp {
+            sender_cfg.add_packet_type(PacketType::ARP);
+        }
+        let is_lan_sweep: bool = plan.ndp && IS_LAN_SCAN.load(Ordering::Relaxed);
+        if is_lan_sweep {
             sender_cfg.add_packet_type(PacketType::ICMPv6);
         }
 
-        let mut target_ips: HashSet<IpAddr> = HashSet::new();
+        let target_ips: HashSet<IpAddr> = collection.into_iter().collect();
 
-        for single in collection.singles {
-            target_ips.insert(single);
-        }
-
-        for range in collection.ranges {
-            for ip in range.to_iter() {
-                target_ips.insert(ip);
-            }
+        if plan.ndp && target_ips.iter().any(IpAddr::is_ipv6) {
+            sender_cfg.add_packet_type(PacketType::NDP);
         }
-
         sender_cfg.add_targets(target_ips);
 
         Ok(Self {
@@ -140,31 +151,44 @@ impl LocalScanner {
             eth_handle,
             timer,
             dns_tx,
+            events: None,
+            state,
             rtt_map: HashMap::with_capacity(ips_len),
+            accept_ipv6: is_lan_sweep,
+            dns_servers: HashSet::new(),
         })
     }
 
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.events = events;
+        self
+    }
+
     fn process_eth_packet(&mut self, bytes: &[u8]) -> anyhow::Result<()> {
         let eth_frame: EthernetPacket = ethernet::get_packet_from_u8(bytes)?;
         if eth_frame.get_source() == self.sender_cfg.local_mac.unwrap() {
             return Ok(());
         }
-        let source_addr: IpAddr = protocol::get_ip_addr_from_eth(&eth_frame)?;
+        let ndp_message: Option<NdpMessage> = ndp::parse_from_eth(&eth_frame);
+        let source_addr: IpAddr = match &ndp_message {
+            Some(NdpMessage::NeighborAdvert(advert)) => IpAddr::V6(advert.target),
+            _ => protocol::get_ip_addr_from_eth(&eth_frame)?,
+        };
 
         ensure!(
             self.sender_cfg.is_addr_in_subnet(source_addr),
             "{source_addr} is not in range"
         );
 
-        // NOTE: This sucks as you might tell
         if source_addr.is_ipv6()
-            && !IS_LAN_SCAN.load(Ordering::Relaxed)
+            && !self.accept_ipv6
+            && !self.sender_cfg.has_addr(&source_addr)
             && !self.hosts_map.contains_key(&eth_frame.get_source())
         {
             return Ok(());
         }
 
-        let rtt: Option<Duration> = match self.calculate_rtt(&eth_frame) {
+        let rtt: Option<Duration> = match self.calculate_rtt(&eth_frame, source_addr) {
             Ok(r) => r,
             Err(e) => {
                 error!(verbosity = 2, "Failed to calculate RTT: {e}");
@@ -177,7 +201,7 @@ impl LocalScanner {
         let mut is_new_host: bool = false;
         let host: &mut Host = self.hosts_map.entry(source_mac).or_insert_with(|| {
             self.timer.mark_seen();
-            super::increment_host_count();
+            self.state.add_host();
             is_new_host = true;
             Host::new(source_addr).with_mac(source_mac)
         });
@@ -197,14 +221,50 @@ impl LocalScanner {
             host.primary_ip = source_addr;
         }
 
+        if is_new_host {
+            let event: ScanEvent = ScanEvent::host_up(source_addr, Some(source_mac), rtt);
+            super::emit(&self.events, event);
+        }
+
         if is_new_host || is_new_ip {
             self.dns_tx.as_ref().map(|tx| tx.send(source_addr));
         }
 
+        match ndp_message {
+            Some(NdpMessage::NeighborAdvert(advert)) if advert.is_router => {
+                host.network_roles.insert(NetworkRole::Gateway);
+            }
+            Some(NdpMessage::RouterAdvert(advert)) => {
+                host.network_roles.insert(NetworkRole::Gateway);
+                if !advert.dns_servers.is_empty() {
+                    info!(
+                        verbosity = 1,
+                        "{source_addr} announces DNS servers {:?}", advert.dns_servers
+                    );
+                }
+                self.dns_servers
+                    .extend(advert.dns_servers.into_iter().map(IpAddr::V6));
+            }
+            _ => {}
+        }
+
         Ok(())
     }
 
-    fn calculate_rtt(&mut self, eth_frame: &EthernetPacket) -> anyhow::Result<Option<Duration>> {
+    /// Marks the hosts announced as DNS servers by a router.
+    fn tag_dns_servers(&mut self) {
+        for host in self.hosts_map.values_mut() {
+            if host.ips.iter().any(|ip| self.dns_servers.contains(ip)) {
+                host.network_roles.insert(NetworkRole::DNS);
+            }
+        }
+    }
+
+    fn calculate_rtt(
+        &mut self,
+        eth_frame: &EthernetPacket,
+        source_addr: IpAddr,
+    ) -> anyhow::Result<Option<Duration>> {
         match eth_frame.get_ethertype() {
             EtherTypes::Arp => {
                 let arp_packet: ArpPacket = ArpPacket::new(eth_frame.payload())
@@ -221,6 +281,11 @@ impl LocalScanner {
             }
 
             EtherTypes::Ipv6 => {
+                // Answers to neighbor solicitations come from the solicited target
+                if let Some(start_time) = self.rtt_map.remove(&source_addr) {
+                    return Ok(Some(start_time.elapsed()));
+                }
+
                 let dst_addr: Ipv6Addr = match ip::get_ipv6_dst_addr_from_eth(eth_frame) {
                     Ok(addr) => addr,
                     Err(_) => bail!("packet invalid [IPv6]"),
@@ -244,7 +309,7 @@ impl LocalScanner {
     }
 
     fn should_continue(&self) -> bool {
-        let not_stopped: bool = !super::STOP_SIGNAL.load(Ordering::Relaxed);
+        let not_stopped: bool = !self.state.is_stopped();
         let time_expired: bool = !self.timer.is_expired();
         let work_remains: bool = self.sender_cfg.len() > self.hosts_map.len();
 
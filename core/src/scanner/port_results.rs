@@ -0,0 +1,132 @@
+//! Bookkeeping shared by the **port scanners**: the order probes go out in, and the
+//! hosts and ports proven by the answers.
+//!
+//! Each scanner only classifies its own answers (a SYN/ACK, a datagram, an ICMP
+//! unreachable) and hands the resulting [`Port`] to [`PortResults::record`].
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use pnet::packet::ip::IpNextHeaderProtocol;
+use tokio::sync::mpsc::UnboundedSender;
+use zond_common::{
+    models::{
+        event::ScanEvent,
+        host::Host,
+        port::{Port, PortState},
+    },
+    success,
+};
+use zond_protocols::icmp;
+
+use super::{EventTx, ScanState};
+
+/// A target address and the port probed on it.
+pub type Probe = (IpAddr, u16);
+pub type ProbeIter = Box<dyn Iterator<Item = Probe> + Send>;
+
+/// Interleaves targets per port so a single host never receives a burst of probes.
+pub fn interleave(targets: Vec<IpAddr>, ports: Vec<u16>) -> ProbeIter {
+    Box::new(
+        ports
+            .into_iter()
+            .flat_map(move |port| targets.clone().into_iter().map(move |ip| (ip, port))),
+    )
+}
+
+/// The probe an ICMP unreachable refers to and the code of the message, if it quotes one
+/// of our `protocol` probes sent from `src_port`.
+pub fn unreachable_probe(
+    bytes: &[u8],
+    protocol: IpNextHeaderProtocol,
+    src_port: u16,
+) -> Option<(Probe, u8)> {
+    let unreachable: icmp::Unreachable = icmp::parse_unreachable_v4(bytes).ok()?;
+    if unreachable.protocol != protocol || unreachable.src_port != src_port {
+        return None;
+    }
+    let probe: Probe = (IpAddr::V4(unreachable.dst_addr), unreachable.dst_port);
+    Some((probe, unreachable.code))
+}
+
+/// The RTT of an ICMP message about `probe`, sent by `src_addr`.
+///
+/// Only an answer from the target itself proves that the host is up, a router reporting
+/// it unreachable does not.
+pub fn icmp_rtt(src_addr: IpAddr, probe: Probe, sent_at: Instant) -> Option<Duration> {
+    (src_addr == probe.0).then(|| sent_at.elapsed())
+}
+
+/// The hosts and ports found by one port scan, with a count per port state.
+pub struct PortResults {
+    dns_tx: Option<UnboundedSender<IpAddr>>,
+    events: Option<EventTx>,
+    state: Arc<ScanState>,
+    hosts: HashMap<IpAddr, Host>,
+    state_count: HashMap<PortState, usize>,
+}
+
+impl PortResults {
+    pub fn new(dns_tx: Option<UnboundedSender<IpAddr>>, state: Arc<ScanState>) -> Self {
+        Self {
+            dns_tx,
+            events: None,
+            state,
+            hosts: HashMap::new(),
+            state_count: HashMap::new(),
+        }
+    }
+
+    pub fn set_events(&mut self, events: Option<EventTx>) {
+        self.events = events;
+    }
+
+    /// Counts the port and attaches it to the host if the host is known to be up.
+    ///
+    /// `rtt` is set when the target itself answered, which announces the host on its
+    /// first answer.
+    pub fn record(&mut self, ip: IpAddr, port: Port, rtt: Option<Duration>) {
+        *self.state_count.entry(port.state).or_default() += 1;
+
+        let host: &mut Host = match (self.hosts.contains_key(&ip), rtt) {
+            (true, _) => self.hosts.get_mut(&ip).unwrap(),
+            (false, Some(_)) => self.hosts.entry(ip).or_insert_with(|| {
+                self.state.add_host();
+                if let Some(dns_tx) = &self.dns_tx {
+                    let _ = dns_tx.send(ip);
+                }
+                super::emit(&self.events, ScanEvent::host_up(ip, None, rtt));
+                Host::new(ip)
+            }),
+            // Silence alone does not prove the host exists
+            (false, None) => return,
+        };
+
+        if let Some(rtt) = rtt {
+            host.add_rtt(rtt);
+        }
+
+        if port.is_open() {
+            success!(
+                verbosity = 1,
+                "Discovered open port {}/{} on {ip}",
+                port.number,
+                port.protocol
+            );
+        }
+        super::emit(&self.events, ScanEvent::port_state(ip, &port));
+        host.add_port(port);
+    }
+
+    pub fn count(&self, state: PortState) -> usize {
+        self.state_count.get(&state).copied().unwrap_or(0)
+    }
+
+    pub fn drain_hosts(&mut self) -> Vec<Host> {
+        self.hosts.drain().map(|(_, host)| host).collect()
+    }
+}
@@ -0,0 +1,135 @@
+//! Resolution of the user's **discovery probes** into what each explorer sends.
+//!
+//! [`LocalScanner`](super::local::LocalScanner) handles the link-layer probes (ARP/NDP),
+//! [`RoutedScanner`](super::routed::RoutedScanner) everything carried in IP packets.
+
+use std::collections::BTreeSet;
+
+use pnet::packet::icmp::{IcmpType, IcmpTypes};
+use zond_common::{
+    config::Config,
+    models::probe::{DEFAULT_SYN_PORT, DiscoveryProbe},
+};
+
+/// Everything discovery sends to a target, merged from all selected probes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProbePlan {
+    pub syn_ports: Vec<u16>,
+    pub ack_ports: Vec<u16>,
+    pub udp_ports: Vec<u16>,
+    pub icmp_types: Vec<IcmpType>,
+    pub arp: bool,
+    pub ndp: bool,
+}
+
+impl ProbePlan {
+    /// The probes of [`Config::probes`], or the defaults if none were chosen:
+    /// ARP and NDP on-link, a TCP SYN to the `-p` ports or 443 beyond.
+    ///
+    /// TCP ports given with `-p` are always probed with a SYN.
+    pub fn from_config(cfg: &Config) -> Self {
+        let mut syn_ports: BTreeSet<u16> = cfg.ports.tcp.iter().copied().collect();
+
+        if cfg.probes.is_empty() {
+            if syn_ports.is_empty() {
+                syn_ports.insert(DEFAULT_SYN_PORT);
+            }
+            return Self {
+                syn_ports: syn_ports.into_iter().collect(),
+                arp: true,
+                ndp: true,
+                ..Default::default()
+            };
+        }
+
+        let mut ack_ports: BTreeSet<u16> = BTreeSet::new();
+        let mut udp_ports: BTreeSet<u16> = BTreeSet::new();
+        let mut plan: ProbePlan = ProbePlan::default();
+
+        for probe in &cfg.probes {
+            match probe {
+                DiscoveryProbe::TcpSyn(ports) => syn_ports.extend(ports),
+                DiscoveryProbe::TcpAck(ports) => ack_ports.extend(ports),
+                DiscoveryProbe::Udp(ports) => udp_ports.extend(ports),
+                DiscoveryProbe::IcmpEcho => plan.add_icmp(IcmpTypes::EchoRequest),
+                DiscoveryProbe::IcmpTimestamp => plan.add_icmp(IcmpTypes::Timestamp),
+                DiscoveryProbe::IcmpAddressMask => plan.add_icmp(IcmpTypes::AddressMaskRequest),
+                DiscoveryProbe::Arp => plan.arp = true,
+                DiscoveryProbe::Ndp => plan.ndp = true,
+            }
+        }
+
+        plan.syn_ports = syn_ports.into_iter().collect();
+        plan.ack_ports = ack_ports.into_iter().collect();
+        plan.udp_ports = udp_ports.into_iter().collect();
+        plan
+    }
+
+    /// Whether anything is sent at the link layer.
+    pub fn has_link_probes(&self) -> bool {
+        self.arp || self.ndp
+    }
+
+    /// Whether anything is sent in IP packets.
+    pub fn has_ip_probes(&self) -> bool {
+        self.probes_per_target() > 0
+    }
+
+    /// Number of IP packets sent to a single target.
+    pub fn probes_per_target(&self) -> usize {
+        self.syn_ports.len() + self.ack_ports.len() + self.udp_ports.len() + self.icmp_types.len()
+    }
+
+    fn add_icmp(&mut self, icmp_type: IcmpType) {
+        if !self.icmp_types.contains(&icmp_type) {
+            self.icmp_types.push(icmp_type);
+        }
+    }
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zond_common::models::port::PortSet;
+
+    #[test]
+    fn defaults_to_arp_and_syn() {
+        let plan: ProbePlan = ProbePlan::from_config(&Config::default());
+
+        assert!(plan.arp);
+        assert_eq!(plan.syn_ports, vec![DEFAULT_SYN_PORT]);
+        assert!(plan.icmp_types.is_empty());
+    }
+
+    #[test]
+    fn merges_selected_probes() {
+        let cfg: Config = Config {
+            ports: PortSet::parse("22").unwrap(),
+            probes: vec![
+                DiscoveryProbe::TcpSyn(vec![80, 22]),
+                DiscoveryProbe::TcpAck(vec![80]),
+                DiscoveryProbe::Udp(vec![53]),
+                DiscoveryProbe::IcmpEcho,
+                DiscoveryProbe::IcmpEcho,
+            ],
+            ..Default::default()
+        };
+        let plan: ProbePlan = ProbePlan::from_config(&cfg);
+
+        assert_eq!(plan.syn_ports, vec![22, 80]);
+        assert_eq!(plan.ack_ports, vec![80]);
+        assert_eq!(plan.udp_ports, vec![53]);
+        assert_eq!(plan.icmp_types, vec![IcmpTypes::EchoRequest]);
+        assert!(!plan.has_link_probes());
+        assert_eq!(plan.probes_per_target(), 5);
+    }
+}
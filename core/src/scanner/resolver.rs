@@ -10,7 +10,10 @@ use std::{
 use anyhow::{Context, ensure};
 use pnet::packet::{Packet, udp::UdpPacket};
 use tokio::sync::mpsc::UnboundedReceiver;
-use zond_common::{models::host::Host, utils};
+use zond_common::{
+    models::{event::ScanEvent, host::Host},
+    utils,
+};
 use zond_protocols::{
     dns,
     mdns::{self, MdnsRecord},
@@ -19,6 +22,8 @@ use zond_protocols::{
 
 use crate::network::transport::{self, TransportHandle, TransportType};
 
+use super::EventTx;
+
 const DNS_PORT: u16 = 53;
 const MDNS_PORT: u16 = 5353;
 
@@ -33,6 +38,7 @@ pub struct HostnameResolver {
     dns_rx: UnboundedReceiver<IpAddr>,
     dns_socket: SocketAddr,
     id_counter: AtomicU16,
+    events: Option<EventTx>,
 }
 
 impl HostnameResolver {
@@ -45,9 +51,15 @@ impl HostnameResolver {
             dns_rx,
             dns_socket: get_dns_server_socket()?,
             id_counter: AtomicU16::new(0),
+            events: None,
         })
     }
 
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.events = events;
+        self
+    }
+
     pub async fn run(mut self) -> Self {
         loop {
             tokio::select! {
@@ -115,6 +127,11 @@ impl HostnameResolver {
     fn process_dns_packet(&mut self, packet: UdpPacket) -> anyhow::Result<()> {
         let (response_id, hostname) = dns::get_hostname(packet.payload())?;
         if let Some(ip) = self.dns_map.remove(&response_id) {
+            let event: ScanEvent = ScanEvent::HostnameResolved {
+                ip,
+                hostname: hostname.clone(),
+            };
+            super::emit(&self.events, event);
             self.hostname_map.insert(ip, hostname);
         }
         Ok(())
@@ -139,6 +156,19 @@ impl HostnameResolver {
             .or_else(|| mdns_record.ips.iter().next());
 
         if let Some(ip) = preferred_ip {
+            let known: Option<&String> = self
+                .mdns_cache
+                .get(ip)
+                .and_then(|record| record.hostname.as_ref());
+            if let Some(hostname) = &mdns_record.hostname
+                && known != Some(hostname)
+            {
+                let event: ScanEvent = ScanEvent::HostnameResolved {
+                    ip: *ip,
+                    hostname: hostname.clone(),
+                };
+                super::emit(&self.events, event);
+            }
             self.mdns_cache.insert(*ip, mdns_record);
         }
 
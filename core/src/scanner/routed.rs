@@ -1,92 +1,154 @@
 use std::{
     collections::{HashMap, VecDeque, hash_map::Entry},
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
-    sync::atomic::Ordering,
+    sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-use anyhow::ensure;
+use anyhow::{Context, ensure};
 use async_trait::async_trait;
-use pnet::{datalink::NetworkInterface, packet::tcp::TcpPacket};
+use pnet::{
+    datalink::NetworkInterface,
+    packet::{
+        Packet,
+        icmp::{IcmpPacket, IcmpType},
+        ip::IpNextHeaderProtocols,
+        tcp::TcpPacket,
+        udp::UdpPacket,
+    },
+    transport::TransportSender,
+};
 use tokio::sync::mpsc::UnboundedSender;
-use zond_common::{error, success};
+use zond_common::{error, info, success};
 
-use zond_common::models::{host::Host, range::IpCollection};
+use zond_common::models::{event::ScanEvent, host::Host, range::IpCollection};
 use zond_protocols as protocol;
 
 use crate::network::transport::{self, TransportHandle, TransportType};
 
-use super::NetworkExplorer;
+use super::{EventTx, NetworkExplorer, ScanState, probe::ProbePlan};
 
 // this shit needs improvement
 const MIN_SCAN_DURATION: Duration = Duration::from_millis(200);
 const MAX_SCAN_DURATION: Duration = Duration::from_millis(3000);
 const MS_PER_IP: f64 = 0.5;
+const ICMP_PORT_UNREACHABLE: u8 = 3;
+const SEND_INTERVAL: Duration = Duration::from_millis(1);
+const PROBES_PER_TICK: usize = 32;
+
+/// One probe of the [`ProbePlan`], sent to every target.
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+    Syn(u16),
+    Ack(u16),
+    Udp(u16),
+    /// The sequence number and type of an ICMP request.
+    Icmp(u16, IcmpType),
+}
 
-type SeqNum = u32;
+type ProbeIter = Box<dyn Iterator<Item = (IpAddr, Probe)> + Send>;
+
+/// Identifies a sent probe in the answer it provokes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ProbeKey {
+    /// The sequence number of a SYN, or the acknowledgement number of an ACK.
+    Tcp(u32),
+    /// The destination port of a UDP datagram.
+    Udp(u16),
+    /// The sequence number of an ICMP request.
+    Icmp(u16),
+}
 
 pub struct RoutedScanner {
     src_v4: Option<Ipv4Addr>,
     src_v6: Option<Ipv6Addr>,
     responded_ips: HashMap<IpAddr, VecDeque<Duration>>,
     ips: IpCollection,
-    tcp_handle: TransportHandle,
+    plan: ProbePlan,
+    src_port: u16,
+    tcp_handle: Option<TransportHandle>,
+    udp_handle: Option<TransportHandle>,
+    /// Receives ICMP replies as well as the port unreachables provoked by UDP probes.
+    icmp_handle: Option<TransportHandle>,
+    /// Identifies our ICMP requests; replies carrying another identifier are ignored.
+    icmp_identifier: u16,
     dns_tx: Option<UnboundedSender<IpAddr>>,
-    rtt_map: HashMap<(IpAddr, SeqNum), Instant>,
+    events: Option<EventTx>,
+    state: Arc<ScanState>,
+    rtt_map: HashMap<(IpAddr, ProbeKey), Instant>,
 }
 
 #[async_trait]
 impl NetworkExplorer for RoutedScanner {
     async fn discover_hosts(&mut self) -> anyhow::Result<Vec<Host>> {
-        if let Err(e) = self.send_discovery_packets() {
-            error!("Failed to send packets: {e}");
-        }
-
-        let deadline: Instant = calculate_deadline(self.ips.len());
+        let mut probe_iter: ProbeIter = self.probe_iter();
+        let mut send_interval = tokio::time::interval(SEND_INTERVAL);
+        // Set once the last probe went out, the scan then only waits for answers
+        let mut deadline: Option<Instant> = None;
 
         loop {
-            if super::STOP_SIGNAL.load(Ordering::Relaxed)
-                || self.ips.len() == self.responded_ips.len()
-            {
+            if self.state.is_stopped() || self.ips.len() == self.responded_ips.len() {
                 break;
             }
 
-            let remaining: Duration = deadline.saturating_duration_since(Instant::now());
-            if remaining.is_zero() {
+            let remaining: Duration = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => SEND_INTERVAL,
+            };
+            if deadline.is_some() && remaining.is_zero() {
                 break;
             }
 
             tokio::select! {
-                res = self.tcp_handle.rx.recv() => {
-                    match res {
-                        Some((bytes, ip)) => {
-                            if !self.ips.contains(&ip) {
-                                continue;
-                            }
-
-                            let entry = self.responded_ips.entry(ip);
-                            let is_new = matches!(entry, Entry::Vacant(_));
-                            let latencies = entry.or_default();
-
-                            if is_new {
-                                let _ = self.dns_tx.as_ref().map(|dns| dns.send(ip));
-                                super::increment_host_count();
-                            }
-
-                            if let Some(tcp_packet) = TcpPacket::new(&bytes) {
-                                let ack_num: u32 = tcp_packet.get_acknowledgement();
-                                let original_seq: u32 = ack_num.wrapping_sub(1);
-
-                                if let Some(start_time) = self.rtt_map.remove(&(ip, original_seq)) {
-                                    let rtt: Duration = start_time.elapsed();
-                                    latencies.push_back(rtt);
-                                }
-                            }
-                        },
-                        None => break,
+                Some((bytes, ip)) = recv(&mut self.tcp_handle) => {
+                    let Some(tcp_packet) = TcpPacket::new(&bytes) else {
+                        continue;
+                    };
+                    if !self.ips.contains(&ip) || tcp_packet.get_destination() != self.src_port {
+                        continue;
                     }
+
+                    // SYN/ACKs and RSTs to a SYN acknowledge its sequence number,
+                    // RSTs to an ACK take the acknowledgement number as their sequence number.
+                    let syn_key: ProbeKey = ProbeKey::Tcp(tcp_packet.get_acknowledgement().wrapping_sub(1));
+                    let ack_key: ProbeKey = ProbeKey::Tcp(tcp_packet.get_sequence());
+                    let rtt: Option<Duration> = self
+                        .take_rtt(ip, syn_key)
+                        .or_else(|| self.take_rtt(ip, ack_key));
+                    self.record_response(ip, rtt);
                 },
-                _ = tokio::time::sleep(remaining) => {
+                Some((bytes, ip)) = recv(&mut self.udp_handle) => {
+                    let Some(udp_packet) = UdpPacket::new(&bytes) else {
+                        continue;
+                    };
+                    if !self.ips.contains(&ip) || udp_packet.get_destination() != self.src_port {
+                        continue;
+                    }
+
+                    let rtt: Option<Duration> = self.take_rtt(ip, ProbeKey::Udp(udp_packet.get_source()));
+                    self.record_response(ip, rtt);
+                },
+                Some((bytes, ip)) = recv(&mut self.icmp_handle) => {
+                    if !self.ips.contains(&ip) {
+                        continue;
+                    }
+                    if let Some(key) = self.match_icmp(ip, &bytes) {
+                        let rtt: Option<Duration> = self.take_rtt(ip, key);
+                        self.record_response(ip, rtt);
+                    }
+                },
+                _ = send_interval.tick(), if deadline.is_none() => {
+                    for _ in 0..PROBES_PER_TICK {
+                        let Some((dst_addr, probe)) = probe_iter.next() else {
+                            let probes: usize = self.ips.len() * self.plan.probes_per_target();
+                            deadline = Some(calculate_deadline(probes));
+                            break;
+                        };
+                        self.send_probe(dst_addr, probe);
+                    }
+                }
+
+                _ = tokio::time::sleep(remaining), if deadline.is_some() => {
                     break;
                 }
             }
@@ -111,10 +173,17 @@ impl RoutedScanner {
     pub fn new(
         intf: NetworkInterface,
         ips: IpCollection,
+        plan: ProbePlan,
         dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
     ) -> anyhow::Result<Self> {
-        let tcp_handle: TransportHandle =
-            transport::start_packet_capture(TransportType::TcpLayer4)?;
+        let has_tcp: bool = !plan.syn_ports.is_empty() || !plan.ack_ports.is_empty();
+        let has_udp: bool = !plan.udp_ports.is_empty();
+        let has_icmp: bool = has_udp || !plan.icmp_types.is_empty();
+
+        let tcp_handle: Option<TransportHandle> = open_if(has_tcp, TransportType::TcpLayer4)?;
+        let udp_handle: Option<TransportHandle> = open_if(has_udp, TransportType::UdpLayer4)?;
+        let icmp_handle: Option<TransportHandle> = open_if(has_icmp, TransportType::IcmpLayer4)?;
 
         let src_v4: Option<Ipv4Addr> = intf.ips.iter().find_map(|ip_net| match ip_net.ip() {
             IpAddr::V4(ipv4) => Some(ipv4),
@@ -136,44 +205,231 @@ impl RoutedScanner {
             src_v6,
             responded_ips: HashMap::new(),
             ips,
+            plan,
+            src_port: rand::random_range(50_000..u16::MAX),
             tcp_handle,
+            udp_handle,
+            icmp_handle,
+            icmp_identifier: rand::random(),
             dns_tx,
+            events: None,
+            state,
             rtt_map: HashMap::new(),
         })
     }
 
-    fn send_discovery_packets(&mut self) -> anyhow::Result<()> {
-        let src_port: u16 = rand::random_range(50_000..u16::MAX);
-        let dst_port: u16 = 443;
-        for dst_addr in self.ips.iter() {
-            let src_addr: IpAddr = match dst_addr {
-                IpAddr::V4(_) => {
-                    ensure!(self.src_v4.is_some(), "interface has no ipv4 address");
-                    IpAddr::V4(self.src_v4.unwrap())
-                }
-                IpAddr::V6(_) => {
-                    ensure!(self.src_v6.is_some(), "interface has no ipv6 address");
-                    IpAddr::V6(self.src_v6.unwrap())
-                }
-            };
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.events = events;
+        self
+    }
 
-            let seq_num: u32 = rand::random_range(0..=u32::MAX);
-            let packet: Vec<u8> =
-                protocol::tcp::create_packet(&src_addr, &dst_addr, src_port, dst_port, seq_num)?;
+    /// Marks `ip` as up, announcing it on its first response, and keeps the probe's RTT.
+    fn record_response(&mut self, ip: IpAddr, rtt: Option<Duration>) {
+        let entry = self.responded_ips.entry(ip);
+        if matches!(entry, Entry::Vacant(_)) {
+            let _ = self.dns_tx.as_ref().map(|dns| dns.send(ip));
+            self.state.add_host();
+            super::emit(&self.events, ScanEvent::host_up(ip, None, rtt));
+        }
 
-            if let Some(packet) = TcpPacket::new(&packet) {
-                let mut tx = self.tcp_handle.tx.lock().unwrap();
-                match tx.send_to(packet, dst_addr) {
-                    Ok(_) => {
-                        success!(verbosity = 2, "Sent discovery packet to {dst_addr}");
-                        self.rtt_map.insert((dst_addr, seq_num), Instant::now());
-                    }
-                    Err(e) => error!(verbosity = 2, "Failed to send packet to {dst_addr}: {e}"),
+        let latencies: &mut VecDeque<Duration> = entry.or_default();
+        if let Some(rtt) = rtt {
+            latencies.push_back(rtt);
+        }
+    }
+
+    fn take_rtt(&mut self, ip: IpAddr, key: ProbeKey) -> Option<Duration> {
+        self.rtt_map
+            .remove(&(ip, key))
+            .map(|start_time| start_time.elapsed())
+    }
+
+    /// Matches an ICMP message from `ip` to the probe that provoked it: either a reply to
+    /// one of our requests, or a port unreachable the target sent about a UDP probe.
+    fn match_icmp(&self, ip: IpAddr, bytes: &[u8]) -> Option<ProbeKey> {
+        if let Some(reply) = protocol::icmp::parse_reply_v4(bytes) {
+            return (reply.identifier == self.icmp_identifier)
+                .then_some(ProbeKey::Icmp(reply.sequence));
+        }
+
+        let unreachable = protocol::icmp::parse_unreachable_v4(bytes).ok()?;
+        let from_target: bool = IpAddr::V4(unreachable.dst_addr) == ip;
+        let is_ours: bool = unreachable.protocol == IpNextHeaderProtocols::Udp
+            && unreachable.src_port == self.src_port;
+
+        (from_target && is_ours && unreachable.code == ICMP_PORT_UNREACHABLE)
+            .then_some(ProbeKey::Udp(unreachable.dst_port))
+    }
+
+    /// Interleaves targets per probe so a single host never receives a burst of probes.
+    ///
+    /// Targets of an address family the interface has no address for are skipped.
+    fn probe_iter(&self) -> ProbeIter {
+        let targets: Vec<IpAddr> = self
+            .ips
+            .iter()
+            .filter(|ip| {
+                let reachable: bool = self.src_addr_for(ip).is_some();
+                if !reachable {
+                    info!(
+                        verbosity = 1,
+                        "Skipping {ip}, the interface has no address of its family"
+                    );
                 }
-            }
+                reachable
+            })
+            .collect();
+
+        let plan: &ProbePlan = &self.plan;
+        let probes: Vec<Probe> = plan
+            .syn_ports
+            .iter()
+            .map(|&port| Probe::Syn(port))
+            .chain(plan.ack_ports.iter().map(|&port| Probe::Ack(port)))
+            .chain(plan.udp_ports.iter().map(|&port| Probe::Udp(port)))
+            .chain(
+                plan.icmp_types
+                    .iter()
+                    .enumerate()
+                    .map(|(sequence, &icmp_type)| Probe::Icmp(sequence as u16, icmp_type)),
+            )
+            .collect();
+
+        Box::new(
+            probes
+                .into_iter()
+                .flat_map(move |probe| targets.clone().into_iter().map(move |ip| (ip, probe)))
+                .filter(|(ip, probe)| ip.is_ipv4() || !matches!(probe, Probe::Icmp(..))),
+        )
+    }
+
+    fn send_probe(&mut self, dst_addr: IpAddr, probe: Probe) {
+        let Some(src_addr) = self.src_addr_for(&dst_addr) else {
+            return;
+        };
+
+        let sent: anyhow::Result<()> = match probe {
+            Probe::Syn(dst_port) => self.send_tcp_probe(src_addr, dst_addr, dst_port, false),
+            Probe::Ack(dst_port) => self.send_tcp_probe(src_addr, dst_addr, dst_port, true),
+            Probe::Udp(dst_port) => self.send_udp_probe(dst_addr, dst_port),
+            Probe::Icmp(sequence, icmp_type) => self.send_icmp_probe(dst_addr, sequence, icmp_type),
+        };
+        if let Err(e) = sent {
+            error!(
+                verbosity = 2,
+                "Failed to build {probe:?} probe for {dst_addr}: {e}"
+            );
         }
+    }
+
+    fn send_tcp_probe(
+        &mut self,
+        src_addr: IpAddr,
+        dst_addr: IpAddr,
+        dst_port: u16,
+        ack: bool,
+    ) -> anyhow::Result<()> {
+        let Some(tx) = self.tcp_handle.as_ref().map(|handle| handle.tx.clone()) else {
+            return Ok(());
+        };
+
+        // SYNs are matched by their sequence number, ACKs by their acknowledgement number
+        let number: u32 = rand::random();
+        let (packet, label) = match ack {
+            false => (
+                protocol::tcp::create_packet(
+                    &src_addr,
+                    &dst_addr,
+                    self.src_port,
+                    dst_port,
+                    number,
+                )?,
+                "TCP SYN",
+            ),
+            true => (
+                protocol::tcp::create_ack_packet(
+                    &src_addr,
+                    &dst_addr,
+                    self.src_port,
+                    dst_port,
+                    number,
+                )?,
+                "TCP ACK",
+            ),
+        };
+        let packet: TcpPacket = TcpPacket::new(&packet).context("creating tcp packet")?;
+        self.transmit(&tx, packet, dst_addr, ProbeKey::Tcp(number), label);
+        Ok(())
+    }
+
+    fn send_udp_probe(&mut self, dst_addr: IpAddr, dst_port: u16) -> anyhow::Result<()> {
+        let Some(tx) = self.udp_handle.as_ref().map(|handle| handle.tx.clone()) else {
+            return Ok(());
+        };
+
+        let packet: Vec<u8> = protocol::udp::create_packet(self.src_port, dst_port, Vec::new())?;
+        let packet: UdpPacket = UdpPacket::new(&packet).context("creating udp packet")?;
+        self.transmit(&tx, packet, dst_addr, ProbeKey::Udp(dst_port), "UDP");
         Ok(())
     }
+
+    fn send_icmp_probe(
+        &mut self,
+        dst_addr: IpAddr,
+        sequence: u16,
+        icmp_type: IcmpType,
+    ) -> anyhow::Result<()> {
+        let Some(tx) = self.icmp_handle.as_ref().map(|handle| handle.tx.clone()) else {
+            return Ok(());
+        };
+
+        let packet: Vec<u8> =
+            protocol::icmp::create_request_v4(icmp_type, self.icmp_identifier, sequence)?;
+        let packet: IcmpPacket = IcmpPacket::new(&packet).context("creating icmp packet")?;
+        self.transmit(&tx, packet, dst_addr, ProbeKey::Icmp(sequence), "ICMP");
+        Ok(())
+    }
+
+    fn transmit<P: Packet>(
+        &mut self,
+        tx: &Arc<Mutex<TransportSender>>,
+        packet: P,
+        dst_addr: IpAddr,
+        key: ProbeKey,
+        label: &str,
+    ) {
+        let mut tx = tx.lock().unwrap();
+        match tx.send_to(packet, dst_addr) {
+            Ok(_) => {
+                success!(verbosity = 2, "Sent {label} probe to {dst_addr} ({key:?})");
+                self.rtt_map.insert((dst_addr, key), Instant::now());
+            }
+            Err(e) => error!(verbosity = 2, "Failed to send packet to {dst_addr}: {e}"),
+        }
+    }
+
+    /// The interface address to send from, `None` if it has none of the target's family.
+    fn src_addr_for(&self, dst_addr: &IpAddr) -> Option<IpAddr> {
+        match dst_addr {
+            IpAddr::V4(_) => self.src_v4.map(IpAddr::V4),
+            IpAddr::V6(_) => self.src_v6.map(IpAddr::V6),
+        }
+    }
+}
+
+fn open_if(needed: bool, transport_type: TransportType) -> anyhow::Result<Option<TransportHandle>> {
+    match needed {
+        true => Ok(Some(transport::start_packet_capture(transport_type)?)),
+        false => Ok(None),
+    }
+}
+
+/// Waits for the next packet of a channel, or forever if the channel was never opened.
+async fn recv(handle: &mut Option<TransportHandle>) -> Option<(Vec<u8>, IpAddr)> {
+    match handle {
+        Some(handle) => handle.rx.recv().await,
+        None => std::future::pending().await,
+    }
 }
 
 fn calculate_deadline(ips_len: usize) -> Instant {
@@ -0,0 +1,510 @@
+//! **Service and version detection** for open TCP ports.
+//!
+//! Every open port is fingerprinted in isolation:
+//! 1. Connect and wait for a greeting (the NULL probe), which identifies most
+//!    banner-first protocols such as SSH, FTP, SMTP or MySQL.
+//! 2. Otherwise send the probes written for that port (HTTP GET, TLS ClientHello,
+//!    Redis PING, ...) on fresh connections until a response matches.
+//!
+//! Responses are matched against the [`SignatureDb`]. A match replaces the service
+//! name guessed from the port table and fills in the version of the port.
+//! Ports that turn out to speak TLS get a full handshake, which records the negotiated
+//! parameters and the server certificate; its SAN names a host that has no PTR record.
+//! Web services finally have their landing page fetched for status, title, headers,
+//! redirects and favicon hash.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    task::JoinSet,
+    time::timeout,
+};
+use zond_common::{
+    error, info,
+    models::{host::Host, http::HttpInfo, port::Protocol, tls::TlsInfo},
+    success,
+};
+
+mod http;
+mod signatures;
+mod tls;
+
+use http::{HttpFetcher, Scheme};
+pub use signatures::{ServiceMatch, SignatureDb};
+use tls::TlsInspector;
+
+use super::ScanState;
+
+/// In-flight limit used when the user did not configure one.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 64;
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(2_000);
+const GREETING_TIMEOUT: Duration = Duration::from_millis(2_000);
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(3_000);
+/// Once data arrived, reading stops after this long without more.
+const READ_IDLE_TIMEOUT: Duration = Duration::from_millis(250);
+const MAX_RESPONSE_SIZE: usize = 8_192;
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A port to fingerprint, with the name to present as TLS SNI.
+struct Target {
+    ip: IpAddr,
+    number: u16,
+    server_name: Option<String>,
+    /// The service guessed from the port table.
+    guess: Option<String>,
+}
+
+/// Everything learned about a single port.
+struct Detection {
+    target: Target,
+    service: Option<ServiceMatch>,
+    tls: Option<TlsInfo>,
+    http: Option<HttpInfo>,
+}
+
+pub struct ServiceDetector {
+    db: Arc<SignatureDb>,
+    tls: TlsInspector,
+    http: HttpFetcher,
+    max_in_flight: usize,
+    state: Arc<ScanState>,
+}
+
+impl ServiceDetector {
+    pub fn new(
+        db: SignatureDb,
+        max_in_flight: usize,
+        state: Arc<ScanState>,
+    ) -> anyhow::Result<Self> {
+        let tls: TlsInspector = TlsInspector::new()?;
+        Ok(Self {
+            db: Arc::new(db),
+            http: HttpFetcher::new(tls.clone()),
+            tls,
+            max_in_flight: max_in_flight.max(1),
+            state,
+        })
+    }
+
+    /// Fingerprints every open TCP port of `hosts` and updates the ports in place.
+    pub async fn detect(&self, hosts: &mut [Host]) {
+        let mut targets = hosts
+            .iter()
+            .flat_map(|host| {
+                host.open_ports()
+                    .filter(|port| port.protocol == Protocol::Tcp)
+                    .map(|port| Target {
+                        ip: host.primary_ip,
+                        number: port.number,
+                        server_name: host.hostname.clone(),
+                        guess: port.service.clone(),
+                    })
+            })
+            .collect::<Vec<Target>>()
+            .into_iter();
+
+        let mut in_flight: JoinSet<Detection> = JoinSet::new();
+        let mut identified: usize = 0;
+
+        loop {
+            if self.state.is_stopped() {
+                in_flight.shutdown().await;
+                break;
+            }
+
+            while in_flight.len() < self.max_in_flight {
+                let Some(target) = targets.next() else {
+                    break;
+                };
+                let (db, tls, http) = (self.db.clone(), self.tls.clone(), self.http.clone());
+                in_flight.spawn(async move { fingerprint(&db, &tls, &http, target).await });
+            }
+
+            if in_flight.is_empty() {
+                break;
+            }
+
+            tokio::select! {
+                Some(res) = in_flight.join_next() => match res {
+                    Ok(detection) => {
+                        if detection.service.is_some() {
+                            identified += 1;
+                        }
+                        apply(hosts, detection);
+                    }
+                    Err(e) => error!(verbosity = 2, "Service probe failed: {e}"),
+                },
+                _ = tokio::time::sleep(STOP_POLL_INTERVAL) => {}
+            }
+        }
+
+        info!(
+            verbosity = 1,
+            "Service detection identified {identified} ports"
+        );
+    }
+}
+
+/// Identifies the service on a port, completes a TLS handshake if it speaks TLS and
+/// fetches the landing page if it serves HTTP.
+async fn fingerprint(
+    db: &SignatureDb,
+    inspector: &TlsInspector,
+    fetcher: &HttpFetcher,
+    target: Target,
+) -> Detection {
+    let socket_addr: SocketAddr = SocketAddr::new(target.ip, target.number);
+    let service: Option<ServiceMatch> = identify(db, socket_addr).await;
+
+    let tls: Option<TlsInfo> = match &service {
+        Some(found) if found.is_tls() => {
+            match inspector
+                .inspect(socket_addr, target.server_name.as_deref())
+                .await
+            {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    info!(
+                        verbosity = 2,
+                        "TLS handshake with {socket_addr} failed: {e}"
+                    );
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let scheme: Option<Scheme> = match &service {
+        Some(found) if found.is_http() => Some(Scheme::Http),
+        // Most TLS ports wrap HTTP, but only ask those the port table agrees with
+        Some(found)
+            if tls.is_some()
+                && (found.service == "https"
+                    || target.guess.as_deref().is_some_and(|g| g.contains("http"))) =>
+        {
+            Some(Scheme::Https)
+        }
+        _ => None,
+    };
+
+    let http: Option<HttpInfo> = match scheme {
+        Some(scheme) => {
+            // Virtual hosts answer to their name, which the certificate may know
+            let host: Option<&str> = target.server_name.as_deref().or_else(|| {
+                tls.as_ref()
+                    .and_then(|tls| tls.certificate.as_ref())
+                    .and_then(|cert| cert.dns_names().next())
+            });
+            match fetcher.fetch(scheme, socket_addr, host).await {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    info!(
+                        verbosity = 2,
+                        "Fetching {scheme}://{socket_addr}/ failed: {e}"
+                    );
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    Detection {
+        target,
+        service,
+        tls,
+        http,
+    }
+}
+
+/// Runs the probes for one port until a response matches.
+async fn identify(db: &SignatureDb, socket_addr: SocketAddr) -> Option<ServiceMatch> {
+    for probe in db.probes_for(socket_addr.port()) {
+        let response: Vec<u8> = match exchange(socket_addr, &probe.payload).await {
+            Ok(response) => response,
+            Err(e) => {
+                info!(
+                    verbosity = 2,
+                    "Probe {} to {socket_addr} failed: {e}", probe.name
+                );
+                continue;
+            }
+        };
+
+        if let Some(found) = db.identify(probe, &response) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+/// Sends the payload (if any) on a fresh connection and collects the response.
+async fn exchange(socket_addr: SocketAddr, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut stream: TcpStream = timeout(CONNECT_TIMEOUT, TcpStream::connect(socket_addr)).await??;
+
+    let first_read: Duration = if payload.is_empty() {
+        GREETING_TIMEOUT
+    } else {
+        stream.write_all(payload).await?;
+        RESPONSE_TIMEOUT
+    };
+
+    let mut response: Vec<u8> = Vec::new();
+    let mut buf: [u8; 2_048] = [0; 2_048];
+    let mut wait: Duration = first_read;
+
+    while response.len() < MAX_RESPONSE_SIZE {
+        match timeout(wait, stream.read(&mut buf)).await {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(n)) => response.extend_from_slice(&buf[..n]),
+            Ok(Err(e)) if response.is_empty() => return Err(e.into()),
+            Ok(Err(_)) => break,
+        }
+        wait = READ_IDLE_TIMEOUT;
+    }
+
+    response.truncate(MAX_RESPONSE_SIZE);
+    Ok(response)
+}
+
+fn apply(hosts: &mut [Host], detection: Detection) {
+    let Detection {
+        target,
+        service,
+        tls,
+        http,
+    } = detection;
+    let (ip, number) = (target.ip, target.number);
+
+    let Some(host) = hosts.iter_mut().find(|host| host.primary_ip == ip) else {
+        return;
+    };
+    let Some(mut port) = host.get_port(number, Protocol::Tcp).cloned() else {
+        return;
+    };
+
+    if let Some(found) = service {
+        let version: Option<String> = found.describe();
+        let name: String = match (found.service.as_str(), &port.service) {
+            // TLS only tells us how the service is wrapped, the port table tells us what it is
+            ("ssl", Some(guess)) if !guess.starts_with("ssl/") => format!("ssl/{guess}"),
+            _ => found.service,
+        };
+
+        success!(
+            verbosity = 1,
+            "Identified {number}/tcp on {ip} as {name}{}",
+            version
+                .as_deref()
+                .map(|v| format!(" ({v})"))
+                .unwrap_or_default()
+        );
+        port.service = Some(name);
+        port.version = version;
+    }
+
+    if let Some(tls) = tls {
+        // Without a PTR record, the certificate is the next best source for a name
+        if host.hostname.is_none()
+            && let Some(name) = tls.certificate.as_ref().and_then(|c| c.dns_names().next())
+        {
+            info!(verbosity = 1, "Named {ip} {name} after its TLS certificate");
+            host.hostname = Some(name.to_string());
+        }
+        port.tls = Some(tls);
+    }
+
+    if http.is_some() {
+        port.http = http;
+    }
+
+    host.add_port(port);
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::CertifiedKey;
+    use rustls::{
+        ServerConfig,
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    };
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+    use zond_common::models::port::{Port, PortReason, PortState};
+
+    const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn host_with_open_port(number: u16) -> Host {
+        let mut host: Host = Host::new(LOCALHOST);
+        host.add_port(Port::new(
+            number,
+            Protocol::Tcp,
+            PortState::Open,
+            PortReason::SynAck,
+        ));
+        host
+    }
+
+    #[tokio::test]
+    async fn detect_should_read_greeting_banners() {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let number: u16 = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream
+                .write_all(b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13.5\r\n")
+                .await
+                .unwrap();
+        });
+
+        let mut hosts: Vec<Host> = vec![host_with_open_port(number)];
+        ServiceDetector::new(SignatureDb::embedded(), 4, ScanState::new())
+            .unwrap()
+            .detect(&mut hosts)
+            .await;
+
+        let port: &Port = hosts[0].get_port(number, Protocol::Tcp).unwrap();
+        assert_eq!(port.service.as_deref(), Some("ssh"));
+        assert_eq!(
+            port.version.as_deref(),
+            Some("OpenSSH 9.6p1 (Ubuntu Linux; protocol 2.0)")
+        );
+    }
+
+    #[tokio::test]
+    async fn detect_should_send_probes_to_silent_services() {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let number: u16 = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buf: [u8; 512] = [0; 512];
+                    let n: usize = stream.read(&mut buf).await.unwrap_or(0);
+                    if buf[..n].starts_with(b"GET /") {
+                        let reply: &[u8] = b"HTTP/1.0 200 OK\r\nServer: lighttpd/1.4.76\r\n\r\n";
+                        let _ = stream.write_all(reply).await;
+                    }
+                });
+            }
+        });
+
+        let mut hosts: Vec<Host> = vec![host_with_open_port(number)];
+        ServiceDetector::new(SignatureDb::embedded(), 4, ScanState::new())
+            .unwrap()
+            .detect(&mut hosts)
+            .await;
+
+        let port: &Port = hosts[0].get_port(number, Protocol::Tcp).unwrap();
+        assert_eq!(port.service.as_deref(), Some("http"));
+        assert_eq!(port.version.as_deref(), Some("lighttpd 1.4.76"));
+    }
+
+    #[tokio::test]
+    async fn detect_should_fetch_landing_pages() {
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let number: u16 = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buf: [u8; 512] = [0; 512];
+                    let n: usize = stream.read(&mut buf).await.unwrap_or(0);
+                    let reply: &[u8] = if buf[..n].starts_with(b"GET / ") {
+                        b"HTTP/1.1 302 Found\r\nServer: nginx/1.24.0\r\nLocation: /login\r\nContent-Length: 0\r\n\r\n"
+                    } else if buf[..n].starts_with(b"GET /login ") {
+                        b"HTTP/1.1 200 OK\r\nServer: nginx/1.24.0\r\nX-Powered-By: PHP/8.3.6\r\nContent-Length: 42\r\n\r\n<html><title>Router Login</title></html>\r\n"
+                    } else if buf[..n].starts_with(b"GET /favicon.ico ") {
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello"
+                    } else {
+                        return;
+                    };
+                    let _ = stream.write_all(reply).await;
+                });
+            }
+        });
+
+        let mut hosts: Vec<Host> = vec![host_with_open_port(number)];
+        ServiceDetector::new(SignatureDb::embedded(), 4, ScanState::new())
+            .unwrap()
+            .detect(&mut hosts)
+            .await;
+
+        let port: &Port = hosts[0].get_port(number, Protocol::Tcp).unwrap();
+        assert_eq!(port.service.as_deref(), Some("http"));
+
+        let http: &HttpInfo = port.http.as_ref().unwrap();
+        assert_eq!(http.status, 200);
+        assert_eq!(http.title.as_deref(), Some("Router Login"));
+        assert_eq!(http.server.as_deref(), Some("nginx/1.24.0"));
+        assert_eq!(http.powered_by.as_deref(), Some("PHP/8.3.6"));
+        assert_eq!(http.redirects, vec!["/login".to_string()]);
+        // mmh3.hash(base64.encodebytes(b"hello"))
+        assert_eq!(http.favicon_hash, Some(1_155_597_304));
+    }
+
+    #[tokio::test]
+    async fn detect_should_harvest_tls_certificates() {
+        let CertifiedKey { cert, signing_key } =
+            rcgen::generate_simple_self_signed(vec!["tls.zond.test".to_string()]).unwrap();
+        let key: PrivateKeyDer = PrivatePkcs8KeyDer::from(signing_key.serialize_der()).into();
+        let config: ServerConfig =
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_no_client_auth()
+                .with_single_cert(vec![CertificateDer::from(cert.der().to_vec())], key)
+                .unwrap();
+        let acceptor: TlsAcceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener: TcpListener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+        let number: u16 = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor: TlsAcceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut tls) = acceptor.accept(stream).await {
+                        let _ = tls.shutdown().await;
+                    }
+                });
+            }
+        });
+
+        let mut hosts: Vec<Host> = vec![host_with_open_port(number)];
+        ServiceDetector::new(SignatureDb::embedded(), 4, ScanState::new())
+            .unwrap()
+            .detect(&mut hosts)
+            .await;
+
+        let port: &Port = hosts[0].get_port(number, Protocol::Tcp).unwrap();
+        assert!(port.service.as_deref().unwrap().starts_with("ssl"));
+
+        let tls: &TlsInfo = port.tls.as_ref().unwrap();
+        assert_eq!(tls.version, "TLSv1.3");
+        let cert = tls.certificate.as_ref().unwrap();
+        assert_eq!(cert.sans, vec!["tls.zond.test".to_string()]);
+        assert_eq!(cert.key_type, "EC");
+        assert!(cert.is_self_signed());
+        assert_eq!(hosts[0].hostname.as_deref(), Some("tls.zond.test"));
+    }
+}
@@ -0,0 +1,471 @@
+//! Fetches the **landing page** of HTTP(S) services.
+//!
+//! Every page is requested on its own `Connection: close` connection, so responses
+//! simply end with the stream. Redirects are followed as long as they stay on the same
+//! host and port; a redirect elsewhere ends the fetch but is still recorded.
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use anyhow::bail;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+};
+use zond_common::models::http::HttpInfo;
+
+use super::tls::TlsInspector;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(5_000);
+const RESPONSE_TIMEOUT: Duration = Duration::from_millis(5_000);
+const MAX_RESPONSE_SIZE: usize = 256 * 1_024;
+const MAX_REDIRECTS: usize = 5;
+const MAX_HEADERS: usize = 64;
+const MAX_TITLE_CHARS: usize = 120;
+const USER_AGENT: &str = concat!("zond/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Http,
+    Https,
+}
+
+impl Scheme {
+    fn default_port(self) -> u16 {
+        match self {
+            Scheme::Http => 80,
+            Scheme::Https => 443,
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scheme::Http => write!(f, "http"),
+            Scheme::Https => write!(f, "https"),
+        }
+    }
+}
+
+/// Where requests go, and the name they are addressed to.
+struct Origin<'a> {
+    scheme: Scheme,
+    socket_addr: SocketAddr,
+    host: Option<&'a str>,
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// Requests landing pages and favicons over plain TCP or TLS.
+#[derive(Clone)]
+pub struct HttpFetcher {
+    tls: TlsInspector,
+}
+
+impl HttpFetcher {
+    pub fn new(tls: TlsInspector) -> Self {
+        Self { tls }
+    }
+
+    /// Fetches `/` (following same-origin redirects) and `/favicon.ico`.
+    ///
+    /// `host` is sent as `Host` header and SNI; the IP address is used without one.
+    pub async fn fetch(
+        &self,
+        scheme: Scheme,
+        socket_addr: SocketAddr,
+        host: Option<&str>,
+    ) -> anyhow::Result<HttpInfo> {
+        let origin: Origin = Origin {
+            scheme,
+            socket_addr,
+            host,
+        };
+
+        let mut path: String = "/".to_string();
+        let mut response: Response = self.get(&origin, &path).await?;
+        let mut redirects: Vec<String> = Vec::new();
+
+        while (300..400).contains(&response.status) && redirects.len() < MAX_REDIRECTS {
+            let Some(location) = response.header("location").map(str::to_string) else {
+                break;
+            };
+            let next: Option<String> = origin.resolve(&path, &location);
+            redirects.push(location);
+
+            let Some(next) = next else {
+                break;
+            };
+            match self.get(&origin, &next).await {
+                Ok(next_response) => response = next_response,
+                Err(_) => break,
+            }
+            path = next;
+        }
+
+        let favicon_hash: Option<i32> = match self.get(&origin, "/favicon.ico").await {
+            Ok(icon) if icon.status == 200 && !icon.body.is_empty() => {
+                Some(favicon_hash(&icon.body))
+            }
+            _ => None,
+        };
+
+        Ok(HttpInfo {
+            status: response.status,
+            title: extract_title(&response.body),
+            server: response.header("server").map(str::to_string),
+            powered_by: response.header("x-powered-by").map(str::to_string),
+            redirects,
+            favicon_hash,
+        })
+    }
+
+    async fn get(&self, origin: &Origin<'_>, path: &str) -> anyhow::Result<Response> {
+        let request: String = format!(
+            "GET {path} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {USER_AGENT}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+            origin.authority()
+        );
+
+        let raw: Vec<u8> = match origin.scheme {
+            Scheme::Http => {
+                let stream: TcpStream =
+                    timeout(CONNECT_TIMEOUT, TcpStream::connect(origin.socket_addr)).await??;
+                roundtrip(stream, request.as_bytes()).await?
+            }
+            Scheme::Https => {
+                let stream = timeout(
+                    CONNECT_TIMEOUT,
+                    self.tls.connect(origin.socket_addr, origin.host),
+                )
+                .await??;
+                roundtrip(stream, request.as_bytes()).await?
+            }
+        };
+
+        Response::parse(&raw)
+    }
+}
+
+impl Origin<'_> {
+    /// The `Host` header value, with the port only if it is not the scheme's default.
+    fn authority(&self) -> String {
+        let host: String = match self.host {
+            Some(name) => name.to_string(),
+            None if self.socket_addr.is_ipv6() => format!("[{}]", self.socket_addr.ip()),
+            None => self.socket_addr.ip().to_string(),
+        };
+
+        if self.socket_addr.port() == self.scheme.default_port() {
+            host
+        } else {
+            format!("{host}:{}", self.socket_addr.port())
+        }
+    }
+
+    /// Resolves a `Location` against the current path, `None` if it leaves this origin.
+    fn resolve(&self, path: &str, location: &str) -> Option<String> {
+        if location.starts_with("//") {
+            return self.resolve(path, &format!("{}:{location}", self.scheme));
+        }
+        if location.starts_with('/') {
+            return Some(location.to_string());
+        }
+
+        let Some((scheme, rest)) = location.split_once("://") else {
+            // Relative to the directory of the current path
+            let dir: &str = &path[..=path.rfind('/').unwrap_or(0)];
+            return Some(format!("{dir}{location}"));
+        };
+
+        let scheme: Scheme = match scheme.to_ascii_lowercase().as_str() {
+            "http" => Scheme::Http,
+            "https" => Scheme::Https,
+            _ => return None,
+        };
+        let (authority, next) = match rest.find(['/', '?', '#']) {
+            Some(idx) => (&rest[..idx], &rest[idx..]),
+            None => (rest, "/"),
+        };
+        let authority: &str = authority.rsplit('@').next().unwrap_or(authority);
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse::<u16>().ok()?),
+            _ => (authority, scheme.default_port()),
+        };
+        let host: &str = host.trim_start_matches('[').trim_end_matches(']');
+
+        let same_host: bool = self
+            .host
+            .is_some_and(|name| host.eq_ignore_ascii_case(name))
+            || host.parse::<IpAddr>().ok() == Some(self.socket_addr.ip());
+        let same_origin: bool =
+            scheme == self.scheme && port == self.socket_addr.port() && same_host;
+
+        same_origin.then(|| {
+            if next.starts_with('/') {
+                next.to_string()
+            } else {
+                format!("/{next}")
+            }
+        })
+    }
+}
+
+impl Response {
+    fn parse(raw: &[u8]) -> anyhow::Result<Self> {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut response = httparse::Response::new(&mut headers);
+        let httparse::Status::Complete(offset) = response.parse(raw)? else {
+            bail!("incomplete HTTP response");
+        };
+        let Some(status) = response.code else {
+            bail!("HTTP response without status code");
+        };
+
+        let headers: Vec<(String, String)> = response
+            .headers
+            .iter()
+            .map(|header| {
+                (
+                    header.name.to_ascii_lowercase(),
+                    String::from_utf8_lossy(header.value).trim().to_string(),
+                )
+            })
+            .collect();
+
+        let mut response: Self = Self {
+            status,
+            headers,
+            body: Vec::new(),
+        };
+
+        let body: &[u8] = &raw[offset..];
+        response.body = if response
+            .header("transfer-encoding")
+            .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+        {
+            dechunk(body)
+        } else {
+            match response
+                .header("content-length")
+                .and_then(|len| len.parse::<usize>().ok())
+            {
+                Some(len) => body[..len.min(body.len())].to_vec(),
+                None => body.to_vec(),
+            }
+        };
+
+        Ok(response)
+    }
+
+    /// The value of a header, looked up by its lowercase name.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// Sends the request and reads until the server closes the connection.
+async fn roundtrip<S>(mut stream: S, request: &[u8]) -> anyhow::Result<Vec<u8>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(request).await?;
+
+    let mut response: Vec<u8> = Vec::new();
+    let mut buf: [u8; 8_192] = [0; 8_192];
+
+    while response.len() < MAX_RESPONSE_SIZE {
+        match timeout(RESPONSE_TIMEOUT, stream.read(&mut buf)).await {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(n)) => response.extend_from_slice(&buf[..n]),
+            Ok(Err(e)) if response.is_empty() => return Err(e.into()),
+            // Servers often drop TLS connections without a close_notify
+            Ok(Err(_)) => break,
+        }
+    }
+
+    Ok(response)
+}
+
+/// Decodes a chunked body, keeping whatever arrived before a truncated chunk.
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut decoded: Vec<u8> = Vec::new();
+
+    while let Ok(httparse::Status::Complete((offset, size))) = httparse::parse_chunk_size(body) {
+        let size: usize = usize::try_from(size).unwrap_or(usize::MAX);
+        if size == 0 {
+            break;
+        }
+
+        body = &body[offset..];
+        let available: usize = size.min(body.len());
+        decoded.extend_from_slice(&body[..available]);
+        if available < size {
+            break;
+        }
+        body = body.get(size + 2..).unwrap_or_default();
+    }
+
+    decoded
+}
+
+fn extract_title(body: &[u8]) -> Option<String> {
+    let text: String = String::from_utf8_lossy(body).into_owned();
+    // ASCII lowercasing keeps byte offsets intact
+    let lower: String = text.to_ascii_lowercase();
+
+    let start: usize = lower.find("<title")?;
+    let content_start: usize = start + lower[start..].find('>')? + 1;
+    let content_end: usize = content_start + lower[content_start..].find("</title")?;
+
+    let title: String = decode_entities(
+        &text[content_start..content_end]
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" "),
+    )
+    .chars()
+    .take(MAX_TITLE_CHARS)
+    .collect();
+
+    (!title.is_empty()).then_some(title)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// MurmurHash3 of the favicon encoded like Python's `base64.encodebytes` (as Shodan does).
+fn favicon_hash(icon: &[u8]) -> i32 {
+    let encoded: String = STANDARD.encode(icon);
+    let mut wrapped: String = String::with_capacity(encoded.len() + encoded.len() / 76 + 1);
+    for line in encoded.as_bytes().chunks(76) {
+        wrapped.extend(line.iter().map(|&b| b as char));
+        wrapped.push('\n');
+    }
+
+    murmur3_32(wrapped.as_bytes(), 0) as i32
+}
+
+fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let scramble = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut hash: u32 = seed;
+    let mut blocks = data.chunks_exact(4);
+    for block in &mut blocks {
+        hash ^= scramble(u32::from_le_bytes([block[0], block[1], block[2], block[3]]));
+        hash = hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe654_6b64);
+    }
+
+    let tail: u32 = blocks
+        .remainder()
+        .iter()
+        .enumerate()
+        .fold(0, |k, (i, &b)| k | (u32::from(b) << (8 * i)));
+    if !blocks.remainder().is_empty() {
+        hash ^= scramble(tail);
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    fn origin(port: u16) -> Origin<'static> {
+        Origin {
+            scheme: Scheme::Http,
+            socket_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), port),
+            host: None,
+        }
+    }
+
+    #[test]
+    fn murmur3_matches_reference_values() {
+        assert_eq!(murmur3_32(b"", 0), 0);
+        assert_eq!(murmur3_32(b"hello", 0), 0x248b_fa47);
+        assert_eq!(
+            murmur3_32(b"The quick brown fox jumps over the lazy dog", 0),
+            0x2e4f_f723
+        );
+    }
+
+    #[test]
+    fn title_is_trimmed_and_decoded() {
+        let body: &[u8] = b"<html><HEAD><Title lang=\"en\">\n  Login &amp;\n Setup </title></head>";
+        assert_eq!(extract_title(body).as_deref(), Some("Login & Setup"));
+        assert_eq!(extract_title(b"<title></title>"), None);
+        assert_eq!(extract_title(b"<html>no title</html>"), None);
+    }
+
+    #[test]
+    fn chunked_bodies_are_decoded() {
+        let raw: &[u8] = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nServer: test\r\n\r\n5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\n\r\n";
+        let response: Response = Response::parse(raw).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("server"), Some("test"));
+        assert_eq!(response.body, b"hello world");
+    }
+
+    #[test]
+    fn redirects_are_followed_only_on_the_same_origin() {
+        let origin: Origin = origin(8080);
+        assert_eq!(origin.resolve("/", "/login").as_deref(), Some("/login"));
+        assert_eq!(
+            origin.resolve("/admin/", "index.php").as_deref(),
+            Some("/admin/index.php")
+        );
+        assert_eq!(
+            origin
+                .resolve("/", "http://192.0.2.1:8080/app?x=1")
+                .as_deref(),
+            Some("/app?x=1")
+        );
+        assert_eq!(
+            origin.resolve("/", "http://192.0.2.1:8080").as_deref(),
+            Some("/")
+        );
+        assert_eq!(origin.resolve("/", "https://192.0.2.1:8080/"), None);
+        assert_eq!(origin.resolve("/", "http://example.com/"), None);
+    }
+}
@@ -0,0 +1,486 @@
+//! The **service signature database** used by service detection.
+//!
+//! Signatures ship inside the binary (`data/signatures.txt`) and can be extended with
+//! user files in the same format. See the header of the embedded file for the syntax.
+
+use std::{ops::RangeInclusive, path::Path};
+
+use anyhow::{Context, bail, ensure};
+use regex::bytes::{Captures, Regex, RegexBuilder};
+
+const SIGNATURES_TXT: &str = include_str!("../../../data/signatures.txt");
+
+/// A payload sent to a port, together with the signatures for its responses.
+#[derive(Debug)]
+pub struct Probe {
+    pub name: String,
+    pub payload: Vec<u8>,
+    ports: Vec<RangeInclusive<u16>>,
+    fallback: bool,
+    signatures: Vec<Signature>,
+}
+
+#[derive(Debug)]
+struct Signature {
+    service: String,
+    pattern: Regex,
+    product: Option<String>,
+    version: Option<String>,
+    info: Option<String>,
+}
+
+/// What a matching signature says about a service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceMatch {
+    pub service: String,
+    pub product: Option<String>,
+    pub version: Option<String>,
+    pub info: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct SignatureDb {
+    probes: Vec<Probe>,
+}
+
+impl Probe {
+    /// Returns `true` if the probe was written for this port.
+    pub fn targets(&self, port: u16) -> bool {
+        self.ports.iter().any(|range| range.contains(&port))
+    }
+
+    /// Returns `true` if the probe is worth a try on ports no other probe claimed.
+    pub fn is_fallback(&self) -> bool {
+        self.fallback
+    }
+
+    fn is_null(&self) -> bool {
+        self.payload.is_empty()
+    }
+}
+
+impl Signature {
+    fn apply(&self, response: &[u8]) -> Option<ServiceMatch> {
+        let captures: Captures = self.pattern.captures(response)?;
+        let expand = |template: &Option<String>| {
+            template
+                .as_deref()
+                .map(|template| expand_template(template, &captures))
+                .filter(|value| !value.is_empty())
+        };
+
+        Some(ServiceMatch {
+            service: self.service.clone(),
+            product: expand(&self.product),
+            version: expand(&self.version),
+            info: expand(&self.info),
+        })
+    }
+}
+
+impl ServiceMatch {
+    /// Returns `true` if the response came from a TLS server.
+    pub fn is_tls(&self) -> bool {
+        matches!(self.service.as_str(), "ssl" | "https")
+    }
+
+    /// Returns `true` if the response came from a plain HTTP server.
+    pub fn is_http(&self) -> bool {
+        matches!(self.service.as_str(), "http" | "elasticsearch")
+    }
+
+    /// Joins product, version and info into one line (e.g. `OpenSSH 9.6p1 (protocol 2.0)`).
+    pub fn describe(&self) -> Option<String> {
+        let mut parts: Vec<&str> = Vec::new();
+        parts.extend(self.product.as_deref());
+        parts.extend(self.version.as_deref());
+
+        let mut description: String = parts.join(" ");
+        if let Some(info) = &self.info {
+            if description.is_empty() {
+                description = info.clone();
+            } else {
+                description.push_str(&format!(" ({info})"));
+            }
+        }
+
+        (!description.is_empty()).then_some(description)
+    }
+}
+
+impl SignatureDb {
+    /// The signatures that ship with zond.
+    pub fn embedded() -> Self {
+        Self::parse(SIGNATURES_TXT).expect("failed to load embedded service signatures")
+    }
+
+    /// The embedded signatures, preceded by the signatures of a user file.
+    ///
+    /// User signatures for an existing probe are tried before the embedded ones,
+    /// so they can refine or override what ships with zond.
+    pub fn with_user_file(path: &Path) -> anyhow::Result<Self> {
+        let text: String = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read signatures from {}", path.display()))?;
+        let mut db: Self = Self::parse(&text)
+            .with_context(|| format!("invalid signature file {}", path.display()))?;
+        db.extend(Self::embedded());
+        Ok(db)
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut probes: Vec<Probe> = Vec::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match keyword {
+                "probe" => {
+                    probes.push(parse_probe(rest).with_context(|| format!("line {}", idx + 1))?)
+                }
+                "match" => {
+                    let Some(probe) = probes.last_mut() else {
+                        bail!("line {}: signature outside of a probe", idx + 1);
+                    };
+                    let signature: Signature =
+                        parse_signature(rest).with_context(|| format!("line {}", idx + 1))?;
+                    probe.signatures.push(signature);
+                }
+                other => bail!("line {}: unknown directive '{other}'", idx + 1),
+            }
+        }
+
+        Ok(Self { probes })
+    }
+
+    /// Appends the probes of `other`, merging signatures into probes of the same name.
+    pub fn extend(&mut self, other: SignatureDb) {
+        for probe in other.probes {
+            match self.probes.iter_mut().find(|p| p.name == probe.name) {
+                Some(existing) => existing.signatures.extend(probe.signatures),
+                None => self.probes.push(probe),
+            }
+        }
+    }
+
+    /// The probes worth sending to a port, in the order they should be tried.
+    ///
+    /// The NULL probe always comes first, followed by the probes written for the port.
+    /// Fallback probes are only tried if no probe claimed the port.
+    pub fn probes_for(&self, port: u16) -> Vec<&Probe> {
+        let null = self.probes.iter().filter(|p| p.is_null());
+        let targeted: Vec<&Probe> = self
+            .probes
+            .iter()
+            .filter(|p| !p.is_null() && p.targets(port))
+            .collect();
+
+        let mut probes: Vec<&Probe> = null.collect();
+        if targeted.is_empty() {
+            probes.extend(
+                self.probes
+                    .iter()
+                    .filter(|p| !p.is_null() && p.is_fallback()),
+            );
+        } else {
+            probes.extend(targeted);
+        }
+        probes
+    }
+
+    /// Matches a response to `probe`, checking the greeting signatures of the NULL probe first.
+    pub fn identify(&self, probe: &Probe, response: &[u8]) -> Option<ServiceMatch> {
+        if response.is_empty() {
+            return None;
+        }
+
+        self.probes
+            .iter()
+            .filter(|p| p.is_null() && p.name != probe.name)
+            .chain(std::iter::once(probe))
+            .flat_map(|p| p.signatures.iter())
+            .find_map(|signature| signature.apply(response))
+    }
+}
+
+fn parse_probe(line: &str) -> anyhow::Result<Probe> {
+    let mut fields = line.splitn(3, ' ');
+    let (Some(name), Some(ports), Some(payload)) = (fields.next(), fields.next(), fields.next())
+    else {
+        bail!("expected 'probe <name> <ports> q|<payload>|'");
+    };
+
+    let mut port_ranges: Vec<RangeInclusive<u16>> = Vec::new();
+    let mut fallback: bool = false;
+    for part in ports.split(',') {
+        if part == "*" {
+            fallback = true;
+            continue;
+        }
+        let range: RangeInclusive<u16> = match part.split_once('-') {
+            Some((start, end)) => start.parse()?..=end.parse()?,
+            None => {
+                let port: u16 = part
+                    .parse()
+                    .with_context(|| format!("invalid port '{part}'"))?;
+                port..=port
+            }
+        };
+        port_ranges.push(range);
+    }
+
+    let (payload, rest) = take_delimited(payload.trim(), 'q')?;
+    ensure!(rest.trim().is_empty(), "unexpected text after payload");
+
+    Ok(Probe {
+        name: name.to_string(),
+        payload: unescape(payload)?,
+        ports: port_ranges,
+        fallback,
+        signatures: Vec::new(),
+    })
+}
+
+fn parse_signature(line: &str) -> anyhow::Result<Signature> {
+    let Some((service, rest)) = line.split_once(' ') else {
+        bail!("expected 'match <service> m|<regex>|'");
+    };
+
+    let (pattern, rest) = take_delimited(rest.trim(), 'm')?;
+    let flags: &str = rest.split(' ').next().unwrap_or_default();
+    let mut rest: &str = rest[flags.len()..].trim_start();
+
+    let pattern: Regex = RegexBuilder::new(pattern)
+        .unicode(false)
+        .case_insensitive(flags.contains('i'))
+        .dot_matches_new_line(flags.contains('s'))
+        .build()
+        .with_context(|| format!("invalid regex '{pattern}'"))?;
+
+    let mut signature = Signature {
+        service: service.to_string(),
+        pattern,
+        product: None,
+        version: None,
+        info: None,
+    };
+
+    while !rest.is_empty() {
+        let field: char = rest.chars().next().unwrap_or_default();
+        let (value, remaining) = take_delimited(rest, field)?;
+        let target: &mut Option<String> = match field {
+            'p' => &mut signature.product,
+            'v' => &mut signature.version,
+            'i' => &mut signature.info,
+            other => bail!("unknown field '{other}'"),
+        };
+        *target = Some(value.to_string());
+        rest = remaining.trim_start();
+    }
+
+    Ok(signature)
+}
+
+/// Splits `<prefix><d>value<d>rest` into value and rest, for any delimiter `d`.
+fn take_delimited(text: &str, prefix: char) -> anyhow::Result<(&str, &str)> {
+    let Some(text) = text.strip_prefix(prefix) else {
+        bail!("expected '{prefix}' in '{text}'");
+    };
+    let Some(delimiter) = text.chars().next() else {
+        bail!("missing delimiter after '{prefix}'");
+    };
+
+    let text: &str = &text[delimiter.len_utf8()..];
+    let Some(end) = text.find(delimiter) else {
+        bail!("unterminated '{prefix}{delimiter}'");
+    };
+    Ok((&text[..end], &text[end + delimiter.len_utf8()..]))
+}
+
+fn unescape(payload: &str) -> anyhow::Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(payload.len());
+    let mut chars = payload.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf: [u8; 4] = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte: u8 = u8::from_str_radix(&hex, 16)
+                    .with_context(|| format!("invalid escape '\\x{hex}'"))?;
+                bytes.push(byte);
+            }
+            Some(other) => bail!("invalid escape '\\{other}'"),
+            None => bail!("payload ends with a lone '\\'"),
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Replaces `$1` to `$9` with the captured groups, printed lossily as UTF-8.
+fn expand_template(template: &str, captures: &Captures) -> String {
+    let mut expanded: String = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let group: Option<usize> = match (c, chars.peek()) {
+            ('$', Some(digit)) => digit.to_digit(10).map(|d| d as usize),
+            _ => None,
+        };
+
+        match group {
+            Some(group) => {
+                chars.next();
+                if let Some(capture) = captures.get(group) {
+                    expanded.push_str(&String::from_utf8_lossy(capture.as_bytes()));
+                }
+            }
+            None => expanded.push(c),
+        }
+    }
+
+    expanded.trim().to_string()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identify(probe_name: &str, response: &[u8]) -> Option<ServiceMatch> {
+        let db: SignatureDb = SignatureDb::embedded();
+        let probe: &Probe = db.probes.iter().find(|p| p.name == probe_name).unwrap();
+        db.identify(probe, response)
+    }
+
+    #[test]
+    fn embedded_database_parses() {
+        let db: SignatureDb = SignatureDb::embedded();
+        assert!(db.probes.iter().any(|p| p.is_null()));
+        assert!(db.probes.iter().all(|p| !p.signatures.is_empty()));
+    }
+
+    #[test]
+    fn null_probe_identifies_openssh() {
+        let found: ServiceMatch =
+            identify("NULL", b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13.5\r\n").unwrap();
+        assert_eq!(found.service, "ssh");
+        assert_eq!(found.version.as_deref(), Some("9.6p1"));
+        assert_eq!(
+            found.describe().as_deref(),
+            Some("OpenSSH 9.6p1 (Ubuntu Linux; protocol 2.0)")
+        );
+    }
+
+    #[test]
+    fn http_probe_reads_server_header() {
+        let response: &[u8] =
+            b"HTTP/1.1 200 OK\r\nDate: Thu, 01 Jan 2026 00:00:00 GMT\r\nServer: nginx/1.24.0\r\n\r\n";
+        let found: ServiceMatch = identify("GetRequest", response).unwrap();
+        assert_eq!(found.service, "http");
+        assert_eq!(found.describe().as_deref(), Some("nginx 1.24.0"));
+    }
+
+    #[test]
+    fn binary_signatures_match_raw_bytes() {
+        let greeting: &[u8] = b"J\x00\x00\x00\x0a8.0.36\x00\x08\x00\x00\x00";
+        let found: ServiceMatch = identify("NULL", greeting).unwrap();
+        assert_eq!(found.service, "mysql");
+        assert_eq!(found.version.as_deref(), Some("8.0.36"));
+
+        let server_hello: &[u8] = b"\x16\x03\x03\x00\x5a\x02\x00\x00\x56\x03\x03";
+        assert_eq!(
+            identify("TLSSessionReq", server_hello).unwrap().service,
+            "ssl"
+        );
+    }
+
+    #[test]
+    fn probe_specific_signatures_see_greetings_first() {
+        let response: &[u8] =
+            b"220 mail.example.com ESMTP Postfix\r\n250-mail.example.com\r\n250 SMTPUTF8\r\n";
+        let found: ServiceMatch = identify("SMTPEhlo", response).unwrap();
+        assert_eq!(found.product.as_deref(), Some("Postfix smtpd"));
+    }
+
+    #[test]
+    fn unknown_response_has_no_match() {
+        assert_eq!(identify("GetRequest", b"\x00\x01garbage"), None);
+        assert_eq!(identify("NULL", b""), None);
+    }
+
+    #[test]
+    fn probes_for_prefers_targeted_probes() {
+        let db: SignatureDb = SignatureDb::embedded();
+
+        let redis: Vec<&str> = db
+            .probes_for(6379)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(redis, vec!["NULL", "RedisPing"]);
+
+        let unknown: Vec<&str> = db
+            .probes_for(31337)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(unknown, vec!["NULL", "TLSSessionReq", "GetRequest"]);
+    }
+
+    #[test]
+    fn user_signatures_take_precedence() {
+        let mut db: SignatureDb =
+            SignatureDb::parse("probe GetRequest 80 q|GET / HTTP/1.0\\r\\n\\r\\n|\nmatch intranet m|^HTTP/1\\.1 200 .*?X-Intranet: (\\d+)|s p/Intranet portal/ v/$1/\n")
+                .unwrap();
+        db.extend(SignatureDb::embedded());
+
+        let probe: &Probe = db.probes.iter().find(|p| p.name == "GetRequest").unwrap();
+        let found: ServiceMatch = db
+            .identify(
+                probe,
+                b"HTTP/1.1 200 OK\r\nServer: nginx\r\nX-Intranet: 3\r\n\r\n",
+            )
+            .unwrap();
+        assert_eq!(found.service, "intranet");
+        assert_eq!(found.version.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_lines() {
+        assert!(SignatureDb::parse("match ssh m|^SSH|").is_err());
+        assert!(SignatureDb::parse("probe Broken 80 q|\\xZZ|").is_err());
+        assert!(SignatureDb::parse("probe Open 80 q|GET").is_err());
+        assert!(SignatureDb::parse("probe A 80 q||\nmatch x m|(|").is_err());
+    }
+
+    #[test]
+    fn unescape_handles_hex_and_control_characters() {
+        assert_eq!(
+            unescape("*1\\r\\n\\x00\\\\").unwrap(),
+            b"*1\r\n\x00\\".to_vec()
+        );
+    }
+}
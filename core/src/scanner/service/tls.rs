@@ -0,0 +1,215 @@
+//! Completes **TLS handshakes** to harvest the negotiated parameters and the leaf certificate.
+//!
+//! Certificates are accepted without any verification: expired, self-signed or mismatched
+//! certificates are exactly what an inventory of TLS services needs to see.
+//! Servers that only speak protocol versions or ciphers unsupported by rustls
+//! (SSLv3, TLS 1.0/1.1, export ciphers) fail the handshake and yield nothing.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, SignatureScheme,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{CryptoProvider, ring},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+};
+use tokio::{net::TcpStream, time::timeout};
+use tokio_rustls::{TlsConnector, client::TlsStream};
+use x509_parser::{
+    certificate::X509Certificate,
+    extensions::GeneralName,
+    oid_registry::{OID_SIG_ED448, OID_SIG_ED25519},
+    public_key::PublicKey,
+};
+use zond_common::models::tls::{Certificate, TlsInfo};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(5_000);
+
+/// Opens TLS sessions that accept whatever certificate the server presents.
+#[derive(Clone)]
+pub struct TlsInspector {
+    connector: TlsConnector,
+}
+
+impl TlsInspector {
+    pub fn new() -> anyhow::Result<Self> {
+        let provider: Arc<CryptoProvider> = Arc::new(ring::default_provider());
+        let config: ClientConfig = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+            .with_no_client_auth();
+
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(config)),
+        })
+    }
+
+    /// Connects and completes a handshake, sending `server_name` as SNI if given.
+    pub async fn inspect(
+        &self,
+        socket_addr: SocketAddr,
+        server_name: Option<&str>,
+    ) -> anyhow::Result<TlsInfo> {
+        let stream: TlsStream<TcpStream> =
+            timeout(HANDSHAKE_TIMEOUT, self.connect(socket_addr, server_name))
+                .await
+                .context("TLS handshake timed out")??;
+
+        Ok(session_info(&stream))
+    }
+
+    /// Connects and completes a handshake, returning the stream for further use.
+    pub async fn connect(
+        &self,
+        socket_addr: SocketAddr,
+        server_name: Option<&str>,
+    ) -> anyhow::Result<TlsStream<TcpStream>> {
+        let name: ServerName<'static> = server_name
+            .and_then(|name| ServerName::try_from(name.to_string()).ok())
+            .unwrap_or(ServerName::IpAddress(socket_addr.ip().into()));
+
+        let tcp: TcpStream = TcpStream::connect(socket_addr).await?;
+        Ok(self.connector.connect(name, tcp).await?)
+    }
+}
+
+/// The negotiated parameters and leaf certificate of an established session.
+pub fn session_info(stream: &TlsStream<TcpStream>) -> TlsInfo {
+    let (_, session) = stream.get_ref();
+
+    let version: String = session
+        .protocol_version()
+        .map(|version| format!("{version:?}").replace('_', "."))
+        .unwrap_or_default();
+    let cipher: String = session
+        .negotiated_cipher_suite()
+        .map(|suite| format!("{:?}", suite.suite()))
+        .unwrap_or_default();
+    let certificate: Option<Certificate> = session
+        .peer_certificates()
+        .and_then(|chain| chain.first())
+        .and_then(|leaf| parse_certificate(leaf));
+
+    TlsInfo {
+        version,
+        cipher,
+        certificate,
+    }
+}
+
+fn parse_certificate(der: &CertificateDer) -> Option<Certificate> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der).ok()?;
+
+    let sans: Vec<String> = match cert.subject_alternative_name() {
+        Ok(Some(extension)) => extension
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => ip_from_bytes(bytes).map(|ip| ip.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let (key_type, key_bits) = describe_key(&cert);
+
+    Some(Certificate {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        sans,
+        not_before: to_system_time(cert.validity().not_before.timestamp()),
+        not_after: to_system_time(cert.validity().not_after.timestamp()),
+        key_type,
+        key_bits,
+    })
+}
+
+fn describe_key(cert: &X509Certificate) -> (String, Option<usize>) {
+    let algorithm = &cert.public_key().algorithm.algorithm;
+    if *algorithm == OID_SIG_ED25519 {
+        return ("Ed25519".to_string(), Some(256));
+    }
+    if *algorithm == OID_SIG_ED448 {
+        return ("Ed448".to_string(), Some(456));
+    }
+
+    match cert.public_key().parsed() {
+        Ok(PublicKey::RSA(rsa)) => ("RSA".to_string(), Some(rsa.key_size())),
+        Ok(PublicKey::EC(ec)) => ("EC".to_string(), Some(ec.key_size())),
+        Ok(PublicKey::DSA(_)) => ("DSA".to_string(), None),
+        _ => (algorithm.to_id_string(), None),
+    }
+}
+
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+fn to_system_time(timestamp: i64) -> SystemTime {
+    match u64::try_from(timestamp) {
+        Ok(secs) => SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+        Err(_) => SystemTime::UNIX_EPOCH - Duration::from_secs(timestamp.unsigned_abs()),
+    }
+}
+
+/// Skips certificate validation; signatures are still checked so the handshake stays sound.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
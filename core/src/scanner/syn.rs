@@ -0,0 +1,328 @@
+//! A raw-socket **TCP SYN** port scanner.
+//!
+//! Sends a bare SYN to every target/port pair and sorts each port by the answer:
+//! * **Open**: the target answered with a SYN/ACK.
+//! * **Closed**: the target answered with a RST.
+//! * **Filtered**: no answer after all retries, or an ICMP unreachable came back.
+//!
+//! The handshake is never completed; the kernel resets the half-open session on its own.
+//! This scanner requires **root privileges** to open raw Layer 4 sockets.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use pnet::{
+    packet::{Packet, ip::IpNextHeaderProtocols, ipv4::Ipv4Packet, tcp::TcpPacket},
+    transport::TransportSender,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use zond_common::{
+    error, info, interface,
+    models::{
+        host::Host,
+        port::{Port, PortReason, PortState, Protocol},
+        range::IpCollection,
+    },
+    warn,
+};
+use zond_protocols::tcp;
+
+use crate::network::transport::{self, TransportHandle, TransportType};
+
+use super::{
+    EventTx, NetworkExplorer, ScanState,
+    port_results::{self, PortResults, Probe, ProbeIter},
+};
+
+const SEND_INTERVAL: Duration = Duration::from_millis(1);
+const PROBES_PER_TICK: usize = 32;
+const MAX_SILENCE: Duration = Duration::from_millis(1_000);
+const MAX_RETRIES: u8 = 1;
+
+struct PendingProbe {
+    seq_num: u32,
+    sent_at: Instant,
+    attempts: u8,
+}
+
+pub struct SynScanner {
+    src_port: u16,
+    sources: HashMap<IpAddr, IpAddr>,
+    targets: Vec<IpAddr>,
+    ports: Vec<u16>,
+    tcp_tx: Arc<Mutex<TransportSender>>,
+    tcp_handle: TransportHandle,
+    icmp_handle: TransportHandle,
+    state: Arc<ScanState>,
+    pending: HashMap<Probe, PendingProbe>,
+    results: PortResults,
+}
+
+#[async_trait]
+impl NetworkExplorer for SynScanner {
+    async fn discover_hosts(&mut self) -> anyhow::Result<Vec<Host>> {
+        let mut probe_iter: ProbeIter =
+            port_results::interleave(self.targets.clone(), self.ports.clone());
+        let mut retry_queue: VecDeque<Probe> = VecDeque::new();
+        let mut retry_round: u8 = 0;
+        let mut sending_finished: bool = false;
+        let mut last_activity: Instant = Instant::now();
+
+        let mut send_interval = tokio::time::interval(SEND_INTERVAL);
+
+        loop {
+            if self.state.is_stopped() {
+                break;
+            }
+
+            if sending_finished {
+                if self.pending.is_empty() {
+                    break;
+                }
+                if last_activity.elapsed() >= MAX_SILENCE {
+                    if retry_round >= MAX_RETRIES {
+                        break;
+                    }
+                    retry_round += 1;
+                    retry_queue.extend(self.pending.keys().copied());
+                    info!(
+                        verbosity = 2,
+                        "Retransmitting {} unanswered probes",
+                        retry_queue.len()
+                    );
+                    sending_finished = false;
+                }
+            }
+
+            let silence_left: Duration = MAX_SILENCE.saturating_sub(last_activity.elapsed());
+
+            tokio::select! {
+                res = self.tcp_handle.rx.recv() => {
+                    match res {
+                        Some((bytes, ip)) => {
+                            if self.process_tcp_packet(&bytes, ip) {
+                                last_activity = Instant::now();
+                            }
+                        }
+                        None => break,
+                    }
+                }
+
+                res = self.icmp_handle.rx.recv() => {
+                    if let Some((bytes, ip)) = res
+                        && self.process_icmp_packet(&bytes, ip)
+                    {
+                        last_activity = Instant::now();
+                    }
+                }
+
+                _ = send_interval.tick(), if !sending_finished => {
+                    for _ in 0..PROBES_PER_TICK {
+                        let next = retry_queue.pop_front().or_else(|| probe_iter.next());
+                        match next {
+                            Some((dst_addr, dst_port)) => self.send_probe(dst_addr, dst_port),
+                            None => {
+                                sending_finished = true;
+                                break;
+                            }
+                        }
+                    }
+                    last_activity = Instant::now();
+                }
+
+                _ = tokio::time::sleep(silence_left), if sending_finished => {}
+            }
+        }
+
+        let unanswered: Vec<Probe> = self.pending.drain().map(|(probe, _)| probe).collect();
+        for probe in unanswered {
+            self.record(probe, PortState::Filtered, PortReason::Timeout, None);
+        }
+
+        info!(
+            verbosity = 1,
+            "SYN scan finished: {} open, {} closed, {} filtered",
+            self.results.count(PortState::Open),
+            self.results.count(PortState::Closed),
+            self.results.count(PortState::Filtered)
+        );
+
+        Ok(self.results.drain_hosts())
+    }
+}
+
+impl SynScanner {
+    pub fn new(
+        targets: IpCollection,
+        ports: Vec<u16>,
+        dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
+    ) -> anyhow::Result<Self> {
+        // Probes go out through layer 4, answers are read with their IPv4 header for the TTL
+        let tcp_tx: Arc<Mutex<TransportSender>> = transport::open_sender(TransportType::TcpLayer4)?;
+        let tcp_handle: TransportHandle =
+            transport::start_packet_capture(TransportType::TcpLayer3)?;
+        let icmp_handle: TransportHandle =
+            transport::start_packet_capture(TransportType::IcmpLayer4)?;
+
+        let mut sources: HashMap<IpAddr, IpAddr> = HashMap::new();
+        let mut skipped_v6: usize = 0;
+
+        for dst_addr in targets.iter() {
+            if dst_addr.is_ipv6() {
+                skipped_v6 += 1;
+                continue;
+            }
+            match interface::get_route_source_addr(dst_addr) {
+                Some(src_addr) => _ = sources.insert(dst_addr, src_addr),
+                None => warn!("No route to {dst_addr}, skipping"),
+            }
+        }
+
+        if skipped_v6 > 0 {
+            warn!("Skipping {skipped_v6} IPv6 targets, SYN scanning is IPv4 only");
+        }
+
+        let mut target_list: Vec<IpAddr> = sources.keys().copied().collect();
+        target_list.sort();
+
+        Ok(Self {
+            src_port: rand::random_range(50_000..u16::MAX),
+            sources,
+            targets: target_list,
+            ports,
+            tcp_tx,
+            tcp_handle,
+            icmp_handle,
+            results: PortResults::new(dns_tx, state.clone()),
+            state,
+            pending: HashMap::new(),
+        })
+    }
+
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.results.set_events(events);
+        self
+    }
+
+    fn send_probe(&mut self, dst_addr: IpAddr, dst_port: u16) {
+        let Some(src_addr) = self.sources.get(&dst_addr).copied() else {
+            return;
+        };
+
+        let seq_num: u32 = rand::random();
+        let packet: Vec<u8> =
+            match tcp::create_packet(&src_addr, &dst_addr, self.src_port, dst_port, seq_num) {
+                Ok(packet) => packet,
+                Err(e) => {
+                    error!(
+                        verbosity = 2,
+                        "Failed to build SYN for {dst_addr}:{dst_port}: {e}"
+                    );
+                    return;
+                }
+            };
+
+        let Some(tcp_packet) = TcpPacket::new(&packet) else {
+            return;
+        };
+
+        let mut tx = self.tcp_tx.lock().unwrap();
+        match tx.send_to(tcp_packet, dst_addr) {
+            Ok(_) => {
+                let attempts: u8 = self
+                    .pending
+                    .get(&(dst_addr, dst_port))
+                    .map_or(0, |probe| probe.attempts + 1);
+
+                self.pending.insert(
+                    (dst_addr, dst_port),
+                    PendingProbe {
+                        seq_num,
+                        sent_at: Instant::now(),
+                        attempts,
+                    },
+                );
+            }
+            Err(e) => error!(
+                verbosity = 2,
+                "Failed to send SYN to {dst_addr}:{dst_port}: {e}"
+            ),
+        }
+    }
+
+    /// Returns `true` if the segment answered one of our probes.
+    fn process_tcp_packet(&mut self, bytes: &[u8], src_addr: IpAddr) -> bool {
+        let Some(ipv4_packet) = Ipv4Packet::new(bytes) else {
+            return false;
+        };
+        let Some(tcp_packet) = TcpPacket::new(ipv4_packet.payload()) else {
+            return false;
+        };
+
+        if tcp_packet.get_destination() != self.src_port {
+            return false;
+        }
+
+        let probe: Probe = (src_addr, tcp_packet.get_source());
+        let Some(pending) = self.pending.get(&probe) else {
+            return false;
+        };
+
+        if tcp::has_ack(&tcp_packet)
+            && tcp_packet.get_acknowledgement() != pending.seq_num.wrapping_add(1)
+        {
+            return false;
+        }
+
+        let (state, reason) = if tcp::is_syn_ack(&tcp_packet) {
+            (PortState::Open, PortReason::SynAck)
+        } else if tcp::is_rst(&tcp_packet) {
+            (PortState::Closed, PortReason::Rst)
+        } else {
+            return false;
+        };
+
+        let rtt: Duration = pending.sent_at.elapsed();
+        self.pending.remove(&probe);
+
+        let port: Port = Port::new(probe.1, Protocol::Tcp, state, reason)
+            .with_ttl(ipv4_packet.get_ttl())
+            .with_window(tcp_packet.get_window());
+        self.results.record(probe.0, port, Some(rtt));
+        true
+    }
+
+    /// Returns `true` if the ICMP message referred to one of our probes.
+    fn process_icmp_packet(&mut self, bytes: &[u8], src_addr: IpAddr) -> bool {
+        let Some((probe, code)) =
+            port_results::unreachable_probe(bytes, IpNextHeaderProtocols::Tcp, self.src_port)
+        else {
+            return false;
+        };
+        let Some(pending) = self.pending.remove(&probe) else {
+            return false;
+        };
+
+        let rtt: Option<Duration> = port_results::icmp_rtt(src_addr, probe, pending.sent_at);
+        let reason: PortReason = PortReason::Icmp { icmp_type: 3, code };
+        self.record(probe, PortState::Filtered, reason, rtt);
+        true
+    }
+
+    fn record(
+        &mut self,
+        (ip, number): Probe,
+        state: PortState,
+        reason: PortReason,
+        rtt: Option<Duration>,
+    ) {
+        let port: Port = Port::new(number, Protocol::Tcp, state, reason);
+        self.results.record(ip, port, rtt);
+    }
+}
@@ -0,0 +1,273 @@
+//! A raw-socket **UDP** port scanner.
+//!
+//! Sends a protocol-specific payload (see [`zond_protocols::payloads`]) to every
+//! target/port pair and sorts each port by the answer:
+//! * **Open**: the target answered with a datagram from the probed port.
+//! * **Closed**: an ICMP port unreachable came back from the target.
+//! * **Filtered**: any other ICMP unreachable came back.
+//! * **Open|Filtered**: no answer after all retries.
+//!
+//! This scanner requires **root privileges** to open raw Layer 4 sockets.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use pnet::packet::{ip::IpNextHeaderProtocols, udp::UdpPacket};
+use tokio::sync::mpsc::UnboundedSender;
+use zond_common::{
+    error, info,
+    models::{
+        host::Host,
+        port::{Port, PortReason, PortState, Protocol},
+        range::IpCollection,
+    },
+    warn,
+};
+use zond_protocols::{payloads, udp};
+
+use crate::network::transport::{self, TransportHandle, TransportType};
+
+use super::{
+    EventTx, NetworkExplorer, ScanState,
+    port_results::{self, PortResults, Probe, ProbeIter},
+};
+
+const SEND_INTERVAL: Duration = Duration::from_millis(1);
+const PROBES_PER_TICK: usize = 8;
+const MAX_SILENCE: Duration = Duration::from_millis(1_500);
+const MAX_RETRIES: u8 = 1;
+const ICMP_PORT_UNREACHABLE: u8 = 3;
+
+pub struct UdpScanner {
+    src_port: u16,
+    targets: Vec<IpAddr>,
+    ports: Vec<u16>,
+    udp_handle: TransportHandle,
+    icmp_handle: TransportHandle,
+    state: Arc<ScanState>,
+    pending: HashMap<Probe, Instant>,
+    results: PortResults,
+}
+
+#[async_trait]
+impl NetworkExplorer for UdpScanner {
+    async fn discover_hosts(&mut self) -> anyhow::Result<Vec<Host>> {
+        let mut probe_iter: ProbeIter =
+            port_results::interleave(self.targets.clone(), self.ports.clone());
+        let mut retry_queue: VecDeque<Probe> = VecDeque::new();
+        let mut retry_round: u8 = 0;
+        let mut sending_finished: bool = false;
+        let mut last_activity: Instant = Instant::now();
+
+        let mut send_interval = tokio::time::interval(SEND_INTERVAL);
+
+        loop {
+            if self.state.is_stopped() {
+                break;
+            }
+
+            if sending_finished {
+                if self.pending.is_empty() {
+                    break;
+                }
+                if last_activity.elapsed() >= MAX_SILENCE {
+                    if retry_round >= MAX_RETRIES {
+                        break;
+                    }
+                    retry_round += 1;
+                    retry_queue.extend(self.pending.keys().copied());
+                    info!(
+                        verbosity = 2,
+                        "Retransmitting {} unanswered probes",
+                        retry_queue.len()
+                    );
+                    sending_finished = false;
+                }
+            }
+
+            let silence_left: Duration = MAX_SILENCE.saturating_sub(last_activity.elapsed());
+
+            tokio::select! {
+                res = self.udp_handle.rx.recv() => {
+                    match res {
+                        Some((bytes, ip)) => {
+                            if self.process_udp_packet(&bytes, ip) {
+                                last_activity = Instant::now();
+                            }
+                        }
+                        None => break,
+                    }
+                }
+
+                res = self.icmp_handle.rx.recv() => {
+                    if let Some((bytes, ip)) = res
+                        && self.process_icmp_packet(&bytes, ip)
+                    {
+                        last_activity = Instant::now();
+                    }
+                }
+
+                _ = send_interval.tick(), if !sending_finished => {
+                    for _ in 0..PROBES_PER_TICK {
+                        let next = retry_queue.pop_front().or_else(|| probe_iter.next());
+                        match next {
+                            Some((dst_addr, dst_port)) => self.send_probe(dst_addr, dst_port),
+                            None => {
+                                sending_finished = true;
+                                break;
+                            }
+                        }
+                    }
+                    last_activity = Instant::now();
+                }
+
+                _ = tokio::time::sleep(silence_left), if sending_finished => {}
+            }
+        }
+
+        let unanswered: Vec<Probe> = self.pending.drain().map(|(probe, _)| probe).collect();
+        for (ip, number) in unanswered {
+            let port: Port = Port::new(
+                number,
+                Protocol::Udp,
+                PortState::OpenFiltered,
+                PortReason::Timeout,
+            );
+            self.results.record(ip, port, None);
+        }
+
+        info!(
+            verbosity = 1,
+            "UDP scan finished: {} open, {} closed, {} filtered, {} open|filtered",
+            self.results.count(PortState::Open),
+            self.results.count(PortState::Closed),
+            self.results.count(PortState::Filtered),
+            self.results.count(PortState::OpenFiltered)
+        );
+
+        Ok(self.results.drain_hosts())
+    }
+}
+
+impl UdpScanner {
+    pub fn new(
+        targets: IpCollection,
+        ports: Vec<u16>,
+        dns_tx: Option<UnboundedSender<IpAddr>>,
+        state: Arc<ScanState>,
+    ) -> anyhow::Result<Self> {
+        let udp_handle: TransportHandle =
+            transport::start_packet_capture(TransportType::UdpLayer4)?;
+        let icmp_handle: TransportHandle =
+            transport::start_packet_capture(TransportType::IcmpLayer4)?;
+
+        let (mut target_list, skipped_v6): (Vec<IpAddr>, Vec<IpAddr>) =
+            targets.iter().partition(|ip| ip.is_ipv4());
+        target_list.sort();
+
+        if !skipped_v6.is_empty() {
+            warn!(
+                "Skipping {} IPv6 targets, UDP scanning is IPv4 only",
+                skipped_v6.len()
+            );
+        }
+
+        Ok(Self {
+            src_port: rand::random_range(50_000..u16::MAX),
+            targets: target_list,
+            ports,
+            udp_handle,
+            icmp_handle,
+            results: PortResults::new(dns_tx, state.clone()),
+            state,
+            pending: HashMap::new(),
+        })
+    }
+
+    pub fn with_events(mut self, events: Option<EventTx>) -> Self {
+        self.results.set_events(events);
+        self
+    }
+
+    fn send_probe(&mut self, dst_addr: IpAddr, dst_port: u16) {
+        let payload: Vec<u8> = payloads::udp_payload(dst_port);
+        let packet: Vec<u8> = match udp::create_packet(self.src_port, dst_port, payload) {
+            Ok(packet) => packet,
+            Err(e) => {
+                error!(
+                    verbosity = 2,
+                    "Failed to build UDP probe for {dst_addr}:{dst_port}: {e}"
+                );
+                return;
+            }
+        };
+
+        let Some(udp_packet) = UdpPacket::new(&packet) else {
+            return;
+        };
+
+        let mut tx = self.udp_handle.tx.lock().unwrap();
+        match tx.send_to(udp_packet, dst_addr) {
+            Ok(_) => _ = self.pending.insert((dst_addr, dst_port), Instant::now()),
+            Err(e) => error!(
+                verbosity = 2,
+                "Failed to send UDP probe to {dst_addr}:{dst_port}: {e}"
+            ),
+        }
+    }
+
+    /// Returns `true` if the datagram answered one of our probes.
+    fn process_udp_packet(&mut self, bytes: &[u8], src_addr: IpAddr) -> bool {
+        let Some(udp_packet) = UdpPacket::new(bytes) else {
+            return false;
+        };
+
+        if udp_packet.get_destination() != self.src_port {
+            return false;
+        }
+
+        let probe: Probe = (src_addr, udp_packet.get_source());
+        let Some(sent_at) = self.pending.remove(&probe) else {
+            return false;
+        };
+
+        let port: Port = Port::new(
+            probe.1,
+            Protocol::Udp,
+            PortState::Open,
+            PortReason::UdpResponse,
+        );
+        self.results.record(probe.0, port, Some(sent_at.elapsed()));
+        true
+    }
+
+    /// Returns `true` if the ICMP message referred to one of our probes.
+    fn process_icmp_packet(&mut self, bytes: &[u8], src_addr: IpAddr) -> bool {
+        let Some((probe, code)) =
+            port_results::unreachable_probe(bytes, IpNextHeaderProtocols::Udp, self.src_port)
+        else {
+            return false;
+        };
+        let Some(sent_at) = self.pending.remove(&probe) else {
+            return false;
+        };
+
+        let state: PortState = match code {
+            ICMP_PORT_UNREACHABLE => PortState::Closed,
+            _ => PortState::Filtered,
+        };
+        let reason: PortReason = PortReason::Icmp { icmp_type: 3, code };
+        let rtt: Option<Duration> = port_results::icmp_rtt(src_addr, probe, sent_at);
+        self.results.record(
+            probe.0,
+            Port::new(probe.1, Protocol::Udp, state, reason),
+            rtt,
+        );
+        true
+    }
+}
@@ -0,0 +1,240 @@
+//! Parsing of **DHCPv4** messages (RFC 2131) overheard on the local network.
+//!
+//! Only the fields that identify a device are extracted: the client's hardware address,
+//! the addresses it asks for or is given, its hostname, and what the server announces
+//! about itself and the network.
+
+use std::net::Ipv4Addr;
+
+use anyhow::{bail, ensure};
+use pnet::util::MacAddr;
+
+pub const SERVER_PORT: u16 = 67;
+pub const CLIENT_PORT: u16 = 68;
+
+const MAGIC_COOKIE: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
+const OPTIONS_OFFSET: usize = 240;
+
+const BOOTREPLY: u8 = 2;
+const OPT_PAD: u8 = 0;
+const OPT_ROUTER: u8 = 3;
+const OPT_DNS_SERVER: u8 = 6;
+const OPT_HOSTNAME: u8 = 12;
+const OPT_REQUESTED_IP: u8 = 50;
+const OPT_MESSAGE_TYPE: u8 = 53;
+const OPT_SERVER_ID: u8 = 54;
+const OPT_END: u8 = 255;
+
+/// The type of a DHCP message (option 53).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    Discover,
+    Offer,
+    Request,
+    Decline,
+    Ack,
+    Nak,
+    Release,
+    Inform,
+    Other(u8),
+}
+
+impl From<u8> for MessageType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => MessageType::Discover,
+            2 => MessageType::Offer,
+            3 => MessageType::Request,
+            4 => MessageType::Decline,
+            5 => MessageType::Ack,
+            6 => MessageType::Nak,
+            7 => MessageType::Release,
+            8 => MessageType::Inform,
+            other => MessageType::Other(other),
+        }
+    }
+}
+
+/// What a single DHCP message reveals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhcpMessage {
+    /// `true` for messages sent by a server.
+    pub is_reply: bool,
+    pub message_type: Option<MessageType>,
+    pub client_mac: MacAddr,
+    /// The address the client already uses (`ciaddr`).
+    pub client_ip: Option<Ipv4Addr>,
+    /// The address the server hands out (`yiaddr`).
+    pub your_ip: Option<Ipv4Addr>,
+    pub requested_ip: Option<Ipv4Addr>,
+    pub hostname: Option<String>,
+    pub server_id: Option<Ipv4Addr>,
+    pub routers: Vec<Ipv4Addr>,
+    pub dns_servers: Vec<Ipv4Addr>,
+}
+
+/// Parses the UDP payload of a DHCP message.
+pub fn parse(payload: &[u8]) -> anyhow::Result<DhcpMessage> {
+    ensure!(
+        payload.len() >= OPTIONS_OFFSET,
+        "truncated DHCP message (len {})",
+        payload.len()
+    );
+    ensure!(
+        payload[236..OPTIONS_OFFSET] == MAGIC_COOKIE,
+        "not a DHCP message (bad magic cookie)"
+    );
+    ensure!(
+        payload[1] == 1 && payload[2] == 6,
+        "DHCP message without an Ethernet client address"
+    );
+
+    let chaddr: &[u8] = &payload[28..34];
+    let mut message: DhcpMessage = DhcpMessage {
+        is_reply: payload[0] == BOOTREPLY,
+        message_type: None,
+        client_mac: MacAddr::new(
+            chaddr[0], chaddr[1], chaddr[2], chaddr[3], chaddr[4], chaddr[5],
+        ),
+        client_ip: read_addr(&payload[12..16]),
+        your_ip: read_addr(&payload[16..20]),
+        requested_ip: None,
+        hostname: None,
+        server_id: None,
+        routers: Vec::new(),
+        dns_servers: Vec::new(),
+    };
+
+    let mut options: &[u8] = &payload[OPTIONS_OFFSET..];
+    while let Some((&code, rest)) = options.split_first() {
+        match code {
+            OPT_PAD => {
+                options = rest;
+                continue;
+            }
+            OPT_END => break,
+            _ => {}
+        }
+
+        let Some((&len, rest)) = rest.split_first() else {
+            bail!("truncated DHCP option {code}");
+        };
+        let Some(value) = rest.get(..len as usize) else {
+            bail!("truncated DHCP option {code}");
+        };
+
+        match code {
+            OPT_MESSAGE_TYPE => message.message_type = value.first().map(|&t| t.into()),
+            OPT_HOSTNAME => {
+                let name: String = String::from_utf8_lossy(value)
+                    .trim_end_matches('\0')
+                    .to_string();
+                message.hostname = (!name.is_empty()).then_some(name);
+            }
+            OPT_REQUESTED_IP => message.requested_ip = read_addr(value),
+            OPT_SERVER_ID => message.server_id = read_addr(value),
+            OPT_ROUTER => message.routers = read_addrs(value),
+            OPT_DNS_SERVER => message.dns_servers = read_addrs(value),
+            _ => {}
+        }
+
+        options = &rest[len as usize..];
+    }
+
+    Ok(message)
+}
+
+/// Reads an IPv4 address, treating `0.0.0.0` as absent.
+fn read_addr(bytes: &[u8]) -> Option<Ipv4Addr> {
+    let octets: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    let addr: Ipv4Addr = Ipv4Addr::from(octets);
+    (!addr.is_unspecified()).then_some(addr)
+}
+
+fn read_addrs(bytes: &[u8]) -> Vec<Ipv4Addr> {
+    bytes.chunks_exact(4).filter_map(read_addr).collect()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_MAC: [u8; 6] = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+    fn build_message(op: u8, yiaddr: [u8; 4], options: &[u8]) -> Vec<u8> {
+        let mut payload: Vec<u8> = vec![0; OPTIONS_OFFSET];
+        payload[0] = op;
+        payload[1] = 1;
+        payload[2] = 6;
+        payload[16..20].copy_from_slice(&yiaddr);
+        payload[28..34].copy_from_slice(&CLIENT_MAC);
+        payload[236..240].copy_from_slice(&MAGIC_COOKIE);
+        payload.extend_from_slice(options);
+        payload.push(OPT_END);
+        payload
+    }
+
+    #[test]
+    fn parse_should_read_client_requests() {
+        let options: Vec<u8> = [
+            &[OPT_MESSAGE_TYPE, 1, 3][..],
+            &[OPT_PAD],
+            &[OPT_REQUESTED_IP, 4, 192, 168, 1, 42],
+            &[OPT_HOSTNAME, 6],
+            b"laptop",
+        ]
+        .concat();
+        let message: DhcpMessage = parse(&build_message(1, [0; 4], &options)).unwrap();
+
+        assert!(!message.is_reply);
+        assert_eq!(message.message_type, Some(MessageType::Request));
+        assert_eq!(message.client_mac, MacAddr::from(CLIENT_MAC));
+        assert_eq!(message.client_ip, None);
+        assert_eq!(message.your_ip, None);
+        assert_eq!(message.requested_ip, Some(Ipv4Addr::new(192, 168, 1, 42)));
+        assert_eq!(message.hostname.as_deref(), Some("laptop"));
+    }
+
+    #[test]
+    fn parse_should_read_server_replies() {
+        let options: Vec<u8> = [
+            &[OPT_MESSAGE_TYPE, 1, 5][..],
+            &[OPT_SERVER_ID, 4, 192, 168, 1, 1],
+            &[OPT_ROUTER, 4, 192, 168, 1, 1],
+            &[OPT_DNS_SERVER, 8, 192, 168, 1, 1, 9, 9, 9, 9],
+        ]
+        .concat();
+        let message: DhcpMessage = parse(&build_message(2, [192, 168, 1, 42], &options)).unwrap();
+
+        assert!(message.is_reply);
+        assert_eq!(message.message_type, Some(MessageType::Ack));
+        assert_eq!(message.your_ip, Some(Ipv4Addr::new(192, 168, 1, 42)));
+        assert_eq!(message.server_id, Some(Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(message.routers, vec![Ipv4Addr::new(192, 168, 1, 1)]);
+        assert_eq!(
+            message.dns_servers,
+            vec![Ipv4Addr::new(192, 168, 1, 1), Ipv4Addr::new(9, 9, 9, 9)]
+        );
+    }
+
+    #[test]
+    fn parse_should_reject_malformed_messages() {
+        assert!(parse(&[0; 100]).is_err());
+
+        let mut no_cookie: Vec<u8> = build_message(1, [0; 4], &[]);
+        no_cookie[236] = 0;
+        assert!(parse(&no_cookie).is_err());
+
+        let truncated: Vec<u8> = build_message(1, [0; 4], &[OPT_HOSTNAME, 200, b'x']);
+        assert!(parse(&truncated).is_err());
+    }
+}
@@ -1,20 +1,29 @@
 use crate::ethernet;
 use crate::ip;
-use crate::utils::{ETH_HDR_LEN, ICMP_V6_ECHO_REQ_LEN, IP_V6_HDR_LEN};
-use anyhow::Context;
+use crate::utils::{ETH_HDR_LEN, ICMP_V4_HDR_LEN, ICMP_V6_ECHO_REQ_LEN, IP_V6_HDR_LEN};
+use anyhow::{Context, bail, ensure};
 use pnet::datalink::MacAddr;
 use pnet::packet::Packet;
 use pnet::packet::ethernet::EtherTypes;
+use pnet::packet::icmp::destination_unreachable::DestinationUnreachablePacket;
+use pnet::packet::icmp::{self as icmpv4, IcmpPacket, IcmpType, IcmpTypes};
 use pnet::packet::icmpv6::echo_reply::Icmpv6Codes;
 use pnet::packet::icmpv6::echo_request::{EchoRequestPacket, MutableEchoRequestPacket};
 use pnet::packet::icmpv6::{Icmpv6Packet, Icmpv6Types, checksum};
 use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
-use std::net::Ipv6Addr;
+use pnet::packet::ipv4::Ipv4Packet;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const TOTAL_LEN: usize = ETH_HDR_LEN + IP_V6_HDR_LEN + ICMP_V6_ECHO_REQ_LEN;
 const PAYLOAD_LENGTH: u16 = ICMP_V6_ECHO_REQ_LEN as u16;
 const NEXT_PROTOCOL: IpNextHeaderProtocol = IpNextHeaderProtocols::Icmpv6;
 
+const ECHO_PAYLOAD_LEN: usize = 16;
+const TIMESTAMP_BODY_LEN: usize = 12;
+const ADDRESS_MASK_BODY_LEN: usize = 4;
+const MS_PER_DAY: u128 = 86_400_000;
+
 pub fn create_all_nodes_echo_request_v6(
     src_mac: MacAddr,
     src_addr: Ipv6Addr,
@@ -48,3 +57,208 @@ pub fn create_all_nodes_echo_request_v6(
 
     Ok(final_packet)
 }
+
+/// Builds an ICMPv4 echo, timestamp or address mask request (without IP header).
+///
+/// `identifier` and `sequence` are echoed back by the target and identify the probe.
+pub fn create_request_v4(
+    icmp_type: IcmpType,
+    identifier: u16,
+    sequence: u16,
+) -> anyhow::Result<Vec<u8>> {
+    let body_len: usize = match icmp_type {
+        IcmpTypes::EchoRequest => ECHO_PAYLOAD_LEN,
+        IcmpTypes::Timestamp => TIMESTAMP_BODY_LEN,
+        IcmpTypes::AddressMaskRequest => ADDRESS_MASK_BODY_LEN,
+        other => bail!("ICMP type {} is not a request", other.0),
+    };
+
+    let mut packet: Vec<u8> = vec![0u8; ICMP_V4_HDR_LEN + body_len];
+    packet[0] = icmp_type.0;
+    packet[4..6].copy_from_slice(&identifier.to_be_bytes());
+    packet[6..8].copy_from_slice(&sequence.to_be_bytes());
+
+    if icmp_type == IcmpTypes::Timestamp {
+        // Originate timestamp: milliseconds since midnight UT (RFC 792)
+        let since_epoch: u128 = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let originate: u32 = (since_epoch % MS_PER_DAY) as u32;
+        packet[8..12].copy_from_slice(&originate.to_be_bytes());
+    }
+
+    let checksum: u16 = {
+        let icmp_packet: IcmpPacket =
+            IcmpPacket::new(&packet).context("failed to create ICMP packet")?;
+        icmpv4::checksum(&icmp_packet)
+    };
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    Ok(packet)
+}
+
+/// An answer to one of the requests built by [`create_request_v4`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reply {
+    /// The type of the reply (echo reply, timestamp reply or address mask reply).
+    pub icmp_type: IcmpType,
+    pub identifier: u16,
+    pub sequence: u16,
+}
+
+/// Parses an ICMPv4 message (without its IP header), keeping replies to our requests only.
+pub fn parse_reply_v4(bytes: &[u8]) -> Option<Reply> {
+    let icmp_packet: IcmpPacket = IcmpPacket::new(bytes)?;
+    let icmp_type: IcmpType = icmp_packet.get_icmp_type();
+    let is_reply: bool = matches!(
+        icmp_type,
+        IcmpTypes::EchoReply | IcmpTypes::TimestampReply | IcmpTypes::AddressMaskReply
+    );
+    if !is_reply || bytes.len() < ICMP_V4_HDR_LEN {
+        return None;
+    }
+
+    Some(Reply {
+        icmp_type,
+        identifier: u16::from_be_bytes([bytes[4], bytes[5]]),
+        sequence: u16::from_be_bytes([bytes[6], bytes[7]]),
+    })
+}
+
+/// The original datagram quoted back inside an ICMPv4 *Destination Unreachable* message.
+///
+/// Only the fields needed to match the message to one of our probes are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unreachable {
+    /// The unreachable code (e.g. 3 for "port unreachable", 13 for "administratively prohibited").
+    pub code: u8,
+    /// The destination of the datagram that could not be delivered.
+    pub dst_addr: Ipv4Addr,
+    /// The transport protocol of the datagram that could not be delivered.
+    pub protocol: IpNextHeaderProtocol,
+    /// The source port of the quoted TCP/UDP header.
+    pub src_port: u16,
+    /// The destination port of the quoted TCP/UDP header.
+    pub dst_port: u16,
+}
+
+/// Parses an ICMPv4 *Destination Unreachable* message (without its IP header).
+pub fn parse_unreachable_v4(bytes: &[u8]) -> anyhow::Result<Unreachable> {
+    let icmp_packet: IcmpPacket =
+        IcmpPacket::new(bytes).context("truncated or invalid ICMP packet")?;
+    ensure!(
+        icmp_packet.get_icmp_type() == IcmpTypes::DestinationUnreachable,
+        "not a destination unreachable message"
+    );
+
+    let unreachable: DestinationUnreachablePacket = DestinationUnreachablePacket::new(bytes)
+        .context("truncated or invalid destination unreachable message")?;
+    let quoted: &[u8] = unreachable.payload();
+    let quoted_ip: Ipv4Packet = Ipv4Packet::new(quoted).context("truncated quoted ipv4 header")?;
+
+    let header_len: usize = quoted_ip.get_header_length() as usize * 4;
+    let ports: &[u8] = quoted
+        .get(header_len..header_len + 4)
+        .context("truncated quoted transport header")?;
+
+    Ok(Unreachable {
+        code: icmp_packet.get_icmp_code().0,
+        dst_addr: quoted_ip.get_destination(),
+        protocol: quoted_ip.get_next_level_protocol(),
+        src_port: u16::from_be_bytes([ports[0], ports[1]]),
+        dst_port: u16::from_be_bytes([ports[2], ports[3]]),
+    })
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pnet::packet::ipv4::MutableIpv4Packet;
+
+    fn build_unreachable(code: u8, protocol: IpNextHeaderProtocol, quoted_len: usize) -> Vec<u8> {
+        let mut quoted: Vec<u8> = vec![0u8; quoted_len];
+        {
+            let mut ip = MutableIpv4Packet::new(&mut quoted).unwrap();
+            ip.set_version(4);
+            ip.set_header_length(5);
+            ip.set_total_length(28);
+            ip.set_next_level_protocol(protocol);
+            ip.set_source(Ipv4Addr::new(10, 0, 0, 1));
+            ip.set_destination(Ipv4Addr::new(10, 0, 0, 2));
+        }
+        if quoted_len >= 24 {
+            quoted[20..22].copy_from_slice(&54_321u16.to_be_bytes());
+            quoted[22..24].copy_from_slice(&8080u16.to_be_bytes());
+        }
+
+        let mut packet: Vec<u8> = vec![3, code, 0, 0, 0, 0, 0, 0];
+        packet.extend_from_slice(&quoted);
+        packet
+    }
+
+    #[test]
+    fn create_request_v4_builds_checksummed_requests() {
+        let cases = [
+            (IcmpTypes::EchoRequest, ECHO_PAYLOAD_LEN),
+            (IcmpTypes::Timestamp, TIMESTAMP_BODY_LEN),
+            (IcmpTypes::AddressMaskRequest, ADDRESS_MASK_BODY_LEN),
+        ];
+
+        for (icmp_type, body_len) in cases {
+            let packet = create_request_v4(icmp_type, 0xBEEF, 7).unwrap();
+            let icmp_packet = IcmpPacket::new(&packet).unwrap();
+
+            assert_eq!(packet.len(), ICMP_V4_HDR_LEN + body_len);
+            assert_eq!(icmp_packet.get_icmp_type(), icmp_type);
+            assert_eq!(icmp_packet.get_checksum(), icmpv4::checksum(&icmp_packet));
+            assert_eq!(&packet[4..8], &[0xBE, 0xEF, 0, 7]);
+        }
+
+        assert!(create_request_v4(IcmpTypes::EchoReply, 1, 1).is_err());
+    }
+
+    #[test]
+    fn parse_reply_v4_matches_identifier_and_sequence() {
+        let mut packet = create_request_v4(IcmpTypes::Timestamp, 0x1234, 42).unwrap();
+        assert_eq!(parse_reply_v4(&packet), None);
+
+        packet[0] = IcmpTypes::TimestampReply.0;
+        let reply = parse_reply_v4(&packet).unwrap();
+        assert_eq!(reply.icmp_type, IcmpTypes::TimestampReply);
+        assert_eq!(reply.identifier, 0x1234);
+        assert_eq!(reply.sequence, 42);
+    }
+
+    #[test]
+    fn parse_unreachable_v4_extracts_quoted_ports() {
+        let bytes = build_unreachable(3, IpNextHeaderProtocols::Udp, 28);
+        let unreachable = parse_unreachable_v4(&bytes).unwrap();
+
+        assert_eq!(unreachable.code, 3);
+        assert_eq!(unreachable.dst_addr, Ipv4Addr::new(10, 0, 0, 2));
+        assert_eq!(unreachable.protocol, IpNextHeaderProtocols::Udp);
+        assert_eq!(unreachable.src_port, 54_321);
+        assert_eq!(unreachable.dst_port, 8080);
+    }
+
+    #[test]
+    fn parse_unreachable_v4_rejects_other_types() {
+        let mut bytes = build_unreachable(0, IpNextHeaderProtocols::Tcp, 28);
+        bytes[0] = 0; // Echo Reply
+        assert!(parse_unreachable_v4(&bytes).is_err());
+    }
+
+    #[test]
+    fn parse_unreachable_v4_rejects_truncated_quote() {
+        let bytes = build_unreachable(13, IpNextHeaderProtocols::Tcp, 22);
+        let err = parse_unreachable_v4(&bytes).unwrap_err().to_string();
+        assert!(err.contains("truncated quoted transport header"));
+    }
+}
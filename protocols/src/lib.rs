@@ -1,10 +1,14 @@
 pub mod arp;
+pub mod dhcp;
 pub mod dns;
 pub mod ethernet;
 pub mod icmp;
 pub mod ip;
 pub mod mdns;
 pub mod ndp;
+pub mod netbios;
+pub mod payloads;
+pub mod ssdp;
 pub mod tcp;
 pub mod udp;
 pub mod utils;
@@ -28,6 +32,11 @@ pub fn eth_packet_iter(sender_config: &SenderConfig) -> anyhow::Result<PacketIte
         combined_iter = Box::new(combined_iter.chain(arp_iter));
     }
 
+    if sender_config.has_packet_type(PacketType::NDP) {
+        let ndp_iter = create_ndp_packets(sender_config)?;
+        combined_iter = Box::new(combined_iter.chain(ndp_iter));
+    }
+
     if sender_config.has_packet_type(PacketType::ICMPv6) {
         let icmp_iter = create_icmpv6_packets(sender_config)?;
         combined_iter = Box::new(combined_iter.chain(icmp_iter));
@@ -54,12 +63,31 @@ pub fn create_arp_packets(sender_config: &SenderConfig) -> anyhow::Result<Packet
     Ok(Box::new(iter))
 }
 
+pub fn create_ndp_packets(sender_config: &SenderConfig) -> anyhow::Result<PacketIter> {
+    let link_local: Ipv6Addr = sender_config.get_link_local()?;
+    let local_mac: MacAddr = sender_config.get_local_mac()?;
+
+    let targets: HashSet<Ipv6Addr> = sender_config.get_targets_v6();
+
+    let iter = targets.into_iter().map(move |target| {
+        let packet: Vec<u8> = ndp::create_neighbor_solicitation(local_mac, link_local, target)
+            .expect("Failed to create neighbor solicitation");
+
+        (packet, IpAddr::V6(target))
+    });
+
+    Ok(Box::new(iter))
+}
+
 fn create_icmpv6_packets(sender_config: &SenderConfig) -> anyhow::Result<PacketIter> {
     let link_local: Ipv6Addr = sender_config.get_link_local()?;
     let local_mac: MacAddr = sender_config.get_local_mac()?;
-    let packet: Vec<u8> = icmp::create_all_nodes_echo_request_v6(local_mac, link_local)?;
+    let echo: Vec<u8> = icmp::create_all_nodes_echo_request_v6(local_mac, link_local)?;
+    let router_solicit: Vec<u8> = ndp::create_router_solicitation(local_mac, link_local)?;
 
-    let iter = std::iter::once((packet, IpAddr::V6(link_local)));
+    let iter = [echo, router_solicit]
+        .into_iter()
+        .map(move |packet| (packet, IpAddr::V6(link_local)));
 
     Ok(Box::new(iter))
 }
@@ -16,17 +16,20 @@ pub fn extract_resource(data: &[u8]) -> Result<MdnsRecord> {
         match &record.data {
             RData::PTR(ptr) => {
                 let name: String = ptr.0.to_string();
-                if !name.ends_with(".arpa") {
+                // DNS-SD pointers name service types and instances, not hosts
+                if !name.ends_with(".arpa") && !name.starts_with('_') && !name.contains("._") {
                     metadata.hostname = Some(name);
                 }
             }
 
             RData::A(a) => {
                 metadata.ips.insert(IpAddr::V4(a.0));
+                metadata.hostname.get_or_insert_with(|| record.name.to_string());
             }
 
             RData::AAAA(aaaa) => {
                 metadata.ips.insert(IpAddr::V6(aaaa.0));
+                metadata.hostname.get_or_insert_with(|| record.name.to_string());
             }
 
             _ => {}
@@ -1,3 +1,321 @@
-// use pnet::packet::icmpv6::ndp::{, NdpOptionTypes, RouterAdvertPacket};
+//! **Neighbor Discovery** for IPv6 (RFC 4861).
+//!
+//! Neighbor Solicitations are the IPv6 counterpart of ARP requests: they go to the
+//! solicited-node multicast group of the target, which answers with a Neighbor Advertisement
+//! carrying its MAC address. A Router Solicitation makes every router on the link send a
+//! Router Advertisement, which may list DNS servers in an RDNSS option (RFC 8106).
 
-// const OPTION_TYPE_RDNSS: u8 = 25;
+use std::net::Ipv6Addr;
+
+use anyhow::Context;
+use pnet::datalink::MacAddr;
+use pnet::packet::Packet;
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::icmpv6::{self, Icmpv6Packet, Icmpv6Types};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::{Ipv6Packet, MutableIpv6Packet};
+
+use crate::{ethernet, ip};
+
+/// Receivers drop NDP messages that were forwarded, which is detected by a hop limit below 255.
+const NDP_HOP_LIMIT: u8 = 255;
+
+const RS_OPTIONS_OFFSET: usize = 8;
+const RA_OPTIONS_OFFSET: usize = 16;
+const NS_OPTIONS_OFFSET: usize = 24;
+const NA_OPTIONS_OFFSET: usize = 24;
+
+const OPT_SOURCE_LL_ADDR: u8 = 1;
+const OPT_TARGET_LL_ADDR: u8 = 2;
+const OPT_RDNSS: u8 = 25;
+const OPTION_UNIT: usize = 8;
+
+const NA_FLAG_ROUTER: u8 = 0x80;
+
+const ALL_ROUTERS: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 2);
+
+/// A Neighbor Advertisement: `target` is at `mac`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NeighborAdvert {
+    pub target: Ipv6Addr,
+    /// The target link-layer address option, if present.
+    pub mac: Option<MacAddr>,
+    /// The sender is a router.
+    pub is_router: bool,
+}
+
+/// A Router Advertisement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouterAdvert {
+    /// The source link-layer address option, if present.
+    pub mac: Option<MacAddr>,
+    /// How long the router may be used as a default router, in seconds (0: not a default router).
+    pub lifetime: u16,
+    /// Recursive DNS servers announced to the link.
+    pub dns_servers: Vec<Ipv6Addr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NdpMessage {
+    NeighborAdvert(NeighborAdvert),
+    RouterAdvert(RouterAdvert),
+}
+
+/// The solicited-node multicast group of `addr` (`ff02::1:ffXX:XXXX`).
+pub fn solicited_node_addr(addr: Ipv6Addr) -> Ipv6Addr {
+    let octets: [u8; 16] = addr.octets();
+    Ipv6Addr::from([
+        0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0xff, octets[13], octets[14], octets[15],
+    ])
+}
+
+/// The Ethernet address an IPv6 multicast group maps to (`33:33` and its last 32 bits).
+pub fn multicast_mac(group: Ipv6Addr) -> MacAddr {
+    let octets: [u8; 16] = group.octets();
+    MacAddr::new(0x33, 0x33, octets[12], octets[13], octets[14], octets[15])
+}
+
+/// Builds a Neighbor Solicitation frame for `target`, sent to its solicited-node group.
+pub fn create_neighbor_solicitation(
+    src_mac: MacAddr,
+    src_addr: Ipv6Addr,
+    target: Ipv6Addr,
+) -> anyhow::Result<Vec<u8>> {
+    let mut message: Vec<u8> = vec![0u8; NS_OPTIONS_OFFSET];
+    message[0] = Icmpv6Types::NeighborSolicit.0;
+    message[8..NS_OPTIONS_OFFSET].copy_from_slice(&target.octets());
+    message.extend(link_layer_option(OPT_SOURCE_LL_ADDR, src_mac));
+
+    create_frame(src_mac, src_addr, solicited_node_addr(target), message)
+}
+
+/// Builds a Router Solicitation frame, sent to all routers of the link.
+pub fn create_router_solicitation(src_mac: MacAddr, src_addr: Ipv6Addr) -> anyhow::Result<Vec<u8>> {
+    let mut message: Vec<u8> = vec![0u8; RS_OPTIONS_OFFSET];
+    message[0] = Icmpv6Types::RouterSolicit.0;
+    message.extend(link_layer_option(OPT_SOURCE_LL_ADDR, src_mac));
+
+    create_frame(src_mac, src_addr, ALL_ROUTERS, message)
+}
+
+/// Parses Neighbor and Router Advertisements out of an Ethernet frame.
+pub fn parse_from_eth(frame: &EthernetPacket) -> Option<NdpMessage> {
+    if frame.get_ethertype() != EtherTypes::Ipv6 {
+        return None;
+    }
+    let ipv6_packet: Ipv6Packet = Ipv6Packet::new(frame.payload())?;
+    if ipv6_packet.get_next_header() != IpNextHeaderProtocols::Icmpv6
+        || ipv6_packet.get_hop_limit() != NDP_HOP_LIMIT
+    {
+        return None;
+    }
+    parse(ipv6_packet.payload())
+}
+
+/// Parses an ICMPv6 message, keeping Neighbor and Router Advertisements.
+pub fn parse(message: &[u8]) -> Option<NdpMessage> {
+    match *message.first()? {
+        t if t == Icmpv6Types::NeighborAdvert.0 => {
+            let target: [u8; 16] = message.get(8..24)?.try_into().ok()?;
+            let options: &[u8] = message.get(NA_OPTIONS_OFFSET..)?;
+            Some(NdpMessage::NeighborAdvert(NeighborAdvert {
+                target: Ipv6Addr::from(target),
+                mac: find_link_layer_addr(options, OPT_TARGET_LL_ADDR),
+                is_router: message.get(4)? & NA_FLAG_ROUTER != 0,
+            }))
+        }
+        t if t == Icmpv6Types::RouterAdvert.0 => {
+            let lifetime: u16 = u16::from_be_bytes([*message.get(6)?, *message.get(7)?]);
+            let options: &[u8] = message.get(RA_OPTIONS_OFFSET..)?;
+            Some(NdpMessage::RouterAdvert(RouterAdvert {
+                mac: find_link_layer_addr(options, OPT_SOURCE_LL_ADDR),
+                lifetime,
+                dns_servers: find_dns_servers(options),
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn create_frame(
+    src_mac: MacAddr,
+    src_addr: Ipv6Addr,
+    dst_addr: Ipv6Addr,
+    mut message: Vec<u8>,
+) -> anyhow::Result<Vec<u8>> {
+    let checksum: u16 = {
+        let icmp_packet: Icmpv6Packet =
+            Icmpv6Packet::new(&message).context("failed to create ICMPv6 packet")?;
+        icmpv6::checksum(&icmp_packet, &src_addr, &dst_addr)
+    };
+    message[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    let eth_header: Vec<u8> =
+        ethernet::make_header(src_mac, multicast_mac(dst_addr), EtherTypes::Ipv6)?;
+    let mut ipv6_header: Vec<u8> = ip::create_ipv6_header(
+        src_addr,
+        dst_addr,
+        message.len() as u16,
+        IpNextHeaderProtocols::Icmpv6,
+    )?;
+    MutableIpv6Packet::new(&mut ipv6_header)
+        .context("creating ipv6 packet")?
+        .set_hop_limit(NDP_HOP_LIMIT);
+
+    let mut frame: Vec<u8> = eth_header;
+    frame.extend_from_slice(&ipv6_header);
+    frame.extend_from_slice(&message);
+    Ok(frame)
+}
+
+fn link_layer_option(option_type: u8, mac: MacAddr) -> [u8; OPTION_UNIT] {
+    let octets: [u8; 6] = mac.octets();
+    [
+        option_type,
+        1,
+        octets[0],
+        octets[1],
+        octets[2],
+        octets[3],
+        octets[4],
+        octets[5],
+    ]
+}
+
+/// Splits NDP options into `(type, option)` pairs, stopping at the first malformed one.
+fn iter_options(mut options: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    std::iter::from_fn(move || {
+        let option_type: u8 = *options.first()?;
+        let len: usize = *options.get(1)? as usize * OPTION_UNIT;
+        if len == 0 {
+            return None;
+        }
+        let option: &[u8] = options.get(..len)?;
+        options = &options[len..];
+        Some((option_type, option))
+    })
+}
+
+fn find_link_layer_addr(options: &[u8], wanted: u8) -> Option<MacAddr> {
+    iter_options(options)
+        .find(|(option_type, _)| *option_type == wanted)
+        .and_then(|(_, option)| {
+            let octets: [u8; 6] = option.get(2..8)?.try_into().ok()?;
+            Some(MacAddr::from(octets))
+        })
+}
+
+/// Collects the addresses of every RDNSS option: 8 header bytes, then 16 bytes per server.
+fn find_dns_servers(options: &[u8]) -> Vec<Ipv6Addr> {
+    iter_options(options)
+        .filter(|(option_type, _)| *option_type == OPT_RDNSS)
+        .flat_map(|(_, option)| option.get(8..).unwrap_or_default().chunks_exact(16))
+        .filter_map(|addr| <[u8; 16]>::try_from(addr).ok().map(Ipv6Addr::from))
+        .collect()
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{ETH_HDR_LEN, IP_V6_HDR_LEN};
+
+    const MAC: MacAddr = MacAddr(0x02, 0xaa, 0xbb, 0xcc, 0xdd, 0xee);
+
+    #[test]
+    fn neighbor_solicitation_targets_solicited_node_group() {
+        let src: Ipv6Addr = "fe80::1".parse().unwrap();
+        let target: Ipv6Addr = "2001:db8::12:3456".parse().unwrap();
+        let frame: Vec<u8> = create_neighbor_solicitation(MAC, src, target).unwrap();
+
+        let eth: EthernetPacket = EthernetPacket::new(&frame).unwrap();
+        assert_eq!(
+            eth.get_destination(),
+            MacAddr::new(0x33, 0x33, 0xff, 0x12, 0x34, 0x56)
+        );
+
+        let ipv6: Ipv6Packet = Ipv6Packet::new(eth.payload()).unwrap();
+        assert_eq!(ipv6.get_hop_limit(), NDP_HOP_LIMIT);
+        assert_eq!(
+            ipv6.get_destination(),
+            "ff02::1:ff12:3456".parse::<Ipv6Addr>().unwrap()
+        );
+
+        let message: &[u8] = &frame[ETH_HDR_LEN + IP_V6_HDR_LEN..];
+        let icmp: Icmpv6Packet = Icmpv6Packet::new(message).unwrap();
+        assert_eq!(icmp.get_icmpv6_type(), Icmpv6Types::NeighborSolicit);
+        assert_eq!(
+            icmp.get_checksum(),
+            icmpv6::checksum(&icmp, &src, &ipv6.get_destination())
+        );
+        assert_eq!(&message[8..24], &target.octets());
+        assert_eq!(
+            find_link_layer_addr(&message[NS_OPTIONS_OFFSET..], 1),
+            Some(MAC)
+        );
+    }
+
+    #[test]
+    fn router_solicitation_goes_to_all_routers() {
+        let frame: Vec<u8> = create_router_solicitation(MAC, "fe80::1".parse().unwrap()).unwrap();
+        let eth: EthernetPacket = EthernetPacket::new(&frame).unwrap();
+
+        assert_eq!(eth.get_destination(), MacAddr::new(0x33, 0x33, 0, 0, 0, 2));
+        assert_eq!(
+            frame.len(),
+            ETH_HDR_LEN + IP_V6_HDR_LEN + RS_OPTIONS_OFFSET + OPTION_UNIT
+        );
+    }
+
+    #[test]
+    fn parse_reads_neighbor_advertisements() {
+        let target: Ipv6Addr = "fe80::42".parse().unwrap();
+        let mut message: Vec<u8> = vec![136, 0, 0, 0, 0xe0, 0, 0, 0];
+        message.extend_from_slice(&target.octets());
+        message.extend(link_layer_option(OPT_TARGET_LL_ADDR, MAC));
+
+        let expected = NeighborAdvert {
+            target,
+            mac: Some(MAC),
+            is_router: true,
+        };
+        assert_eq!(parse(&message), Some(NdpMessage::NeighborAdvert(expected)));
+    }
+
+    #[test]
+    fn parse_reads_router_advertisements_with_rdnss() {
+        let dns: Ipv6Addr = "2001:db8::53".parse().unwrap();
+        let mut message: Vec<u8> = vec![134, 0, 0, 0, 64, 0, 0x07, 0x08, 0, 0, 0, 0, 0, 0, 0, 0];
+        message.extend(link_layer_option(OPT_SOURCE_LL_ADDR, MAC));
+        message.extend_from_slice(&[OPT_RDNSS, 3, 0, 0, 0, 0, 0x0e, 0x10]);
+        message.extend_from_slice(&dns.octets());
+
+        let expected = RouterAdvert {
+            mac: Some(MAC),
+            lifetime: 1800,
+            dns_servers: vec![dns],
+        };
+        assert_eq!(parse(&message), Some(NdpMessage::RouterAdvert(expected)));
+    }
+
+    #[test]
+    fn parse_ignores_other_messages_and_bad_options() {
+        assert_eq!(parse(&[128, 0, 0, 0, 0, 0, 0, 0]), None);
+        assert_eq!(parse(&[]), None);
+
+        let mut message: Vec<u8> = vec![134, 0, 0, 0, 64, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        message.extend_from_slice(&[OPT_SOURCE_LL_ADDR, 0, 1, 2, 3, 4, 5, 6]);
+        let Some(NdpMessage::RouterAdvert(advert)) = parse(&message) else {
+            panic!("expected a router advertisement");
+        };
+        assert_eq!(advert.mac, None);
+    }
+}
@@ -0,0 +1,148 @@
+//! Parsing of **NetBIOS** name service and datagram broadcasts (RFC 1002).
+//!
+//! Windows hosts and Samba servers regularly announce their own names: in name
+//! registrations and refreshes on port 137, and as the source name of browser
+//! datagrams on port 138. Only names that identify a single machine are returned.
+
+pub const NAME_SERVICE_PORT: u16 = 137;
+pub const DATAGRAM_PORT: u16 = 138;
+
+const NS_HEADER_LEN: usize = 12;
+const DGM_HEADER_LEN: usize = 14;
+const ENCODED_NAME_LEN: usize = 32;
+
+const OPCODE_QUERY: u16 = 0;
+const OPCODE_REGISTRATION: u16 = 5;
+const OPCODE_REFRESH: u16 = 8;
+const OPCODE_REFRESH_ALT: u16 = 9;
+
+/// Name suffixes of the workstation and file server services.
+const MACHINE_SUFFIXES: [u8; 2] = [0x00, 0x20];
+
+/// Returns the name a host announced about itself, if the payload carries one.
+pub fn get_announced_name(port: u16, payload: &[u8]) -> Option<String> {
+    match port {
+        NAME_SERVICE_PORT => name_service_name(payload),
+        DATAGRAM_PORT => datagram_source_name(payload),
+        _ => None,
+    }
+}
+
+/// Registrations, refreshes and positive query answers name their sender.
+fn name_service_name(payload: &[u8]) -> Option<String> {
+    let flags: u16 = u16::from_be_bytes([*payload.get(2)?, *payload.get(3)?]);
+    let is_response: bool = flags & 0x8000 != 0;
+    let opcode: u16 = (flags >> 11) & 0x0F;
+    let rcode: u16 = flags & 0x0F;
+
+    let announces_self: bool = match opcode {
+        OPCODE_REGISTRATION | OPCODE_REFRESH | OPCODE_REFRESH_ALT => !is_response,
+        OPCODE_QUERY => is_response && rcode == 0,
+        _ => false,
+    };
+    if !announces_self {
+        return None;
+    }
+
+    machine_name(payload.get(NS_HEADER_LEN..)?)
+}
+
+/// Direct and broadcast datagrams carry the sender's name after the header.
+fn datagram_source_name(payload: &[u8]) -> Option<String> {
+    if !(0x10..=0x12).contains(payload.first()?) {
+        return None;
+    }
+    machine_name(payload.get(DGM_HEADER_LEN..)?)
+}
+
+/// Decodes a length-prefixed, first-level encoded name and keeps machine names only.
+fn machine_name(label: &[u8]) -> Option<String> {
+    if *label.first()? as usize != ENCODED_NAME_LEN {
+        return None;
+    }
+    let (name, suffix) = decode_name(label.get(1..=ENCODED_NAME_LEN)?)?;
+    (MACHINE_SUFFIXES.contains(&suffix) && !name.is_empty()).then_some(name)
+}
+
+/// Undoes the half-ASCII encoding: every byte is split into two nibbles offset by 'A'.
+/// Returns the space-trimmed name and its suffix byte.
+fn decode_name(encoded: &[u8]) -> Option<(String, u8)> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(ENCODED_NAME_LEN / 2);
+    for pair in encoded.chunks_exact(2) {
+        let high: u8 = pair[0].checked_sub(b'A').filter(|n| *n < 16)?;
+        let low: u8 = pair[1].checked_sub(b'A').filter(|n| *n < 16)?;
+        bytes.push((high << 4) | low);
+    }
+
+    let suffix: u8 = bytes.pop()?;
+    if !bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        return None;
+    }
+    let name: String = String::from_utf8_lossy(&bytes).trim_end().to_string();
+    Some((name, suffix))
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_name(name: &str, suffix: u8) -> Vec<u8> {
+        let mut raw: Vec<u8> = format!("{name:<15}").into_bytes();
+        raw.push(suffix);
+
+        let mut label: Vec<u8> = vec![ENCODED_NAME_LEN as u8];
+        for byte in raw {
+            label.push(b'A' + (byte >> 4));
+            label.push(b'A' + (byte & 0x0F));
+        }
+        label.push(0);
+        label
+    }
+
+    fn name_service_packet(flags: u16, name: &str, suffix: u8) -> Vec<u8> {
+        let mut packet: Vec<u8> = vec![0x13, 0x37];
+        packet.extend_from_slice(&flags.to_be_bytes());
+        packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 1]);
+        packet.extend(encode_name(name, suffix));
+        packet
+    }
+
+    #[test]
+    fn registrations_announce_the_sender() {
+        let packet: Vec<u8> = name_service_packet(0x2910, "DESKTOP-4F2K", 0x00);
+        assert_eq!(
+            get_announced_name(NAME_SERVICE_PORT, &packet).as_deref(),
+            Some("DESKTOP-4F2K")
+        );
+    }
+
+    #[test]
+    fn queries_and_group_names_are_ignored() {
+        let query: Vec<u8> = name_service_packet(0x0110, "FILESERVER", 0x20);
+        assert_eq!(get_announced_name(NAME_SERVICE_PORT, &query), None);
+
+        let domain: Vec<u8> = name_service_packet(0x2910, "WORKGROUP", 0x1E);
+        assert_eq!(get_announced_name(NAME_SERVICE_PORT, &domain), None);
+    }
+
+    #[test]
+    fn datagrams_name_their_source() {
+        let mut packet: Vec<u8> = vec![0x11, 0x0A, 0x00, 0x01, 192, 168, 1, 7, 0, 138, 0, 0, 0, 0];
+        packet.extend(encode_name("NAS", 0x20));
+        packet.extend(encode_name("WORKGROUP", 0x1D));
+
+        assert_eq!(
+            get_announced_name(DATAGRAM_PORT, &packet).as_deref(),
+            Some("NAS")
+        );
+    }
+}
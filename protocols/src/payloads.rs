@@ -0,0 +1,169 @@
+//! Protocol-specific **UDP probe payloads**.
+//!
+//! Most UDP services stay silent on an empty datagram, so a port can only be proven
+//! open by speaking its protocol. Ports without a dedicated payload get an empty one.
+
+use anyhow::anyhow;
+use dns_parser::{Builder, QueryClass, QueryType};
+
+/// Returns the payload that is most likely to make the service on `port` answer.
+pub fn udp_payload(port: u16) -> Vec<u8> {
+    match port {
+        53 | 5353 | 5355 => dns_query(port).unwrap_or_default(),
+        111 => rpcbind_null_call(),
+        123 => ntp_client_request(),
+        137 => netbios_node_status(),
+        161 => snmp_get_sys_descr(),
+        1900 => ssdp_m_search(),
+        11211 => memcache_stats(),
+        _ => Vec::new(),
+    }
+}
+
+/// A DNS (or LLMNR) `version.bind` query, or a DNS-SD service query on mDNS.
+fn dns_query(port: u16) -> anyhow::Result<Vec<u8>> {
+    let mut builder: Builder = Builder::new_query(rand::random(), false);
+
+    match port {
+        5353 => builder.add_question(
+            "_services._dns-sd._udp.local",
+            false,
+            QueryType::PTR,
+            QueryClass::IN,
+        ),
+        _ => builder.add_question("version.bind", false, QueryType::TXT, QueryClass::CH),
+    };
+
+    builder
+        .build()
+        .map_err(|e| anyhow!("Failed to build DNS packet: {:?}", e))
+}
+
+/// A mode 3 (client) NTPv4 request.
+fn ntp_client_request() -> Vec<u8> {
+    let mut packet: Vec<u8> = vec![0u8; 48];
+    // LI = 3 (unsynchronized), VN = 4, Mode = 3 (client)
+    packet[0] = 0xe3;
+    packet
+}
+
+/// A NetBIOS node status request for the wildcard name `*`.
+fn netbios_node_status() -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(50);
+    packet.extend_from_slice(&rand::random::<u16>().to_be_bytes());
+    // Flags, 1 question, no answer/authority/additional records
+    packet.extend_from_slice(&[0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+    // "*" padded with NULs to 16 bytes, half-byte encoded into 32 characters
+    packet.push(0x20);
+    let mut name: [u8; 16] = [0u8; 16];
+    name[0] = b'*';
+    for byte in name {
+        packet.push(b'A' + (byte >> 4));
+        packet.push(b'A' + (byte & 0x0f));
+    }
+    packet.push(0x00);
+
+    // Type NBSTAT, class IN
+    packet.extend_from_slice(&[0x00, 0x21, 0x00, 0x01]);
+    packet
+}
+
+/// An SNMPv1 get-request for `sysDescr.0` with the community `public`.
+fn snmp_get_sys_descr() -> Vec<u8> {
+    vec![
+        0x30, 0x29, // SEQUENCE
+        0x02, 0x01, 0x00, // version: 1
+        0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c', // community
+        0xa0, 0x1c, // GetRequest PDU
+        0x02, 0x04, 0x71, 0x2a, 0x4e, 0x19, // request id
+        0x02, 0x01, 0x00, // error status
+        0x02, 0x01, 0x00, // error index
+        0x30, 0x0e, // variable bindings
+        0x30, 0x0c, // variable binding
+        0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00, // 1.3.6.1.2.1.1.1.0
+        0x05, 0x00, // NULL
+    ]
+}
+
+/// An SSDP discovery request for every device and service.
+fn ssdp_m_search() -> Vec<u8> {
+    concat!(
+        "M-SEARCH * HTTP/1.1\r\n",
+        "HOST: 239.255.255.250:1900\r\n",
+        "MAN: \"ssdp:discover\"\r\n",
+        "MX: 1\r\n",
+        "ST: ssdp:all\r\n",
+        "\r\n"
+    )
+    .as_bytes()
+    .to_vec()
+}
+
+/// An ONC RPC NULL call to the portmapper (program 100000, version 2).
+fn rpcbind_null_call() -> Vec<u8> {
+    let mut packet: Vec<u8> = Vec::with_capacity(40);
+    packet.extend_from_slice(&rand::random::<u32>().to_be_bytes()); // xid
+    for word in [0u32, 2, 100_000, 2, 0, 0, 0, 0, 0] {
+        // call, rpc version, program, version, procedure, auth null, verifier null
+        packet.extend_from_slice(&word.to_be_bytes());
+    }
+    packet
+}
+
+/// A memcached `stats` command behind the UDP frame header.
+fn memcache_stats() -> Vec<u8> {
+    let mut packet: Vec<u8> = vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00];
+    packet.extend_from_slice(b"stats\r\n");
+    packet
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dns_parser::Packet;
+
+    #[test]
+    fn dns_payload_should_parse() {
+        let payload: Vec<u8> = udp_payload(53);
+        let packet = Packet::parse(&payload).expect("payload is not valid DNS");
+        assert_eq!(packet.questions.len(), 1);
+    }
+
+    #[test]
+    fn mdns_payload_should_ask_for_services() {
+        let payload: Vec<u8> = udp_payload(5353);
+        let packet = Packet::parse(&payload).expect("payload is not valid DNS");
+        assert_eq!(
+            packet.questions[0].qname.to_string(),
+            "_services._dns-sd._udp.local"
+        );
+    }
+
+    #[test]
+    fn netbios_payload_should_encode_wildcard() {
+        let payload: Vec<u8> = udp_payload(137);
+        assert_eq!(payload.len(), 50);
+        assert_eq!(&payload[13..15], b"CK");
+    }
+
+    #[test]
+    fn snmp_payload_length_should_match_header() {
+        let payload: Vec<u8> = udp_payload(161);
+        assert_eq!(payload[1] as usize, payload.len() - 2);
+    }
+
+    #[test]
+    fn unknown_port_should_get_empty_payload() {
+        assert!(udp_payload(40_000).is_empty());
+    }
+}
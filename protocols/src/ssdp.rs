@@ -0,0 +1,47 @@
+//! Recognition of **SSDP** (UPnP discovery) messages.
+//!
+//! UPnP devices multicast `NOTIFY` announcements and answer `M-SEARCH` requests,
+//! which makes them visible to anyone on the link without asking.
+
+pub const PORT: u16 = 1900;
+
+const START_LINES: [&[u8]; 3] = [
+    b"NOTIFY * HTTP/1.1",
+    b"M-SEARCH * HTTP/1.1",
+    b"HTTP/1.1 200",
+];
+
+/// Returns `true` if the payload is an SSDP announcement, search or search response.
+pub fn is_message(payload: &[u8]) -> bool {
+    START_LINES.iter().any(|line| payload.starts_with(line))
+}
+
+// ╔════════════════════════════════════════════╗
+// ║ ████████╗███████╗███████╗████████╗███████╗ ║
+// ║ ╚══██╔══╝██╔════╝██╔════╝╚══██╔══╝██╔════╝ ║
+// ║    ██║   █████╗  ███████╗   ██║   ███████╗ ║
+// ║    ██║   ██╔══╝  ╚════██║   ██║   ╚════██║ ║
+// ║    ██║   ███████╗███████║   ██║   ███████║ ║
+// ║    ╚═╝   ╚══════╝╚══════╝   ╚═╝   ╚══════╝ ║
+// ╚════════════════════════════════════════════╝
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notify_messages_are_recognized() {
+        assert!(is_message(
+            b"NOTIFY * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nNTS: ssdp:alive\r\n\r\n"
+        ));
+        assert!(is_message(
+            b"HTTP/1.1 200 OK\r\nST: upnp:rootdevice\r\n\r\n"
+        ));
+    }
+
+    #[test]
+    fn other_payloads_are_rejected() {
+        assert!(!is_message(b"GET / HTTP/1.1\r\n\r\n"));
+        assert!(!is_message(b""));
+    }
+}
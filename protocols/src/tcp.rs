@@ -6,6 +6,8 @@ use pnet::packet::tcp::{MutableTcpPacket, TcpOption, TcpPacket};
 const MIN_TCP_HDR_LEN: usize = 24;
 const WORD_IN_BYTES: usize = 4;
 const SYN_FLAG: u8 = 1 << 1;
+const RST_FLAG: u8 = 1 << 2;
+const ACK_FLAG: u8 = 1 << 4;
 
 pub fn create_packet(
     src_addr: &IpAddr,
@@ -13,6 +15,30 @@ pub fn create_packet(
     src_port: u16,
     dst_port: u16,
     seq_num: u32,
+) -> anyhow::Result<Vec<u8>> {
+    create_segment(src_addr, dst_addr, src_port, dst_port, SYN_FLAG, seq_num, 0)
+}
+
+/// A bare ACK, answered with a RST whose sequence number equals `ack_num` (RFC 9293).
+pub fn create_ack_packet(
+    src_addr: &IpAddr,
+    dst_addr: &IpAddr,
+    src_port: u16,
+    dst_port: u16,
+    ack_num: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let seq_num: u32 = rand::random();
+    create_segment(src_addr, dst_addr, src_port, dst_port, ACK_FLAG, seq_num, ack_num)
+}
+
+fn create_segment(
+    src_addr: &IpAddr,
+    dst_addr: &IpAddr,
+    src_port: u16,
+    dst_port: u16,
+    flags: u8,
+    seq_num: u32,
+    ack_num: u32,
 ) -> anyhow::Result<Vec<u8>> {
     let mut buffer: Vec<u8> = vec![0u8; MIN_TCP_HDR_LEN];
     {
@@ -22,8 +48,8 @@ pub fn create_packet(
         tcp.set_destination(dst_port);
         tcp.set_data_offset((MIN_TCP_HDR_LEN / WORD_IN_BYTES) as u8);
         tcp.set_sequence(seq_num);
-        tcp.set_acknowledgement(0);
-        tcp.set_flags(SYN_FLAG);
+        tcp.set_acknowledgement(ack_num);
+        tcp.set_flags(flags);
         tcp.set_window(1024);
         tcp.set_checksum(0);
 
@@ -51,3 +77,19 @@ pub fn create_packet(
 pub fn from_u8(bytes: &'_ [u8]) -> anyhow::Result<TcpPacket<'_>> {
     TcpPacket::new(bytes).context("truncated or invalid TCP packet")
 }
+
+/// Returns `true` if the segment carries both SYN and ACK (the answer of an open port).
+pub fn is_syn_ack(packet: &TcpPacket) -> bool {
+    let flags: u8 = packet.get_flags();
+    flags & (SYN_FLAG | ACK_FLAG) == (SYN_FLAG | ACK_FLAG)
+}
+
+/// Returns `true` if the segment resets the connection (the answer of a closed port).
+pub fn is_rst(packet: &TcpPacket) -> bool {
+    packet.get_flags() & RST_FLAG != 0
+}
+
+/// Returns `true` if the acknowledgement field of the segment is meaningful.
+pub fn has_ack(packet: &TcpPacket) -> bool {
+    packet.get_flags() & ACK_FLAG != 0
+}
@@ -1,11 +1,11 @@
 #![cfg(test)]
 use std::net::{IpAddr, Ipv4Addr};
-use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
 use zond_common::config::Config;
 use zond_common::models::host::Host;
 use zond_common::models::range::{IpCollection, Ipv4Range};
-use zond_core::scanner::{self, perform_discovery, STOP_SIGNAL};
+use zond_core::scanner::{self, perform_discovery, ScanState};
 
 use crate::utils::NetnsContext;
 
@@ -17,13 +17,14 @@ async fn test_discovery_single_loopback() {
         redact: false,
         quiet: 0,
         disable_input: true,
+        ..Default::default()
     };
 
     let mut targets: IpCollection = IpCollection::new();
     let localhost: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
     targets.add_single(localhost);
 
-    let result = scanner::perform_discovery(targets, &config).await;
+    let result = scanner::perform_discovery(targets, &config, ScanState::new(), None).await;
 
     assert!(result.is_ok(), "Discovery failed: {:?}", result.err());
     let hosts: Vec<Host> = result.unwrap();
@@ -46,6 +47,7 @@ async fn test_discovery_range_loopback() {
         redact: false,
         quiet: 0,
         disable_input: true,
+        ..Default::default()
     };
 
     let mut targets: IpCollection = IpCollection::new();
@@ -54,7 +56,7 @@ async fn test_discovery_range_loopback() {
     let range: Ipv4Range = Ipv4Range::new(start, end);
     targets.add_range(range);
 
-    let result = scanner::perform_discovery(targets, &cfg).await;
+    let result = scanner::perform_discovery(targets, &cfg, ScanState::new(), None).await;
 
     assert!(result.is_ok(), "Discovery failed: {:?}", result.is_err());
     let hosts: Vec<Host> = result.unwrap();
@@ -80,15 +82,17 @@ async fn test_stop_signal_aborts() {
         redact: false,
         quiet: 0,
         disable_input: true,
+        ..Default::default()
     };
 
-    STOP_SIGNAL.store(false, Ordering::Relaxed);
+    let state: Arc<ScanState> = ScanState::new();
+    let state_c: Arc<ScanState> = state.clone();
 
-    let handle = tokio::spawn(async move { perform_discovery(targets, &cfg).await });
+    let handle = tokio::spawn(async move { perform_discovery(targets, &cfg, state_c, None).await });
 
     tokio::time::sleep(Duration::from_millis(10)).await;
 
-    STOP_SIGNAL.store(true, Ordering::Relaxed);
+    state.stop();
 
     let result = tokio::time::timeout(Duration::from_millis(50), handle).await;
 
@@ -114,12 +118,13 @@ async fn test_privileged_discovery_netns() {
         redact: false,
         quiet: 0,
         disable_input: true,
+        ..Default::default()
     };
 
     let mut collection: IpCollection = IpCollection::new();
     collection.add_single(target_ip);
 
-    let result = scanner::perform_discovery(collection, &config).await;
+    let result = scanner::perform_discovery(collection, &config, ScanState::new(), None).await;
 
     match result {
         Ok(hosts) => {